@@ -49,7 +49,7 @@ fn main() {
         // Note: both name and size are different.
         child1: Child1 {
             name: "Ralph",
-            size: usize::max_value(),
+            size: usize::MAX,
         },
         others: vec![
             Other::Prince,
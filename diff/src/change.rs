@@ -0,0 +1,69 @@
+//! Keeping track of how much of a value differs while rendering it.
+//!
+//! Renderers that break values down as they go, and then need to know whether
+//! a value was the same or different all the way through, have their `Diff`
+//! impls return a [`Change`], and total up the parts of compound values with a
+//! [`Tally`].
+
+/// How much of a value differs, as found while rendering it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Change {
+    Unchanged,
+    /// Some parts differ, and others don't.
+    Partly,
+    /// Every part differs, as in [`all_different`].
+    ///
+    /// [`all_different`]: ../fn.all_different.html
+    Entirely,
+}
+
+/// Totals up the parts of a struct, tuple, sequence, map or set.
+pub(crate) struct Tally {
+    parts: usize,
+    changed: bool,
+    all: bool,
+}
+
+impl Tally {
+    pub(crate) fn new() -> Self {
+        Tally {
+            parts: 0,
+            changed: false,
+            all: true,
+        }
+    }
+
+    /// Notes a part that's on both sides.
+    pub(crate) fn part(&mut self, change: Change) {
+        self.parts += 1;
+        if change == Change::Unchanged {
+            self.all = false;
+        } else {
+            self.changed = true;
+        }
+    }
+
+    /// Notes a part that's only on one side. Extra sequence elements keep the
+    /// sequence from being entirely different; extra map or set entries
+    /// don't, matching [`all_different`].
+    ///
+    /// [`all_different`]: ../fn.all_different.html
+    pub(crate) fn one_sided(&mut self, partial: bool) {
+        self.parts += 1;
+        self.changed = true;
+        if partial {
+            self.all = false;
+        }
+    }
+
+    /// How much of the whole value differs.
+    pub(crate) fn change(&self) -> Change {
+        if !self.changed {
+            Change::Unchanged
+        } else if self.all && self.parts > 0 {
+            Change::Entirely
+        } else {
+            Change::Partly
+        }
+    }
+}
@@ -0,0 +1,859 @@
+//! Differs built out of other differs.
+//!
+//! [`Tee`] feeds a diff to two differs at once, so that, for example, the
+//! differences between two values can be recorded and counted by one call to
+//! `Diff::diff`. [`MapOk`] and [`MapErr`] transform what a differ returns,
+//! which is handy for making the results of the differs given to `Tee` fit
+//! together.
+//!
+//! ```
+//! use visit_diff::combinators::{MapOk, Tee};
+//! use visit_diff::constant::Const;
+//! use visit_diff::record::{Value, ValueRecorder};
+//! use visit_diff::Diff;
+//! use void::ResultVoidExt;
+//!
+//! let out = Tee(ValueRecorder, MapOk(Const(2), |n| n * 10));
+//! let (value, n) = Diff::diff(&1, &2, out).void_unwrap();
+//! assert_eq!(value, Value::Difference("1".into(), "2".into()));
+//! assert_eq!(n, 20);
+//! ```
+//!
+//! Each of these types is its own sub-differ: for instance, `Tee` of two
+//! `StructDiffer`s is a `StructDiffer`.
+//!
+//! The differs behind the crate's own functions can be combined this way
+//! too: [`difference_detector`] and [`all_different_detector`] behind
+//! [`any_difference`] and [`all_different`], [`debug_differ`] behind
+//! [`debug_diff`], and [`ValueRecorder`] behind [`record_diff`].
+//!
+//! `Tee` takes the values apart just once, feeding both differs as it goes,
+//! rather than once per differ, as separate calls to `Diff::diff` would.
+//!
+//! [`Tee`]: struct.Tee.html
+//! [`MapOk`]: struct.MapOk.html
+//! [`MapErr`]: struct.MapErr.html
+//! [`difference_detector`]: ../fn.difference_detector.html
+//! [`all_different_detector`]: ../fn.all_different_detector.html
+//! [`any_difference`]: ../fn.any_difference.html
+//! [`all_different`]: ../fn.all_different.html
+//! [`debug_differ`]: ../fn.debug_differ.html
+//! [`debug_diff`]: ../fn.debug_diff.html
+//! [`ValueRecorder`]: ../record/struct.ValueRecorder.html
+//! [`record_diff`]: ../record/fn.record_diff.html
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+use void::{ResultVoidExt, Void};
+
+use crate::{
+    Diff, DiffOptions, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer,
+    TupleDiffer,
+};
+
+/// A differ that passes everything on to two differs, and returns both of
+/// their results.
+///
+/// Both differs see the same calls in the same order, so they describe the
+/// same diff, and the values are taken apart just once for both of them:
+/// each nested part is diffed by one traversal, which feeds the two differs
+/// that the `Tee`'s differs hand the part to. A part that one of them diffs
+/// more than once, or that only one of them diffs, is diffed again for that
+/// one alone, and parts only in one of the values are handed to each
+/// separately.
+///
+/// The two differs must have the same `Err` type, which can be arranged with
+/// [`MapErr`]. If either differ fails, so does the `Tee`, with the first
+/// differ's error if both fail. [`Differ::options`] are those of the first
+/// differ.
+///
+/// [`MapErr`]: struct.MapErr.html
+/// [`Differ::options`]: ../trait.Differ.html#method.options
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Tee<D1, D2>(pub D1, pub D2);
+
+/// Combines the results of the two halves of a `Tee`.
+fn tee<A, B, E>(a: Result<A, E>, b: Result<B, E>) -> Result<(A, B), E> {
+    Ok((a?, b?))
+}
+
+/// Two differs run together on the parts of values a `Tee` takes apart.
+///
+/// Unlike a `Tee`, the differs can have different `Err` types, since they're
+/// only whatever the `Tee`'s differs hand nested parts to, and their results
+/// are handed back separately.
+struct Both<D1, D2>(D1, D2);
+
+/// Combines the results of the two halves of a `Both`.
+fn both<A, B>(a: A, b: B) -> Result<(A, B), Void> {
+    Ok((a, b))
+}
+
+/// Implements `Differ` and the sub-differ traits for `Tee` and `Both`, which
+/// differ only in their results, and so in whether their differs need the
+/// same `Err` type.
+macro_rules! impl_pair {
+    ($pair:ident, [$($same:tt)*], $ok:ty, $err:ty, $finish:ident) => {
+        impl<A, B> Differ for $pair<A, B>
+        where
+            A: Differ,
+            B: Differ<$($same)*>,
+        {
+            type Ok = $ok;
+            type Err = $err;
+
+            type StructDiffer = $pair<A::StructDiffer, B::StructDiffer>;
+            type StructVariantDiffer =
+                $pair<A::StructVariantDiffer, B::StructVariantDiffer>;
+            type TupleDiffer = $pair<A::TupleDiffer, B::TupleDiffer>;
+            type TupleVariantDiffer =
+                $pair<A::TupleVariantDiffer, B::TupleVariantDiffer>;
+            type SeqDiffer = $pair<A::SeqDiffer, B::SeqDiffer>;
+            type MapDiffer = $pair<A::MapDiffer, B::MapDiffer>;
+            type SetDiffer = $pair<A::SetDiffer, B::SetDiffer>;
+
+            fn options(&self) -> &DiffOptions {
+                self.0.options()
+            }
+
+            fn difference(
+                self,
+                a: &dyn Debug,
+                b: &dyn Debug,
+            ) -> Result<Self::Ok, Self::Err> {
+                $finish(self.0.difference(a, b), self.1.difference(a, b))
+            }
+
+            fn same(
+                self,
+                a: &dyn Debug,
+                b: &dyn Debug,
+            ) -> Result<Self::Ok, Self::Err> {
+                $finish(self.0.same(a, b), self.1.same(a, b))
+            }
+
+            fn diff_newtype<T: ?Sized>(
+                self,
+                ty: &'static str,
+                a: &T,
+                b: &T,
+            ) -> Result<Self::Ok, Self::Err>
+            where
+                T: Diff,
+            {
+                let $pair(d1, d2) = self;
+                let (r1, r2) = lockstep(
+                    a,
+                    b,
+                    |a, b| d1.diff_newtype(ty, a, b),
+                    Newtype(d2, ty),
+                );
+                $finish(r1, r2)
+            }
+
+            fn begin_struct(self, ty: &'static str) -> Self::StructDiffer {
+                $pair(self.0.begin_struct(ty), self.1.begin_struct(ty))
+            }
+
+            fn begin_struct_variant(
+                self,
+                ty: &'static str,
+                var: &'static str,
+            ) -> Self::StructVariantDiffer {
+                $pair(
+                    self.0.begin_struct_variant(ty, var),
+                    self.1.begin_struct_variant(ty, var),
+                )
+            }
+
+            fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+                $pair(self.0.begin_tuple(ty), self.1.begin_tuple(ty))
+            }
+
+            fn begin_tuple_variant(
+                self,
+                ty: &'static str,
+                var: &'static str,
+            ) -> Self::TupleVariantDiffer {
+                $pair(
+                    self.0.begin_tuple_variant(ty, var),
+                    self.1.begin_tuple_variant(ty, var),
+                )
+            }
+
+            fn begin_seq(self) -> Self::SeqDiffer {
+                $pair(self.0.begin_seq(), self.1.begin_seq())
+            }
+
+            fn begin_map(self) -> Self::MapDiffer {
+                $pair(self.0.begin_map(), self.1.begin_map())
+            }
+
+            fn begin_set(self) -> Self::SetDiffer {
+                $pair(self.0.begin_set(), self.1.begin_set())
+            }
+        }
+
+        impl<A, B> StructDiffer for $pair<A, B>
+        where
+            A: StructDiffer,
+            B: StructDiffer<$($same)*>,
+        {
+            type Ok = $ok;
+            type Err = $err;
+
+            fn diff_field<T: ?Sized>(
+                &mut self,
+                name: &'static str,
+                a: &T,
+                b: &T,
+            ) where
+                T: Diff,
+            {
+                let $pair(s1, s2) = self;
+                lockstep(a, b, |a, b| s1.diff_field(name, a, b), Field(s2, name));
+            }
+
+            fn skip_field<T: ?Sized>(&mut self, name: &'static str) {
+                self.0.skip_field::<T>(name);
+                self.1.skip_field::<T>(name);
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                $finish(self.0.end(), self.1.end())
+            }
+
+            fn end_non_exhaustive(self) -> Result<Self::Ok, Self::Err> {
+                $finish(self.0.end_non_exhaustive(), self.1.end_non_exhaustive())
+            }
+        }
+
+        impl<A, B> TupleDiffer for $pair<A, B>
+        where
+            A: TupleDiffer,
+            B: TupleDiffer<$($same)*>,
+        {
+            type Ok = $ok;
+            type Err = $err;
+
+            fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+            where
+                T: Diff,
+            {
+                let $pair(s1, s2) = self;
+                lockstep(a, b, |a, b| s1.diff_field(a, b), TupleField(s2));
+            }
+
+            fn skip_field<T: ?Sized>(&mut self) {
+                self.0.skip_field::<T>();
+                self.1.skip_field::<T>();
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                $finish(self.0.end(), self.1.end())
+            }
+        }
+
+        impl<A, B> SeqDiffer for $pair<A, B>
+        where
+            A: SeqDiffer,
+            B: SeqDiffer<$($same)*>,
+        {
+            type Ok = $ok;
+            type Err = $err;
+
+            fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+            where
+                T: Diff,
+            {
+                let $pair(s1, s2) = self;
+                lockstep(a, b, |a, b| s1.diff_element(a, b), Element(s2));
+            }
+
+            fn left_excess<T: ?Sized>(&mut self, a: &T)
+            where
+                T: Diff,
+            {
+                self.0.left_excess(a);
+                self.1.left_excess(a);
+            }
+
+            fn right_excess<T: ?Sized>(&mut self, b: &T)
+            where
+                T: Diff,
+            {
+                self.0.right_excess(b);
+                self.1.right_excess(b);
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                $finish(self.0.end(), self.1.end())
+            }
+        }
+
+        impl<A, B> MapDiffer for $pair<A, B>
+        where
+            A: MapDiffer,
+            B: MapDiffer<$($same)*>,
+        {
+            type Ok = $ok;
+            type Err = $err;
+
+            fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+            where
+                K: ?Sized + Debug,
+                V: ?Sized + Diff,
+            {
+                let $pair(s1, s2) = self;
+                lockstep(a, b, |a, b| s1.diff_entry(key, a, b), Entry(s2, key));
+            }
+
+            fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+            where
+                K: ?Sized + Debug,
+                V: ?Sized + Diff,
+            {
+                self.0.only_in_left(key, a);
+                self.1.only_in_left(key, a);
+            }
+
+            fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+            where
+                K: ?Sized + Debug,
+                V: ?Sized + Diff,
+            {
+                self.0.only_in_right(key, b);
+                self.1.only_in_right(key, b);
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                $finish(self.0.end(), self.1.end())
+            }
+        }
+
+        impl<A, B> SetDiffer for $pair<A, B>
+        where
+            A: SetDiffer,
+            B: SetDiffer<$($same)*>,
+        {
+            type Ok = $ok;
+            type Err = $err;
+
+            fn diff_equal<V>(&mut self, a: &V, b: &V)
+            where
+                V: ?Sized + Diff,
+            {
+                let $pair(s1, s2) = self;
+                lockstep(a, b, |a, b| s1.diff_equal(a, b), Equal(s2));
+            }
+
+            fn only_in_left<V>(&mut self, a: &V)
+            where
+                V: ?Sized + Diff,
+            {
+                self.0.only_in_left(a);
+                self.1.only_in_left(a);
+            }
+
+            fn only_in_right<V>(&mut self, b: &V)
+            where
+                V: ?Sized + Diff,
+            {
+                self.0.only_in_right(b);
+                self.1.only_in_right(b);
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                $finish(self.0.end(), self.1.end())
+            }
+        }
+    };
+}
+
+impl_pair!(Tee, [Err = A::Err], (A::Ok, B::Ok), A::Err, tee);
+impl_pair!(
+    Both,
+    [],
+    (Result<A::Ok, A::Err>, Result<B::Ok, B::Err>),
+    Void,
+    both
+);
+
+/// Hands a pair of values to the second of two differs or sub-differs fed in
+/// lockstep, in the way the first was handed them.
+trait Feed {
+    type Out;
+
+    fn feed<T: ?Sized + Diff>(self, a: &T, b: &T) -> Self::Out;
+}
+
+/// Feeds a newtype's contents to a differ.
+struct Newtype<D>(D, &'static str);
+
+impl<D: Differ> Feed for Newtype<D> {
+    type Out = Result<D::Ok, D::Err>;
+
+    fn feed<T: ?Sized + Diff>(self, a: &T, b: &T) -> Self::Out {
+        self.0.diff_newtype(self.1, a, b)
+    }
+}
+
+/// Feeds a field to a `StructDiffer`.
+struct Field<'s, S>(&'s mut S, &'static str);
+
+impl<'s, S: StructDiffer> Feed for Field<'s, S> {
+    type Out = ();
+
+    fn feed<T: ?Sized + Diff>(self, a: &T, b: &T) {
+        self.0.diff_field(self.1, a, b)
+    }
+}
+
+/// Feeds a field to a `TupleDiffer`.
+struct TupleField<'s, S>(&'s mut S);
+
+impl<'s, S: TupleDiffer> Feed for TupleField<'s, S> {
+    type Out = ();
+
+    fn feed<T: ?Sized + Diff>(self, a: &T, b: &T) {
+        self.0.diff_field(a, b)
+    }
+}
+
+/// Feeds an element to a `SeqDiffer`.
+struct Element<'s, S>(&'s mut S);
+
+impl<'s, S: SeqDiffer> Feed for Element<'s, S> {
+    type Out = ();
+
+    fn feed<T: ?Sized + Diff>(self, a: &T, b: &T) {
+        self.0.diff_element(a, b)
+    }
+}
+
+/// Feeds an entry's values to a `MapDiffer`.
+struct Entry<'s, 'k, S, K: ?Sized>(&'s mut S, &'k K);
+
+impl<'s, 'k, S: MapDiffer, K: ?Sized + Debug> Feed for Entry<'s, 'k, S, K> {
+    type Out = ();
+
+    fn feed<T: ?Sized + Diff>(self, a: &T, b: &T) {
+        self.0.diff_entry(self.1, a, b)
+    }
+}
+
+/// Feeds a pair of equal elements to a `SetDiffer`.
+struct Equal<'s, S>(&'s mut S);
+
+impl<'s, S: SetDiffer> Feed for Equal<'s, S> {
+    type Out = ();
+
+    fn feed<T: ?Sized + Diff>(self, a: &T, b: &T) {
+        self.0.diff_equal(a, b)
+    }
+}
+
+/// Hands `a` and `b` to two differs or sub-differs: to the first through
+/// `first`, and to the second through `second`. If the first diffs them, the
+/// second is handed them from within that diff, so that the differs the two
+/// of them diff them with can be run together, as a `Both`.
+fn lockstep<T, F, R>(
+    a: &T,
+    b: &T,
+    first: impl FnOnce(&First<T, F>, &First<T, F>) -> R,
+    second: F,
+) -> (R, F::Out)
+where
+    T: ?Sized + Diff,
+    F: Feed,
+{
+    let feed = Cell::new(Some(second));
+    let out = Cell::new(None);
+    let r = first(
+        &First {
+            value: a,
+            feed: &feed,
+            out: &out,
+        },
+        &First {
+            value: b,
+            feed: &feed,
+            out: &out,
+        },
+    );
+    match (feed.into_inner(), out.into_inner()) {
+        (_, Some(out)) => (r, out),
+        // The first didn't diff them, so the second is handed them directly.
+        (Some(second), None) => (r, second.feed(a, b)),
+        (None, None) => unreachable!("values handed on without a result"),
+    }
+}
+
+/// A value handed to the first of two differs by [`lockstep`], which hands it
+/// on to the second when diffed.
+///
+/// [`lockstep`]: fn.lockstep.html
+struct First<'a, T: ?Sized, F: Feed> {
+    value: &'a T,
+    /// Hands the values to the second differ, until they're diffed.
+    feed: &'a Cell<Option<F>>,
+    /// What handing the values on returned.
+    out: &'a Cell<Option<F::Out>>,
+}
+
+impl<'a, T, F> Diff for First<'a, T, F>
+where
+    T: ?Sized + Diff,
+    F: Feed,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let feed = match a.feed.take() {
+            Some(feed) => feed,
+            // They've been handed on already, so this diff is run alone.
+            None => return Diff::diff(a.value, b.value, out),
+        };
+        let first = Cell::new(Some(out));
+        let result = Cell::new(None);
+        a.out.set(Some(feed.feed(
+            &Second {
+                value: a.value,
+                first: &first,
+                result: &result,
+            },
+            &Second {
+                value: b.value,
+                first: &first,
+                result: &result,
+            },
+        )));
+        match (first.into_inner(), result.into_inner()) {
+            (_, Some(result)) => result,
+            // The second didn't diff them, so this diff is run alone.
+            (Some(out), None) => Diff::diff(a.value, b.value, out),
+            (None, None) => unreachable!("differ taken without a result"),
+        }
+    }
+}
+
+impl<'a, T: ?Sized + Debug, F: Feed> Debug for First<'a, T, F> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// A value handed to the second of two differs by [`lockstep`], holding the
+/// differ the first is diffing it with, to run together with the second's.
+///
+/// [`lockstep`]: fn.lockstep.html
+struct Second<'a, T: ?Sized, D: Differ> {
+    value: &'a T,
+    first: &'a Cell<Option<D>>,
+    result: &'a Cell<Option<Result<D::Ok, D::Err>>>,
+}
+
+impl<'a, T, D1> Diff for Second<'a, T, D1>
+where
+    T: ?Sized + Diff,
+    D1: Differ,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        match a.first.take() {
+            Some(first) => {
+                let (r1, r2) = Diff::diff(a.value, b.value, Both(first, out))
+                    .void_unwrap();
+                a.result.set(Some(r1));
+                r2
+            }
+            // They've been diffed together already, so this diff is run
+            // alone.
+            None => Diff::diff(a.value, b.value, out),
+        }
+    }
+}
+
+impl<'a, T: ?Sized + Debug, D: Differ> Debug for Second<'a, T, D> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// A differ that applies a function to the successful result of another.
+///
+/// Only the result of the whole diff is passed to the function, not those of
+/// nested values.
+#[derive(Copy, Clone, Debug)]
+pub struct MapOk<D, F>(pub D, pub F);
+
+/// A differ that applies a function to the error returned by another.
+#[derive(Copy, Clone, Debug)]
+pub struct MapErr<D, F>(pub D, pub F);
+
+/// Implements `Differ` and the sub-differ traits for `MapOk` and `MapErr`,
+/// which differ only in which of the associated types `Ok` and `Err` is
+/// mapped, and how they finish.
+macro_rules! impl_map {
+    ($map:ident, $mapped:ident, $kept:ident, $out:ident, $finish:ident) => {
+        impl<D, F, $out> Differ for $map<D, F>
+        where
+            D: Differ,
+            F: FnOnce(D::$mapped) -> $out,
+        {
+            type $mapped = $out;
+            type $kept = D::$kept;
+
+            type StructDiffer = $map<D::StructDiffer, F>;
+            type StructVariantDiffer = $map<D::StructVariantDiffer, F>;
+            type TupleDiffer = $map<D::TupleDiffer, F>;
+            type TupleVariantDiffer = $map<D::TupleVariantDiffer, F>;
+            type SeqDiffer = $map<D::SeqDiffer, F>;
+            type MapDiffer = $map<D::MapDiffer, F>;
+            type SetDiffer = $map<D::SetDiffer, F>;
+
+            fn options(&self) -> &DiffOptions {
+                self.0.options()
+            }
+
+            fn difference(
+                self,
+                a: &dyn Debug,
+                b: &dyn Debug,
+            ) -> Result<Self::Ok, Self::Err> {
+                self.0.difference(a, b).$finish(self.1)
+            }
+
+            fn same(
+                self,
+                a: &dyn Debug,
+                b: &dyn Debug,
+            ) -> Result<Self::Ok, Self::Err> {
+                self.0.same(a, b).$finish(self.1)
+            }
+
+            fn diff_newtype<T: ?Sized>(
+                self,
+                ty: &'static str,
+                a: &T,
+                b: &T,
+            ) -> Result<Self::Ok, Self::Err>
+            where
+                T: Diff,
+            {
+                self.0.diff_newtype(ty, a, b).$finish(self.1)
+            }
+
+            fn begin_struct(self, ty: &'static str) -> Self::StructDiffer {
+                $map(self.0.begin_struct(ty), self.1)
+            }
+
+            fn begin_struct_variant(
+                self,
+                ty: &'static str,
+                var: &'static str,
+            ) -> Self::StructVariantDiffer {
+                $map(self.0.begin_struct_variant(ty, var), self.1)
+            }
+
+            fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+                $map(self.0.begin_tuple(ty), self.1)
+            }
+
+            fn begin_tuple_variant(
+                self,
+                ty: &'static str,
+                var: &'static str,
+            ) -> Self::TupleVariantDiffer {
+                $map(self.0.begin_tuple_variant(ty, var), self.1)
+            }
+
+            fn begin_seq(self) -> Self::SeqDiffer {
+                $map(self.0.begin_seq(), self.1)
+            }
+
+            fn begin_map(self) -> Self::MapDiffer {
+                $map(self.0.begin_map(), self.1)
+            }
+
+            fn begin_set(self) -> Self::SetDiffer {
+                $map(self.0.begin_set(), self.1)
+            }
+        }
+
+        impl<S, F, $out> StructDiffer for $map<S, F>
+        where
+            S: StructDiffer,
+            F: FnOnce(S::$mapped) -> $out,
+        {
+            type $mapped = $out;
+            type $kept = S::$kept;
+
+            fn diff_field<T: ?Sized>(
+                &mut self,
+                name: &'static str,
+                a: &T,
+                b: &T,
+            ) where
+                T: Diff,
+            {
+                self.0.diff_field(name, a, b)
+            }
+
+            fn skip_field<T: ?Sized>(&mut self, name: &'static str) {
+                self.0.skip_field::<T>(name)
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                self.0.end().$finish(self.1)
+            }
+
+            fn end_non_exhaustive(self) -> Result<Self::Ok, Self::Err> {
+                self.0.end_non_exhaustive().$finish(self.1)
+            }
+        }
+
+        impl<S, F, $out> TupleDiffer for $map<S, F>
+        where
+            S: TupleDiffer,
+            F: FnOnce(S::$mapped) -> $out,
+        {
+            type $mapped = $out;
+            type $kept = S::$kept;
+
+            fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+            where
+                T: Diff,
+            {
+                self.0.diff_field(a, b)
+            }
+
+            fn skip_field<T: ?Sized>(&mut self) {
+                self.0.skip_field::<T>()
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                self.0.end().$finish(self.1)
+            }
+        }
+
+        impl<S, F, $out> SeqDiffer for $map<S, F>
+        where
+            S: SeqDiffer,
+            F: FnOnce(S::$mapped) -> $out,
+        {
+            type $mapped = $out;
+            type $kept = S::$kept;
+
+            fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+            where
+                T: Diff,
+            {
+                self.0.diff_element(a, b)
+            }
+
+            fn left_excess<T: ?Sized>(&mut self, a: &T)
+            where
+                T: Diff,
+            {
+                self.0.left_excess(a)
+            }
+
+            fn right_excess<T: ?Sized>(&mut self, b: &T)
+            where
+                T: Diff,
+            {
+                self.0.right_excess(b)
+            }
+
+            fn diff_elements<T, I>(&mut self, a: I, b: I)
+            where
+                T: Diff,
+                I: IntoIterator<Item = T>,
+            {
+                self.0.diff_elements(a, b)
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                self.0.end().$finish(self.1)
+            }
+        }
+
+        impl<S, F, $out> MapDiffer for $map<S, F>
+        where
+            S: MapDiffer,
+            F: FnOnce(S::$mapped) -> $out,
+        {
+            type $mapped = $out;
+            type $kept = S::$kept;
+
+            fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+            where
+                K: ?Sized + Debug,
+                V: ?Sized + Diff,
+            {
+                self.0.diff_entry(key, a, b)
+            }
+
+            fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+            where
+                K: ?Sized + Debug,
+                V: ?Sized + Diff,
+            {
+                self.0.only_in_left(key, a)
+            }
+
+            fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+            where
+                K: ?Sized + Debug,
+                V: ?Sized + Diff,
+            {
+                self.0.only_in_right(key, b)
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                self.0.end().$finish(self.1)
+            }
+        }
+
+        impl<S, F, $out> SetDiffer for $map<S, F>
+        where
+            S: SetDiffer,
+            F: FnOnce(S::$mapped) -> $out,
+        {
+            type $mapped = $out;
+            type $kept = S::$kept;
+
+            fn diff_equal<V>(&mut self, a: &V, b: &V)
+            where
+                V: ?Sized + Diff,
+            {
+                self.0.diff_equal(a, b)
+            }
+
+            fn only_in_left<V>(&mut self, a: &V)
+            where
+                V: ?Sized + Diff,
+            {
+                self.0.only_in_left(a)
+            }
+
+            fn only_in_right<V>(&mut self, b: &V)
+            where
+                V: ?Sized + Diff,
+            {
+                self.0.only_in_right(b)
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Err> {
+                self.0.end().$finish(self.1)
+            }
+        }
+    };
+}
+
+impl_map!(MapOk, Ok, Err, O, map);
+impl_map!(MapErr, Err, Ok, E, map_err);
@@ -39,11 +39,15 @@ impl<R> Differ for Const<R> {
     type MapDiffer = Self;
     type SetDiffer = Self;
 
-    fn difference(self, _: &Debug, _: &Debug) -> Result<Self::Ok, Self::Err> {
+    fn difference(
+        self,
+        _: &dyn Debug,
+        _: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
         Ok(self.0)
     }
 
-    fn same(self, _: &Debug, _: &Debug) -> Result<Self::Ok, Self::Err> {
+    fn same(self, _: &dyn Debug, _: &dyn Debug) -> Result<Self::Ok, Self::Err> {
         Ok(self.0)
     }
 
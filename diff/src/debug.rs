@@ -1,14 +1,21 @@
 //! Report differences using `Debug` and `Formatter`.
 
 use crate::{
-    Diff, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer, TupleDiffer,
+    debug_diff_with, Diff, DiffOptions, Differ, MapDiffer, SeqDiffer,
+    SetDiffer, StructDiffer, TupleDiffer,
 };
 use core::fmt::Debug;
 
 use super::detect::all_different;
 
 /// Adapts a `core::fmt::Formatter` into a `Differ`.
-struct DebugDiffer<'a, 'b>(&'a mut core::fmt::Formatter<'b>);
+///
+/// With `whole` set, only the left-hand values are shown, which is how
+/// [`Whole`] prints a value without any `DIFF`s.
+struct DebugDiffer<'a, 'b> {
+    fmt: &'a mut core::fmt::Formatter<'b>,
+    whole: bool,
+}
 
 impl<'a, 'b> Differ for DebugDiffer<'a, 'b> {
     type Ok = ();
@@ -22,12 +29,20 @@ impl<'a, 'b> Differ for DebugDiffer<'a, 'b> {
     type MapDiffer = DebugMapDiff<'a, 'b>;
     type SetDiffer = DebugSetDiff<'a, 'b>;
 
-    fn difference(self, a: &Debug, b: &Debug) -> Result<Self::Ok, Self::Err> {
-        DIFF { L: a, R: b }.fmt(self.0)
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
+        if self.whole {
+            a.fmt(self.fmt)
+        } else {
+            DIFF { L: a, R: b }.fmt(self.fmt)
+        }
     }
 
-    fn same(self, a: &Debug, _: &Debug) -> Result<Self::Ok, Self::Err> {
-        a.fmt(self.0)
+    fn same(self, a: &dyn Debug, _: &dyn Debug) -> Result<Self::Ok, Self::Err> {
+        a.fmt(self.fmt)
     }
 
     fn diff_newtype<T: ?Sized>(
@@ -39,11 +54,12 @@ impl<'a, 'b> Differ for DebugDiffer<'a, 'b> {
     where
         T: Diff,
     {
-        self.0.debug_tuple(name).field(&DebugDiff(a, b)).finish()
+        let part = Part(a, b, self.whole);
+        self.fmt.debug_tuple(name).field(&part).finish()
     }
 
     fn begin_struct(self, name: &'static str) -> Self::StructDiffer {
-        DebugStructDiff(Ok(self.0.debug_struct(name)))
+        DebugStructDiff(Ok(self.fmt.debug_struct(name)), self.whole)
     }
 
     fn begin_struct_variant(
@@ -51,11 +67,11 @@ impl<'a, 'b> Differ for DebugDiffer<'a, 'b> {
         _: &'static str,
         v: &'static str,
     ) -> Self::StructVariantDiffer {
-        DebugStructDiff(Ok(self.0.debug_struct(v)))
+        DebugStructDiff(Ok(self.fmt.debug_struct(v)), self.whole)
     }
 
     fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
-        DebugTupleDiff(Ok(self.0.debug_tuple(ty)))
+        DebugTupleDiff(Ok(self.fmt.debug_tuple(ty)), self.whole)
     }
 
     fn begin_tuple_variant(
@@ -63,25 +79,27 @@ impl<'a, 'b> Differ for DebugDiffer<'a, 'b> {
         _: &'static str,
         v: &'static str,
     ) -> Self::TupleDiffer {
-        DebugTupleDiff(Ok(self.0.debug_tuple(v)))
+        DebugTupleDiff(Ok(self.fmt.debug_tuple(v)), self.whole)
     }
 
     fn begin_seq(self) -> Self::SeqDiffer {
-        DebugSeqDiff(Ok(self.0.debug_list()))
+        DebugSeqDiff(Ok(self.fmt.debug_list()), self.whole)
     }
 
     fn begin_map(self) -> Self::MapDiffer {
-        DebugMapDiff(Ok(self.0.debug_map()))
+        DebugMapDiff(Ok(self.fmt.debug_map()), self.whole)
     }
 
     fn begin_set(self) -> Self::SetDiffer {
-        DebugSetDiff(Ok(self.0.debug_set()))
+        DebugSetDiff(Ok(self.fmt.debug_set()), self.whole)
     }
 }
 
 #[derive(Debug)]
 #[allow(non_camel_case_types)]
 #[allow(non_snake_case)]
+#[allow(dead_code)] // fields are only read by the derived Debug impl
+#[allow(clippy::upper_case_acronyms)]
 struct DIFF<T, S> {
     L: T,
     R: S,
@@ -97,6 +115,7 @@ impl core::fmt::Debug for Missing {
 
 struct DebugStructDiff<'a, 'b>(
     Result<core::fmt::DebugStruct<'a, 'b>, core::fmt::Error>,
+    bool,
 );
 
 impl<'a, 'b> StructDiffer for DebugStructDiff<'a, 'b> {
@@ -108,17 +127,22 @@ impl<'a, 'b> StructDiffer for DebugStructDiff<'a, 'b> {
         T: Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.field(name, &DebugDiff(a, b) as &dyn core::fmt::Debug);
+            f.field(name, &Part(a, b, self.1));
         }
     }
 
     fn end(self) -> Result<Self::Ok, Self::Err> {
         self.0.and_then(|mut f| f.finish())
     }
+
+    fn end_non_exhaustive(self) -> Result<Self::Ok, Self::Err> {
+        self.0.and_then(|mut f| f.finish_non_exhaustive())
+    }
 }
 
 struct DebugTupleDiff<'a, 'b>(
     Result<core::fmt::DebugTuple<'a, 'b>, core::fmt::Error>,
+    bool,
 );
 
 impl<'a, 'b> TupleDiffer for DebugTupleDiff<'a, 'b> {
@@ -130,7 +154,7 @@ impl<'a, 'b> TupleDiffer for DebugTupleDiff<'a, 'b> {
         T: Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.field(&DebugDiff(a, b) as &dyn core::fmt::Debug);
+            f.field(&Part(a, b, self.1));
         }
     }
 
@@ -141,6 +165,7 @@ impl<'a, 'b> TupleDiffer for DebugTupleDiff<'a, 'b> {
 
 struct DebugSeqDiff<'a, 'b>(
     Result<core::fmt::DebugList<'a, 'b>, core::fmt::Error>,
+    bool,
 );
 
 impl<'a, 'b> SeqDiffer for DebugSeqDiff<'a, 'b> {
@@ -152,7 +177,7 @@ impl<'a, 'b> SeqDiffer for DebugSeqDiff<'a, 'b> {
         T: Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&DebugDiff(a, b) as &dyn core::fmt::Debug);
+            f.entry(&Part(a, b, self.1));
         }
     }
 
@@ -161,7 +186,7 @@ impl<'a, 'b> SeqDiffer for DebugSeqDiff<'a, 'b> {
         T: Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&DIFF { L: a, R: Missing });
+            f.entry(&DIFF { L: Whole(a), R: Missing });
         }
     }
 
@@ -170,7 +195,7 @@ impl<'a, 'b> SeqDiffer for DebugSeqDiff<'a, 'b> {
         T: Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&DIFF { L: Missing, R: b });
+            f.entry(&DIFF { L: Missing, R: Whole(b) });
         }
     }
 
@@ -181,6 +206,7 @@ impl<'a, 'b> SeqDiffer for DebugSeqDiff<'a, 'b> {
 
 struct DebugSetDiff<'a, 'b>(
     Result<core::fmt::DebugSet<'a, 'b>, core::fmt::Error>,
+    bool,
 );
 
 impl<'a, 'b> SetDiffer for DebugSetDiff<'a, 'b> {
@@ -192,7 +218,7 @@ impl<'a, 'b> SetDiffer for DebugSetDiff<'a, 'b> {
         V: ?Sized + Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&DebugDiff(a, b) as &dyn core::fmt::Debug);
+            f.entry(&Part(a, b, self.1));
         }
     }
 
@@ -201,7 +227,7 @@ impl<'a, 'b> SetDiffer for DebugSetDiff<'a, 'b> {
         V: ?Sized + Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&DIFF { L: a, R: Missing });
+            f.entry(&DIFF { L: Whole(a), R: Missing });
         }
     }
 
@@ -210,7 +236,7 @@ impl<'a, 'b> SetDiffer for DebugSetDiff<'a, 'b> {
         V: ?Sized + Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&DIFF { L: Missing, R: a });
+            f.entry(&DIFF { L: Missing, R: Whole(a) });
         }
     }
 
@@ -221,6 +247,7 @@ impl<'a, 'b> SetDiffer for DebugSetDiff<'a, 'b> {
 
 struct DebugMapDiff<'a, 'b>(
     Result<core::fmt::DebugMap<'a, 'b>, core::fmt::Error>,
+    bool,
 );
 
 impl<'a, 'b> MapDiffer for DebugMapDiff<'a, 'b> {
@@ -233,7 +260,7 @@ impl<'a, 'b> MapDiffer for DebugMapDiff<'a, 'b> {
         V: ?Sized + Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&k, &DebugDiff(a, b) as &dyn core::fmt::Debug);
+            f.entry(&k, &Part(a, b, self.1));
         }
     }
 
@@ -243,7 +270,7 @@ impl<'a, 'b> MapDiffer for DebugMapDiff<'a, 'b> {
         V: ?Sized + Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&k, &DIFF { L: a, R: Missing });
+            f.entry(&k, &DIFF { L: Whole(a), R: Missing });
         }
     }
 
@@ -253,7 +280,7 @@ impl<'a, 'b> MapDiffer for DebugMapDiff<'a, 'b> {
         V: ?Sized + Diff,
     {
         if let Ok(f) = &mut self.0 {
-            f.entry(&k, &DIFF { L: Missing, R: a });
+            f.entry(&k, &DIFF { L: Missing, R: Whole(a) });
         }
     }
 
@@ -272,9 +299,46 @@ where
 {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
         if all_different(&self.0, &self.1) {
-            DebugDiffer(fmt).difference(&self.0, &self.1)
+            DebugDiffer { fmt, whole: false }
+                .difference(&Whole(&self.0), &Whole(&self.1))
         } else {
-            Diff::diff(&self.0, &self.1, DebugDiffer(fmt))
+            Diff::diff(&self.0, &self.1, DebugDiffer { fmt, whole: false })
+        }
+    }
+}
+
+/// Shows a value by diffing it with itself, which presents it the way its
+/// `Diff` impl does. Differs use this, rather than the value's own Debug
+/// impl, to show values whole, so that redacted parts stay so.
+///
+/// Values that differ from themselves, such as NaNs, still show up once.
+///
+/// Derived `Diff` impls use this, as `__Whole`, to show values of different
+/// enum variants.
+pub struct Whole<'a, T: ?Sized>(pub &'a T);
+
+impl<'a, T> core::fmt::Debug for Whole<'a, T>
+where
+    T: ?Sized + Diff,
+{
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        Diff::diff(self.0, self.0, DebugDiffer { fmt, whole: true })
+    }
+}
+
+/// A pair of parts of the values being diffed, shown as a `DebugDiff`, or as
+/// a `Whole` left-hand value when the flag is set.
+struct Part<'a, T: ?Sized>(&'a T, &'a T, bool);
+
+impl<'a, T> core::fmt::Debug for Part<'a, T>
+where
+    T: ?Sized + Diff,
+{
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if self.2 {
+            Whole(self.0).fmt(fmt)
+        } else {
+            DebugDiff(self.0, self.1).fmt(fmt)
         }
     }
 }
@@ -359,42 +423,292 @@ where
     DebugDiff(a, b)
 }
 
+/// Returns a `Differ` that writes a diff to `fmt` the way [`debug_diff`] shows
+/// it.
+///
+/// This is for `Debug` impls that show a diff of values they hold, or for
+/// combining with other differs using the [`combinators`].
+///
+/// ```
+/// use std::fmt;
+/// use visit_diff::{debug_differ, Diff};
+///
+/// struct Change(u32, u32);
+///
+/// impl fmt::Debug for Change {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         Diff::diff(&self.0, &self.1, debug_differ(f))
+///     }
+/// }
+///
+/// assert_eq!(format!("{:?}", Change(1, 2)), "DIFF { L: 1, R: 2 }");
+/// ```
+///
+/// [`debug_diff`]: fn.debug_diff.html
+/// [`combinators`]: combinators/index.html
+pub fn debug_differ<'a, 'b>(
+    fmt: &'a mut core::fmt::Formatter<'b>,
+) -> impl Differ<Ok = (), Err = core::fmt::Error> + use<'a, 'b> {
+    DebugDiffer { fmt, whole: false }
+}
+
 /// Replacement for the standard `assert_eq!` macro that prints a [`debug_diff`]
 /// between its arguments on failure.
 ///
+/// The arguments are compared using their [`Diff`] impl, through
+/// [`any_difference`], so they don't need to implement `PartialEq`.
+///
+/// The comparison can be given [`DiffOptions`] after the arguments, as
+/// `options = ...`. For the common case of ignoring parts of the values, their
+/// [path patterns] can be listed as `ignore = [...]` instead. Either can be
+/// followed by a message, as with `assert_eq!`.
+///
+/// The `limits` in the options don't affect whether the assertion passes,
+/// only how much of the diff the panic message shows.
+///
+/// ```
+/// use visit_diff::{assert_eq_diff, Diff, DiffOptions};
+///
+/// #[derive(Diff, Debug)]
+/// struct Event {
+///     id: u64,
+///     name: &'static str,
+/// }
+///
+/// let a = Event { id: 1, name: "start" };
+/// let b = Event { id: 2, name: "start" };
+/// assert_eq_diff!(a, b, ignore = ["id"]);
+///
+/// let options = DiffOptions {
+///     float_tolerance: 0.5,
+///     ..DiffOptions::default()
+/// };
+/// assert_eq_diff!(1.0, 1.25, options = options, "close enough");
+/// ```
+///
 /// [`debug_diff`]: fn.debug_diff.html
+/// [`Diff`]: trait.Diff.html
+/// [`any_difference`]: fn.any_difference.html
+/// [`DiffOptions`]: struct.DiffOptions.html
+/// [path patterns]: filter/index.html
 #[macro_export]
 macro_rules! assert_eq_diff {
-    ($left:expr, $right:expr) => ({
+    ($left:expr, $right:expr $(,)?) => ({
         match (&$left, &$right) {
             (left_val, right_val) => {
-                if !(*left_val == *right_val) {
-                    panic!(r#"assertion failed: `(left == right)`
-difference:
-{:#?}"#, $crate::debug_diff(left_val, right_val))
+                if $crate::any_difference(left_val, right_val) {
+                    $crate::__assert_failed(
+                        "==",
+                        left_val,
+                        right_val,
+                        ::core::option::Option::None,
+                        ::core::option::Option::None,
+                    );
                 }
             }
         }
     });
-    ($left:expr, $right:expr,) => ({
-        assert_eq_diff!($left, $right)
+    ($left:expr, $right:expr, ignore = [$($pattern:expr),* $(,)?]
+     $(, $($arg:tt)*)?) => ({
+        let options = $crate::DiffOptions {
+            filter: $crate::filter::Filter::new()$(.ignore($pattern))*,
+            ..$crate::DiffOptions::new()
+        };
+        $crate::assert_eq_diff!($left, $right, options = options
+                                $(, $($arg)*)?)
+    });
+    ($left:expr, $right:expr, options = $options:expr $(,)?) => ({
+        match (&$left, &$right, &$options) {
+            (left_val, right_val, options) => {
+                if $crate::any_difference_with(left_val, right_val, options) {
+                    $crate::__assert_failed(
+                        "==",
+                        left_val,
+                        right_val,
+                        ::core::option::Option::Some(options),
+                        ::core::option::Option::None,
+                    );
+                }
+            }
+        }
+    });
+    ($left:expr, $right:expr, options = $options:expr, $($arg:tt)+) => ({
+        match (&$left, &$right, &$options) {
+            (left_val, right_val, options) => {
+                if $crate::any_difference_with(left_val, right_val, options) {
+                    $crate::__assert_failed(
+                        "==",
+                        left_val,
+                        right_val,
+                        ::core::option::Option::Some(options),
+                        ::core::option::Option::Some(
+                            format_args!($($arg)+),
+                        ),
+                    );
+                }
+            }
+        }
+    });
+    ($left:expr, $right:expr, $($arg:tt)+) => ({
+        match (&($left), &($right)) {
+            (left_val, right_val) => {
+                if $crate::any_difference(left_val, right_val) {
+                    $crate::__assert_failed(
+                        "==",
+                        left_val,
+                        right_val,
+                        ::core::option::Option::None,
+                        ::core::option::Option::Some(
+                            format_args!($($arg)+),
+                        ),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Counterpart of [`assert_eq_diff!`] that checks that its arguments differ,
+/// using their [`Diff`] impl.
+///
+/// It takes the same `options = ...` and `ignore = [...]` settings as
+/// `assert_eq_diff!`. On failure it prints the arguments, without the parts
+/// that were ignored.
+///
+/// [`assert_eq_diff!`]: macro.assert_eq_diff.html
+/// [`Diff`]: trait.Diff.html
+#[macro_export]
+macro_rules! assert_ne_diff {
+    ($left:expr, $right:expr $(,)?) => ({
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !$crate::any_difference(left_val, right_val) {
+                    $crate::__assert_failed(
+                        "!=",
+                        left_val,
+                        right_val,
+                        ::core::option::Option::None,
+                        ::core::option::Option::None,
+                    );
+                }
+            }
+        }
+    });
+    ($left:expr, $right:expr, ignore = [$($pattern:expr),* $(,)?]
+     $(, $($arg:tt)*)?) => ({
+        let options = $crate::DiffOptions {
+            filter: $crate::filter::Filter::new()$(.ignore($pattern))*,
+            ..$crate::DiffOptions::new()
+        };
+        $crate::assert_ne_diff!($left, $right, options = options
+                                $(, $($arg)*)?)
+    });
+    ($left:expr, $right:expr, options = $options:expr $(,)?) => ({
+        match (&$left, &$right, &$options) {
+            (left_val, right_val, options) => {
+                if !$crate::any_difference_with(left_val, right_val, options) {
+                    $crate::__assert_failed(
+                        "!=",
+                        left_val,
+                        right_val,
+                        ::core::option::Option::Some(options),
+                        ::core::option::Option::None,
+                    );
+                }
+            }
+        }
+    });
+    ($left:expr, $right:expr, options = $options:expr, $($arg:tt)+) => ({
+        match (&$left, &$right, &$options) {
+            (left_val, right_val, options) => {
+                if !$crate::any_difference_with(left_val, right_val, options) {
+                    $crate::__assert_failed(
+                        "!=",
+                        left_val,
+                        right_val,
+                        ::core::option::Option::Some(options),
+                        ::core::option::Option::Some(
+                            format_args!($($arg)+),
+                        ),
+                    );
+                }
+            }
+        }
     });
     ($left:expr, $right:expr, $($arg:tt)+) => ({
         match (&($left), &($right)) {
             (left_val, right_val) => {
-                if !(*left_val == *right_val) {
-                    panic!(r#"assertion failed: `(left == right)`
-{}
-difference:
-{:#?}"#,
+                if !$crate::any_difference(left_val, right_val) {
+                    $crate::__assert_failed(
+                        "!=",
+                        left_val,
+                        right_val,
+                        ::core::option::Option::None,
+                        ::core::option::Option::Some(
                             format_args!($($arg)+),
-                            $crate::debug_diff(left_val, right_val))
+                        ),
+                    );
                 }
             }
         }
     });
 }
 
+/// Like [`assert_eq_diff!`], but only checked in builds with debug
+/// assertions enabled, as with `debug_assert_eq!`.
+///
+/// [`assert_eq_diff!`]: macro.assert_eq_diff.html
+#[macro_export]
+macro_rules! debug_assert_eq_diff {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_eq_diff!($($arg)*);
+        }
+    };
+}
+
+/// Like [`assert_ne_diff!`], but only checked in builds with debug
+/// assertions enabled, as with `debug_assert_ne!`.
+///
+/// [`assert_ne_diff!`]: macro.assert_ne_diff.html
+#[macro_export]
+macro_rules! debug_assert_ne_diff {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::assert_ne_diff!($($arg)*);
+        }
+    };
+}
+
+/// Panics for a failed `assert_eq_diff!` or `assert_ne_diff!`, where `op` is
+/// the comparison that failed. Used by the macros; not public API.
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_failed<T>(
+    op: &str,
+    left: &T,
+    right: &T,
+    options: Option<&DiffOptions>,
+    message: Option<core::fmt::Arguments>,
+) -> !
+where
+    T: Diff + ?Sized,
+{
+    let shown = if op == "==" { "difference" } else { "value" };
+    let options = options.unwrap_or(&crate::options::DEFAULT);
+    let diff = debug_diff_with(left, right, options);
+    match message {
+        Some(message) => panic!(
+            "assertion failed: `(left {} right)`\n{}\n{}:\n{:#?}",
+            op, message, shown, diff
+        ),
+        None => panic!(
+            "assertion failed: `(left {} right)`\n{}:\n{:#?}",
+            op, shown, diff
+        ),
+    }
+}
+
 #[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
@@ -74,6 +74,32 @@ where
     Diff::diff(a, b, Detector::<All>::default()).void_unwrap()
 }
 
+/// Returns the `Differ` behind [`any_difference`], for combining with other
+/// differs using the [`combinators`].
+///
+/// ```
+/// use visit_diff::{difference_detector, Diff};
+/// use void::ResultVoidExt;
+///
+/// let out = difference_detector();
+/// assert!(Diff::diff(&[1, 2], &[1, 3], out).void_unwrap());
+/// ```
+///
+/// [`any_difference`]: fn.any_difference.html
+/// [`combinators`]: combinators/index.html
+pub fn difference_detector() -> impl Differ<Ok = bool, Err = Void> {
+    Detector::<Any>::default()
+}
+
+/// Returns the `Differ` behind [`all_different`], for combining with other
+/// differs using the [`combinators`].
+///
+/// [`all_different`]: fn.all_different.html
+/// [`combinators`]: combinators/index.html
+pub fn all_different_detector() -> impl Differ<Ok = bool, Err = Void> {
+    Detector::<All>::default()
+}
+
 trait Accumulator: Into<bool> + Default {
     fn consider<T>(&mut self, a: &T, b: &T)
     where
@@ -180,7 +206,6 @@ impl Accumulator for All {
 
 impl From<All> for bool {
     fn from(x: All) -> bool {
-        println!("{:?}", x);
         x.any && x.all
     }
 }
@@ -200,11 +225,15 @@ impl<A: Accumulator> Differ for Detector<A> {
     type MapDiffer = MapDetector<A>;
     type SetDiffer = SetDetector<A>;
 
-    fn difference(self, _: &Debug, _: &Debug) -> Result<Self::Ok, Self::Err> {
+    fn difference(
+        self,
+        _: &dyn Debug,
+        _: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
         Ok(true)
     }
 
-    fn same(self, _: &Debug, _: &Debug) -> Result<Self::Ok, Self::Err> {
+    fn same(self, _: &dyn Debug, _: &dyn Debug) -> Result<Self::Ok, Self::Err> {
         Ok(false)
     }
 
@@ -0,0 +1,269 @@
+//! Impls for the dynamically typed values of document formats, each behind a
+//! feature named after the crate that provides them.
+//!
+//! Objects (or tables, or mappings) are diffed as maps, arrays as sequences,
+//! and everything else as atomic values. Within a document, values are shown
+//! the way they'd be written in it -- `1.5` and `"x"` rather than `Number(1.5)`
+//! and `String("x")` -- so diffs read like the documents being compared. Only
+//! when two documents are different all the way through will `debug_diff` fall
+//! back to the value type's own `Debug` impl to show them.
+
+use super::*;
+
+/// A type of document value that can be written out as it would appear in a
+/// document.
+trait Document: Diff {
+    fn fmt_literal(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result;
+}
+
+/// A reference to part of a document, which is formatted using
+/// `Document::fmt_literal` and diffed like the value it refers to.
+struct Doc<'a, V>(&'a V);
+
+impl<'a, V: Document> Debug for Doc<'a, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.0.fmt_literal(f)
+    }
+}
+
+impl<'a, V: Document> Diff for Doc<'a, V> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(a.0, b.0, out)
+    }
+}
+
+/// Formats a value using its `Display` impl.
+struct Displayed<T>(T);
+
+impl<T: core::fmt::Display> Debug for Displayed<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// serde_json
+
+#[cfg(feature = "serde_json")]
+impl Document for serde_json::Value {
+    fn fmt_literal(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use serde_json::Value::*;
+        match self {
+            Null => f.write_str("null"),
+            Bool(x) => x.fmt(f),
+            Number(x) => Displayed(x).fmt(f),
+            String(x) => x.fmt(f),
+            Array(x) => f.debug_list().entries(x.iter().map(Doc)).finish(),
+            Object(x) => f
+                .debug_map()
+                .entries(x.iter().map(|(k, v)| (k, Doc(v))))
+                .finish(),
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl Diff for serde_json::Value {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        use serde_json::Value::*;
+        match (a, b) {
+            (Array(a), Array(b)) => {
+                let mut out = out.begin_seq();
+                out.diff_elements(a.iter().map(Doc), b.iter().map(Doc));
+                out.end()
+            }
+            (Object(a), Object(b)) => Diff::diff(a, b, out),
+            // Floats go through `f64`'s impl, which allows for the
+            // `float_tolerance`.
+            (Number(x), Number(y)) if x.is_f64() && y.is_f64() => {
+                let (x, y) = (x.as_f64().unwrap(), y.as_f64().unwrap());
+                Diff::diff(&x, &y, out)
+            }
+            _ if a != b => out.difference(&Doc(a), &Doc(b)),
+            _ => out.same(&Doc(a), &Doc(b)),
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl Diff for serde_json::Map<String, serde_json::Value> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let mut out = out.begin_map();
+
+        for (k, va) in a {
+            if let Some(vb) = b.get(k) {
+                out.diff_entry(k, &Doc(va), &Doc(vb))
+            } else {
+                out.only_in_left(k, &Doc(va))
+            }
+        }
+
+        for (k, vb) in b {
+            if !a.contains_key(k) {
+                out.only_in_right(k, &Doc(vb))
+            }
+        }
+
+        out.end()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// toml
+
+#[cfg(feature = "toml")]
+impl Document for toml::Value {
+    fn fmt_literal(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use toml::Value::*;
+        match self {
+            String(x) => x.fmt(f),
+            Integer(x) => x.fmt(f),
+            Float(x) => x.fmt(f),
+            Boolean(x) => x.fmt(f),
+            Datetime(x) => Displayed(x).fmt(f),
+            Array(x) => f.debug_list().entries(x.iter().map(Doc)).finish(),
+            Table(x) => f
+                .debug_map()
+                .entries(x.iter().map(|(k, v)| (k, Doc(v))))
+                .finish(),
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+impl Diff for toml::Value {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        use toml::Value::*;
+        match (a, b) {
+            (Array(a), Array(b)) => {
+                let mut out = out.begin_seq();
+                out.diff_elements(a.iter().map(Doc), b.iter().map(Doc));
+                out.end()
+            }
+            (Table(a), Table(b)) => Diff::diff(a, b, out),
+            // Floats go through `f64`'s impl, which allows for the
+            // `float_tolerance`.
+            (Float(x), Float(y)) => Diff::diff(x, y, out),
+            _ if a != b => out.difference(&Doc(a), &Doc(b)),
+            _ => out.same(&Doc(a), &Doc(b)),
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+impl Diff for toml::Table {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let mut out = out.begin_map();
+
+        for (k, va) in a {
+            if let Some(vb) = b.get(k) {
+                out.diff_entry(k, &Doc(va), &Doc(vb))
+            } else {
+                out.only_in_left(k, &Doc(va))
+            }
+        }
+
+        for (k, vb) in b {
+            if !a.contains_key(k) {
+                out.only_in_right(k, &Doc(vb))
+            }
+        }
+
+        out.end()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// serde_yaml
+//
+// YAML mappings can have any value as a key, not just strings. Keys are shown
+// the same way as values. Tagged values are treated as atomic.
+
+#[cfg(feature = "serde_yaml")]
+impl Document for serde_yaml::Value {
+    fn fmt_literal(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use serde_yaml::Value::*;
+        match self {
+            Null => f.write_str("null"),
+            Bool(x) => x.fmt(f),
+            Number(x) => Displayed(x).fmt(f),
+            String(x) => x.fmt(f),
+            Sequence(x) => f.debug_list().entries(x.iter().map(Doc)).finish(),
+            Mapping(x) => f
+                .debug_map()
+                .entries(x.iter().map(|(k, v)| (Doc(k), Doc(v))))
+                .finish(),
+            Tagged(x) => {
+                write!(f, "{} ", x.tag)?;
+                x.value.fmt_literal(f)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+impl Diff for serde_yaml::Value {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        use serde_yaml::Value::*;
+        match (a, b) {
+            (Sequence(a), Sequence(b)) => {
+                let mut out = out.begin_seq();
+                out.diff_elements(a.iter().map(Doc), b.iter().map(Doc));
+                out.end()
+            }
+            (Mapping(a), Mapping(b)) => Diff::diff(a, b, out),
+            // Floats go through `f64`'s impl, which allows for the
+            // `float_tolerance`.
+            (Number(x), Number(y)) if x.is_f64() && y.is_f64() => {
+                let (x, y) = (x.as_f64().unwrap(), y.as_f64().unwrap());
+                Diff::diff(&x, &y, out)
+            }
+            _ if a != b => out.difference(&Doc(a), &Doc(b)),
+            _ => out.same(&Doc(a), &Doc(b)),
+        }
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+impl Diff for serde_yaml::Mapping {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let mut out = out.begin_map();
+
+        for (k, va) in a {
+            if let Some(vb) = b.get(k) {
+                out.diff_entry(&Doc(k), &Doc(va), &Doc(vb))
+            } else {
+                out.only_in_left(&Doc(k), &Doc(va))
+            }
+        }
+
+        for (k, vb) in b {
+            if !a.contains_key(k) {
+                out.only_in_right(&Doc(k), &Doc(vb))
+            }
+        }
+
+        out.end()
+    }
+}
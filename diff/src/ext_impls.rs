@@ -0,0 +1,316 @@
+//! Impls for types from other crates, each behind a feature named after the
+//! crate.
+
+#[allow(unused_imports)]
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+// indexmap
+//
+// Entries are paired up by key, and reported in the order of the left-hand
+// map, followed by entries that only appear in the right-hand map, in its
+// order. As with the maps' `PartialEq` impls, order alone doesn't make two
+// maps different; wrap them in `Ordered` for that.
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> Diff for indexmap::IndexMap<K, V, S>
+where
+    K: Eq + core::hash::Hash + Debug,
+    V: Diff,
+    S: core::hash::BuildHasher,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let mut out = out.begin_map();
+
+        for (k, va) in a {
+            if let Some(vb) = b.get(k) {
+                out.diff_entry(k, va, vb)
+            } else {
+                out.only_in_left(k, va)
+            }
+        }
+
+        for (k, vb) in b {
+            if !a.contains_key(k) {
+                out.only_in_right(k, vb)
+            }
+        }
+
+        out.end()
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, S> Diff for indexmap::IndexSet<K, S>
+where
+    K: Eq + core::hash::Hash + Diff,
+    S: core::hash::BuildHasher,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let mut out = out.begin_set();
+
+        for ea in a {
+            if let Some(eb) = b.get(ea) {
+                out.diff_equal(ea, eb)
+            } else {
+                out.only_in_left(ea)
+            }
+        }
+
+        for eb in b {
+            if !a.contains(eb) {
+                out.only_in_right(eb)
+            }
+        }
+
+        out.end()
+    }
+}
+
+/// Wraps an `IndexMap` or `IndexSet` (or a reference to one) so that it's
+/// diffed as a sequence of its entries, in order, rather than by key.
+///
+/// Two maps with the same entries in a different order are then different, and
+/// the entries are reported by position, the way a `Vec` of key-value pairs
+/// would be.
+///
+/// ```
+/// use indexmap::IndexMap;
+/// use visit_diff::{any_difference, debug_diff, Ordered};
+///
+/// let a: IndexMap<_, _> = vec![(1, "a"), (2, "b"), (3, "c")]
+///     .into_iter()
+///     .collect();
+/// let b: IndexMap<_, _> = vec![(1, "a"), (3, "c"), (2, "b")]
+///     .into_iter()
+///     .collect();
+///
+/// assert!(!any_difference(&a, &b));
+/// assert_eq!(
+///     format!("{:?}", debug_diff(Ordered(&a), Ordered(&b))),
+///     "[(1, \"a\"), DIFF { L: (2, \"b\"), R: (3, \"c\") }, \
+///      DIFF { L: (3, \"c\"), R: (2, \"b\") }]",
+/// );
+/// ```
+///
+/// `Ordered` can also be used to hold a map or set in a type that derives
+/// `Diff`.
+#[cfg(feature = "indexmap")]
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct Ordered<T>(pub T);
+
+#[cfg(feature = "indexmap")]
+impl<T> core::ops::Deref for Ordered<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<T> core::ops::DerefMut for Ordered<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "indexmap")]
+macro_rules! ordered_impl {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a, K, V, S> Debug for Ordered<$ty>
+            where
+                K: Debug,
+                V: Debug,
+            {
+                fn fmt(
+                    &self,
+                    f: &mut core::fmt::Formatter,
+                ) -> core::fmt::Result {
+                    f.debug_list().entries(self.0.iter()).finish()
+                }
+            }
+
+            impl<'a, K, V, S> Diff for Ordered<$ty>
+            where
+                K: Diff,
+                V: Diff,
+            {
+                fn diff<D>(
+                    a: &Self,
+                    b: &Self,
+                    out: D,
+                ) -> Result<D::Ok, D::Err>
+                where
+                    D: Differ,
+                {
+                    let mut out = out.begin_seq();
+                    out.diff_elements(a.0.iter(), b.0.iter());
+                    out.end()
+                }
+            }
+        )*
+    };
+    (set $($ty:ty),*) => {
+        $(
+            impl<'a, K, S> Debug for Ordered<$ty>
+            where
+                K: Debug,
+            {
+                fn fmt(
+                    &self,
+                    f: &mut core::fmt::Formatter,
+                ) -> core::fmt::Result {
+                    f.debug_list().entries(self.0.iter()).finish()
+                }
+            }
+
+            impl<'a, K, S> Diff for Ordered<$ty>
+            where
+                K: Diff,
+            {
+                fn diff<D>(
+                    a: &Self,
+                    b: &Self,
+                    out: D,
+                ) -> Result<D::Ok, D::Err>
+                where
+                    D: Differ,
+                {
+                    let mut out = out.begin_seq();
+                    out.diff_elements(a.0.iter(), b.0.iter());
+                    out.end()
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "indexmap")]
+ordered_impl!(
+    indexmap::IndexMap<K, V, S>,
+    &'a indexmap::IndexMap<K, V, S>
+);
+#[cfg(feature = "indexmap")]
+ordered_impl!(set
+    indexmap::IndexSet<K, S>,
+    &'a indexmap::IndexSet<K, S>
+);
+
+////////////////////////////////////////////////////////////////////////////////
+// Inline vectors: diffed as slices, like `Vec`.
+
+#[cfg(feature = "smallvec")]
+impl<A> Diff for smallvec::SmallVec<A>
+where
+    A: smallvec::Array,
+    A::Item: Diff,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(a.as_slice(), b.as_slice(), out)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T, const CAP: usize> Diff for arrayvec::ArrayVec<T, CAP>
+where
+    T: Diff,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(a.as_slice(), b.as_slice(), out)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<const CAP: usize> Diff for arrayvec::ArrayString<CAP> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(a.as_str(), b.as_str(), out)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Atomic types: these all diff using PartialEq.
+
+#[cfg(feature = "bytes")]
+impl_diff_partial_eq!(bytes::Bytes);
+#[cfg(feature = "bytes")]
+impl_diff_partial_eq!(bytes::BytesMut);
+
+#[cfg(feature = "uuid")]
+impl_diff_partial_eq!(uuid::Uuid);
+
+#[cfg(feature = "url")]
+impl_diff_partial_eq!(url::Url);
+
+#[cfg(feature = "ordered-float")]
+impl_diff_partial_eq!(ordered_float::OrderedFloat<T> | T);
+#[cfg(feature = "ordered-float")]
+impl_diff_partial_eq!(ordered_float::NotNan<T> | T);
+
+#[cfg(feature = "chrono")]
+impl_diff_partial_eq!(chrono::NaiveDate);
+#[cfg(feature = "chrono")]
+impl_diff_partial_eq!(chrono::NaiveTime);
+#[cfg(feature = "chrono")]
+impl_diff_partial_eq!(chrono::NaiveDateTime);
+#[cfg(feature = "chrono")]
+impl_diff_partial_eq!(chrono::TimeDelta);
+#[cfg(feature = "chrono")]
+impl_diff_partial_eq!(chrono::FixedOffset);
+#[cfg(feature = "chrono")]
+impl_diff_partial_eq!(chrono::Utc);
+#[cfg(feature = "chrono")]
+impl_diff_partial_eq!(chrono::Weekday);
+#[cfg(feature = "chrono")]
+impl_diff_partial_eq!(chrono::Month);
+
+#[cfg(feature = "chrono")]
+impl<Tz> Diff for chrono::DateTime<Tz>
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: Debug,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        if a != b {
+            out.difference(a, b)
+        } else {
+            out.same(a, b)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl_diff_partial_eq!(time::Date);
+#[cfg(feature = "time")]
+impl_diff_partial_eq!(time::Time);
+#[cfg(feature = "time")]
+impl_diff_partial_eq!(time::PrimitiveDateTime);
+#[cfg(feature = "time")]
+impl_diff_partial_eq!(time::OffsetDateTime);
+#[cfg(feature = "time")]
+impl_diff_partial_eq!(time::UtcOffset);
+#[cfg(feature = "time")]
+impl_diff_partial_eq!(time::Duration);
+#[cfg(feature = "time")]
+impl_diff_partial_eq!(time::Weekday);
+#[cfg(feature = "time")]
+impl_diff_partial_eq!(time::Month);
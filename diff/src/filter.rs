@@ -0,0 +1,1243 @@
+//! Ignoring parts of values, or focusing on them, for a single diff.
+//!
+//! A [`Filter`] holds path patterns saying which parts of a value to ignore,
+//! and optionally which parts to focus on to the exclusion of everything
+//! else. Like [`limit`], it can be applied by wrapping either the values
+//! being diffed, using [`Filter::wrap`], or the `Differ`, using
+//! [`Filtered`].
+//!
+//! ```
+//! use visit_diff::{any_difference, filter::Filter, Diff};
+//!
+//! #[derive(Diff, Debug)]
+//! struct User {
+//!     name: &'static str,
+//!     updated_at: u64,
+//! }
+//!
+//! let a = User { name: "ann", updated_at: 1 };
+//! let b = User { name: "ann", updated_at: 2 };
+//!
+//! let filter = Filter::new().ignore("updated_at");
+//! assert!(!any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+//! ```
+//!
+//! # Paths and patterns
+//!
+//! The path of a part of a value is written much as it would be in Rust:
+//! `.name` for a field of a struct, `.0` for a field of a tuple, `[3]` for an
+//! element of a sequence, and `[key]` for an entry in a map or an element of
+//! a set, where `key` is its `Debug` representation, or the string itself if
+//! it's a string. So `users[ann].sessions[0].token` is a path, and the
+//! leading `.` can be left off. A key can also be written in quotes, as
+//! `["key"]`, in which case it may contain `]` and `*`, and matches only
+//! itself; within the quotes, `\` escapes the character after it, so
+//! `["a\"]b"]` is the key `a"]b`.
+//!
+//! Patterns are paths in which `*` stands for any run of characters in a
+//! field name or key. On its own between dots, `*` matches any one field,
+//! element or entry, and `**` matches any number of them. So `*.updated_at`
+//! matches `updated_at` one level down, `sessions[*].token` matches the token
+//! of every session, and `**.updated_at` matches `updated_at` anywhere.
+//!
+//! # Ignoring and focusing
+//!
+//! Struct and tuple fields that are ignored are reported to the `Differ`
+//! through `skip_field`. Ignored elements and map entries are shown as `_` if
+//! they're on both sides, and left out if they're on only one side.
+//!
+//! If there are any focus patterns, only the parts of the value matching one
+//! of them, and whatever contains those parts, are compared; everything else
+//! is ignored.
+//!
+//! Sequences matching an unordered pattern are compared without regard to
+//! the order of their elements: each element on the left is paired with an
+//! element on the right that's the same, if there is one, and what's left
+//! over is paired up by position.
+//!
+//! [`Filter`]: struct.Filter.html
+//! [`Filter::wrap`]: struct.Filter.html#method.wrap
+//! [`Filtered`]: struct.Filtered.html
+//! [`limit`]: ../limit/index.html
+
+use itertools::{EitherOrBoth, Itertools};
+use std::fmt::{self, Debug, Formatter};
+use void::{ResultVoidExt, Void};
+
+use crate::{
+    Diff, DiffOptions, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer,
+    TupleDiffer,
+};
+
+/// Patterns for parts of a value to ignore or focus on.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    ignore: Vec<Pattern>,
+    focus: Vec<Pattern>,
+    unordered: Vec<Pattern>,
+}
+
+impl Filter {
+    /// Creates a filter that lets everything through.
+    pub const fn new() -> Self {
+        Filter {
+            ignore: Vec::new(),
+            focus: Vec::new(),
+            unordered: Vec::new(),
+        }
+    }
+
+    /// Ignores the parts of values matching `pattern`.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is malformed, such as by having an unclosed `[`.
+    pub fn ignore(mut self, pattern: &str) -> Self {
+        self.ignore.push(Pattern::parse(pattern));
+        self
+    }
+
+    /// Compares only the parts of values matching `pattern`, or those
+    /// matching any other pattern given to `focus`.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is malformed, such as by having an unclosed `[`.
+    pub fn focus(mut self, pattern: &str) -> Self {
+        self.focus.push(Pattern::parse(pattern));
+        self
+    }
+
+    /// Compares the sequences matching `pattern` without regard to the order
+    /// of their elements.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is malformed, such as by having an unclosed `[`.
+    pub fn unordered(mut self, pattern: &str) -> Self {
+        self.unordered.push(Pattern::parse(pattern));
+        self
+    }
+
+    /// Wraps `value` so that diffing it, with any `Differ`, is subject to
+    /// this filter.
+    ///
+    /// Formatting the wrapped value with `Debug` also applies the filter.
+    pub fn wrap<'a, T>(&'a self, value: &'a T) -> FilteredValue<'a, T>
+    where
+        T: Diff + ?Sized,
+    {
+        let root = Place {
+            path: vec![],
+            focused: self.focus.is_empty(),
+        };
+        FilteredValue {
+            value,
+            filter: self,
+            place: if self.ignores(&root) {
+                None
+            } else {
+                Some(root)
+            },
+        }
+    }
+
+    /// Decides whether the part of a value at `place` is ignored.
+    fn ignores(&self, place: &Place) -> bool {
+        let path = &place.path;
+        self.ignore.iter().any(|p| p.matches(path) == Match::Full)
+            || (!place.focused
+                && self.focus.iter().all(|p| p.matches(path) == Match::No))
+    }
+
+    /// Decides whether the sequence at `place` is compared without regard to
+    /// order.
+    fn unorders(&self, place: &Place) -> bool {
+        let path = &place.path;
+        self.unordered
+            .iter()
+            .any(|p| p.matches(path) == Match::Full)
+    }
+
+    /// Works out where a part of a value is, given where its parent is.
+    fn place(&self, parent: &Place, segment: Segment) -> Option<Place> {
+        let mut path = parent.path.clone();
+        path.push(segment);
+        let focused = parent.focused
+            || self.focus.iter().any(|p| p.matches(&path) == Match::Full);
+        let place = Place { path, focused };
+        if self.ignores(&place) {
+            None
+        } else {
+            Some(place)
+        }
+    }
+}
+
+/// Given two values that can be diffed, returns an object that will describe
+/// their differences, subject to `filter`, when formatted using `Debug`.
+///
+/// ```
+/// use visit_diff::{filter::{self, Filter}, Diff};
+///
+/// #[derive(Diff, Debug)]
+/// struct Session {
+///     id: u32,
+///     user: &'static str,
+///     token: u64,
+/// }
+///
+/// let a = Session { id: 1, user: "ann", token: 1 };
+/// let b = Session { id: 1, user: "bob", token: 2 };
+///
+/// let filter = Filter::new().ignore("token");
+/// assert_eq!(
+///     format!("{:?}", filter::debug_diff(&a, &b, &filter)),
+///     "Session { id: 1, user: DIFF { L: \"ann\", R: \"bob\" } }",
+/// );
+/// ```
+pub fn debug_diff<'a, T>(
+    a: &'a T,
+    b: &'a T,
+    filter: &'a Filter,
+) -> impl Debug + 'a
+where
+    T: Diff + ?Sized,
+{
+    crate::debug_diff(filter.wrap(a), filter.wrap(b))
+}
+
+/// Checks that `pattern` is well-formed, so that it can be given to the
+/// methods of [`Filter`] without a panic, returning why it isn't otherwise.
+///
+/// ```
+/// use visit_diff::filter::check_pattern;
+///
+/// assert!(check_pattern("users[*].name").is_ok());
+/// assert!(check_pattern("users[*.name").is_err());
+/// ```
+///
+/// [`Filter`]: struct.Filter.html
+pub fn check_pattern(pattern: &str) -> Result<(), String> {
+    Pattern::try_parse(pattern).map(drop)
+}
+
+/// One step along the path to part of a value.
+#[derive(Clone, Debug)]
+enum Segment {
+    /// A named or numbered field of a struct or tuple.
+    Field(String),
+    /// An element of a sequence, or a key of a map or set.
+    Key(String),
+}
+
+/// Where a part of a value is.
+#[derive(Clone, Debug)]
+struct Place {
+    path: Vec<Segment>,
+    /// Whether this is within a part matching a focus pattern, or there are
+    /// no focus patterns.
+    focused: bool,
+}
+
+/// How a pattern matches a path.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum Match {
+    /// Neither the path nor anything inside it matches.
+    No,
+    /// Something inside the path might match.
+    Prefix,
+    /// The path matches.
+    Full,
+}
+
+/// One step of a pattern.
+#[derive(Clone, Debug)]
+enum Step {
+    /// Matches a field whose name matches the glob.
+    Field(String),
+    /// Matches an element or key whose text matches the glob.
+    Key(String),
+    /// Matches an element or key whose text is exactly this.
+    ExactKey(String),
+    /// Matches any one segment.
+    Any,
+    /// Matches any number of segments.
+    AnyDepth,
+}
+
+impl Step {
+    fn matches(&self, segment: &Segment) -> bool {
+        match (self, segment) {
+            (Step::Field(glob), Segment::Field(name)) => glob_match(glob, name),
+            (Step::Key(glob), Segment::Key(key)) => glob_match(glob, key),
+            (Step::ExactKey(text), Segment::Key(key)) => text == key,
+            (Step::Any, _) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Pattern {
+    steps: Vec<Step>,
+}
+
+impl Pattern {
+    fn parse(text: &str) -> Self {
+        Self::try_parse(text).unwrap_or_else(|why| {
+            panic!("malformed path pattern {:?}: {}", text, why)
+        })
+    }
+
+    fn try_parse(text: &str) -> Result<Self, String> {
+        let malformed = |why: &str| Err(why.to_string());
+        let mut steps = vec![];
+        let mut rest = text;
+        while !rest.is_empty() {
+            if let Some(inner) = rest.strip_prefix('[') {
+                let (step, after) =
+                    if let Some(quoted) = inner.strip_prefix('"') {
+                        match quoted_key(quoted) {
+                            Some((key, after)) => (Step::ExactKey(key), after),
+                            None => return malformed("unclosed `[\"`"),
+                        }
+                    } else {
+                        match inner.find(']') {
+                            Some(end) => (
+                                Step::Key(inner[..end].to_string()),
+                                &inner[end + 1..],
+                            ),
+                            None => return malformed("unclosed `[`"),
+                        }
+                    };
+                steps.push(step);
+                rest = after;
+            } else {
+                let name = rest.strip_prefix('.').unwrap_or(rest);
+                let end = name.find(['.', '[']).unwrap_or(name.len());
+                steps.push(match &name[..end] {
+                    "" => return malformed("empty field name"),
+                    "*" => Step::Any,
+                    "**" => Step::AnyDepth,
+                    name => Step::Field(name.to_string()),
+                });
+                rest = &name[end..];
+            }
+        }
+        Ok(Pattern { steps })
+    }
+
+    fn matches(&self, path: &[Segment]) -> Match {
+        match_steps(&self.steps, path)
+    }
+}
+
+/// Reads a quoted key up to its closing `"]`, returning the key, with any
+/// escapes undone, and the text after it.
+fn quoted_key(text: &str) -> Option<(String, &str)> {
+    let mut key = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => key.push(chars.next()?.1),
+            '"' if text[i + 1..].starts_with(']') => {
+                return Some((key, &text[i + 2..]))
+            }
+            c => key.push(c),
+        }
+    }
+    None
+}
+
+fn match_steps(steps: &[Step], path: &[Segment]) -> Match {
+    match (steps.split_first(), path.split_first()) {
+        (None, None) => Match::Full,
+        (None, Some(_)) => Match::No,
+        (Some((Step::AnyDepth, rest)), _) => (0..=path.len())
+            .map(|i| match_steps(rest, &path[i..]))
+            .fold(Match::Prefix, Ord::max),
+        (Some(_), None) => Match::Prefix,
+        (Some((step, rest)), Some((segment, path))) => {
+            if step.matches(segment) {
+                match_steps(rest, path)
+            } else {
+                Match::No
+            }
+        }
+    }
+}
+
+/// Matches `text` against a pattern where `*` stands for any sequence of
+/// characters.
+fn glob_match(glob: &str, text: &str) -> bool {
+    match glob.split_once('*') {
+        None => glob == text,
+        Some((prefix, rest)) => match text.strip_prefix(prefix) {
+            Some(text) => text
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(Some(text.len()))
+                .any(|i| glob_match(rest, &text[i..])),
+            None => false,
+        },
+    }
+}
+
+/// The text of a key in a path: its `Debug` representation, unquoted if it's
+/// a string.
+fn key_text(key: &dyn Debug) -> String {
+    let text = format!("{:?}", key);
+    unquote(&text).unwrap_or(text)
+}
+
+/// Reverses the `Debug` formatting of a string, or returns `None` if `text`
+/// doesn't look like a formatted string.
+fn unquote(text: &str) -> Option<String> {
+    let mut chars = text.strip_prefix('"')?.strip_suffix('"')?.chars();
+    let mut out = String::new();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'u' => {
+                if chars.next()? != '{' {
+                    return None;
+                }
+                let hex: String =
+                    chars.by_ref().take_while(|&c| c != '}').collect();
+                std::char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+            }
+            c @ ('\\' | '"' | '\'') => c,
+            _ => return None,
+        });
+    }
+    Some(out)
+}
+
+/// A value wrapped by [`Filter::wrap`].
+///
+/// [`Filter::wrap`]: struct.Filter.html#method.wrap
+pub struct FilteredValue<'a, T: ?Sized> {
+    value: &'a T,
+    filter: &'a Filter,
+    /// Where the value is, or `None` if it's ignored.
+    place: Option<Place>,
+}
+
+impl<'a, T> Diff for FilteredValue<'a, T>
+where
+    T: Diff + ?Sized,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        match &a.place {
+            Some(place) => Diff::diff(
+                a.value,
+                b.value,
+                Filtered {
+                    inner: out,
+                    filter: a.filter,
+                    place: place.clone(),
+                },
+            ),
+            None => out.same(&Ignored, &Ignored),
+        }
+    }
+}
+
+impl<'a, T> Debug for FilteredValue<'a, T>
+where
+    T: Diff + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Diffing the value with itself prints it, through the filter.
+        crate::debug_diff(self, self).fmt(f)
+    }
+}
+
+/// Stands in for an ignored value.
+struct Ignored;
+
+impl Debug for Ignored {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("_")
+    }
+}
+
+impl Diff for Ignored {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        out.same(a, b)
+    }
+}
+
+/// Adapts a `Differ` so that it only sees the parts of values let through by
+/// a [`Filter`].
+///
+/// [`Filter`]: struct.Filter.html
+pub struct Filtered<'f, D> {
+    inner: D,
+    filter: &'f Filter,
+    place: Place,
+}
+
+impl<'f, D: Differ> Filtered<'f, D> {
+    /// Wraps `inner` to apply `filter`.
+    ///
+    /// Patterns that ignore the whole value (such as `**`) aren't taken into
+    /// account, since there must be something for `inner` to compare.
+    pub fn new(inner: D, filter: &'f Filter) -> Self {
+        Filtered {
+            inner,
+            filter,
+            place: Place {
+                path: vec![],
+                focused: filter.focus.is_empty(),
+            },
+        }
+    }
+
+    fn begin<S>(self, begin: impl FnOnce(D) -> S) -> FilteredPart<'f, S> {
+        FilteredPart {
+            float_tolerance: self.inner.options().float_tolerance,
+            inner: begin(self.inner),
+            filter: self.filter,
+            place: self.place,
+            index: 0,
+        }
+    }
+}
+
+impl<'f, D: Differ> Differ for Filtered<'f, D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    type StructDiffer = FilteredPart<'f, D::StructDiffer>;
+    type StructVariantDiffer = FilteredPart<'f, D::StructVariantDiffer>;
+    type TupleDiffer = FilteredPart<'f, D::TupleDiffer>;
+    type TupleVariantDiffer = FilteredPart<'f, D::TupleVariantDiffer>;
+    type SeqDiffer = FilteredPart<'f, D::SeqDiffer>;
+    type MapDiffer = FilteredPart<'f, D::MapDiffer>;
+    type SetDiffer = FilteredPart<'f, D::SetDiffer>;
+
+    fn options(&self) -> &DiffOptions {
+        self.inner.options()
+    }
+
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
+        self.inner.difference(a, b)
+    }
+
+    fn same(self, a: &dyn Debug, b: &dyn Debug) -> Result<Self::Ok, Self::Err> {
+        self.inner.same(a, b)
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        ty: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<Self::Ok, Self::Err>
+    where
+        T: Diff,
+    {
+        let segment = Segment::Field("0".to_string());
+        match self.filter.place(&self.place, segment) {
+            Some(place) => {
+                let wrap = |value| FilteredValue {
+                    value,
+                    filter: self.filter,
+                    place: Some(place.clone()),
+                };
+                let (a, b) = (wrap(a), wrap(b));
+                self.inner.diff_newtype(ty, &a, &b)
+            }
+            None => self.inner.diff_newtype(ty, &Ignored, &Ignored),
+        }
+    }
+
+    fn begin_struct(self, ty: &'static str) -> Self::StructDiffer {
+        self.begin(|d| d.begin_struct(ty))
+    }
+
+    fn begin_struct_variant(
+        self,
+        ty: &'static str,
+        var: &'static str,
+    ) -> Self::StructVariantDiffer {
+        self.begin(|d| d.begin_struct_variant(ty, var))
+    }
+
+    fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+        self.begin(|d| d.begin_tuple(ty))
+    }
+
+    fn begin_tuple_variant(
+        self,
+        ty: &'static str,
+        var: &'static str,
+    ) -> Self::TupleVariantDiffer {
+        self.begin(|d| d.begin_tuple_variant(ty, var))
+    }
+
+    fn begin_seq(self) -> Self::SeqDiffer {
+        self.begin(|d| d.begin_seq())
+    }
+
+    fn begin_map(self) -> Self::MapDiffer {
+        self.begin(|d| d.begin_map())
+    }
+
+    fn begin_set(self) -> Self::SetDiffer {
+        self.begin(|d| d.begin_set())
+    }
+}
+
+/// The sub-differs of [`Filtered`], wrapping those of the inner differ.
+///
+/// [`Filtered`]: struct.Filtered.html
+pub struct FilteredPart<'f, S> {
+    inner: S,
+    filter: &'f Filter,
+    place: Place,
+    /// Position of the next tuple field or sequence element.
+    index: usize,
+    /// The inner differ's tolerance, for pairing up the elements of unordered
+    /// sequences.
+    float_tolerance: f64,
+}
+
+impl<'f, S> FilteredPart<'f, S> {
+    fn next_index(&mut self) -> String {
+        self.index += 1;
+        (self.index - 1).to_string()
+    }
+
+    /// Offers a pair of parts at `segment`, calling `show` with them wrapped
+    /// up unless they're ignored, and `hide` if they are.
+    fn pair<T: ?Sized + Diff>(
+        &mut self,
+        segment: Segment,
+        a: &T,
+        b: &T,
+        show: impl FnOnce(&mut S, &FilteredValue<T>, &FilteredValue<T>),
+        hide: impl FnOnce(&mut S),
+    ) {
+        match self.filter.place(&self.place, segment) {
+            Some(place) => {
+                let filter = self.filter;
+                let a = FilteredValue {
+                    value: a,
+                    filter,
+                    place: Some(place.clone()),
+                };
+                let b = FilteredValue {
+                    value: b,
+                    filter,
+                    place: Some(place),
+                };
+                show(&mut self.inner, &a, &b)
+            }
+            None => hide(&mut self.inner),
+        }
+    }
+
+    /// Offers a part only on one side at `segment`, calling `show` with it
+    /// wrapped up unless it's ignored.
+    fn one_sided<T: ?Sized + Diff>(
+        &mut self,
+        segment: Segment,
+        x: &T,
+        show: impl FnOnce(&mut S, &FilteredValue<T>),
+    ) {
+        if let Some(place) = self.filter.place(&self.place, segment) {
+            let x = FilteredValue {
+                value: x,
+                filter: self.filter,
+                place: Some(place),
+            };
+            show(&mut self.inner, &x)
+        }
+    }
+}
+
+impl<'f, S: StructDiffer> StructDiffer for FilteredPart<'f, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_field<T: ?Sized>(&mut self, name: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.pair(
+            Segment::Field(name.to_string()),
+            a,
+            b,
+            |s, a, b| s.diff_field(name, a, b),
+            |s| s.skip_field::<T>(name),
+        )
+    }
+
+    fn skip_field<T: ?Sized>(&mut self, name: &'static str) {
+        self.inner.skip_field::<T>(name)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+
+    fn end_non_exhaustive(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end_non_exhaustive()
+    }
+}
+
+impl<'f, S: TupleDiffer> TupleDiffer for FilteredPart<'f, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let segment = Segment::Field(self.next_index());
+        self.pair(
+            segment,
+            a,
+            b,
+            |s, a, b| s.diff_field(a, b),
+            |s| s.skip_field::<T>(),
+        )
+    }
+
+    fn skip_field<T: ?Sized>(&mut self) {
+        self.index += 1;
+        self.inner.skip_field::<T>()
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+}
+
+impl<'f, S: SeqDiffer> SeqDiffer for FilteredPart<'f, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let segment = Segment::Key(self.next_index());
+        self.pair(
+            segment,
+            a,
+            b,
+            |s, a, b| s.diff_element(a, b),
+            |s| s.diff_element(&Ignored, &Ignored),
+        )
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, a: &T)
+    where
+        T: Diff,
+    {
+        let segment = Segment::Key(self.next_index());
+        self.one_sided(segment, a, |s, a| s.left_excess(a))
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, b: &T)
+    where
+        T: Diff,
+    {
+        let segment = Segment::Key(self.next_index());
+        self.one_sided(segment, b, |s, b| s.right_excess(b))
+    }
+
+    fn diff_elements<T, I>(&mut self, a: I, b: I)
+    where
+        T: Diff,
+        I: IntoIterator<Item = T>,
+    {
+        if !self.filter.unorders(&self.place) {
+            for ab in a.into_iter().zip_longest(b) {
+                match ab {
+                    EitherOrBoth::Both(a, b) => self.diff_element(&a, &b),
+                    EitherOrBoth::Left(a) => self.left_excess(&a),
+                    EitherOrBoth::Right(b) => self.right_excess(&b),
+                }
+            }
+            return;
+        }
+
+        let options = DiffOptions {
+            float_tolerance: self.float_tolerance,
+            ..DiffOptions::new()
+        };
+        let a: Vec<T> = a.into_iter().collect();
+        let mut b: Vec<Option<T>> = b.into_iter().map(Some).collect();
+        // Each element on the left is paired with the first unused one on the
+        // right that's the same, as seen through the filter.
+        let mut pairs: Vec<(T, Option<T>)> = Vec::with_capacity(a.len());
+        for (i, x) in a.into_iter().enumerate() {
+            let segment = Segment::Key((self.index + i).to_string());
+            let place = self.filter.place(&self.place, segment);
+            let filter = self.filter;
+            let same = b.iter_mut().find(|y| match y {
+                Some(y) => {
+                    let x = FilteredValue {
+                        value: &x,
+                        filter,
+                        place: place.clone(),
+                    };
+                    let y = FilteredValue {
+                        value: y,
+                        filter,
+                        place: place.clone(),
+                    };
+                    !crate::any_difference_with(&x, &y, &options)
+                }
+                None => false,
+            });
+            let y = same.and_then(Option::take);
+            pairs.push((x, y));
+        }
+        // The rest are paired up in order.
+        let mut rest = b.into_iter().flatten();
+        for (x, y) in pairs {
+            match y.or_else(|| rest.next()) {
+                Some(y) => self.diff_element(&x, &y),
+                None => self.left_excess(&x),
+            }
+        }
+        for y in rest {
+            self.right_excess(&y);
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+}
+
+impl<'f, S: MapDiffer> MapDiffer for FilteredPart<'f, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.pair(
+            Segment::Key(key_text(&key)),
+            a,
+            b,
+            |s, a, b| s.diff_entry(key, a, b),
+            |s| s.diff_entry(key, &Ignored, &Ignored),
+        )
+    }
+
+    fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let segment = Segment::Key(key_text(&key));
+        self.one_sided(segment, a, |s, a| s.only_in_left(key, a))
+    }
+
+    fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let segment = Segment::Key(key_text(&key));
+        self.one_sided(segment, b, |s, b| s.only_in_right(key, b))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+}
+
+/// Set elements are their own keys.
+impl<'f, S: SetDiffer> SetDiffer for FilteredPart<'f, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.pair(
+            Segment::Key(key_text(&a)),
+            a,
+            b,
+            |s, a, b| s.diff_equal(a, b),
+            |s| s.diff_equal(&Ignored, &Ignored),
+        )
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let segment = Segment::Key(key_text(&a));
+        self.one_sided(segment, a, |s, a| s.only_in_left(a))
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let segment = Segment::Key(key_text(&b));
+        self.one_sided(segment, b, |s, b| s.only_in_right(b))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+}
+
+/// Lists the paths of the parts of `a` and `b` that differ, written as in
+/// patterns, so that they can be passed to [`Filter::focus`].
+///
+/// Only the innermost parts that differ are listed: if a struct has a field
+/// that differs, the field is listed, and not the struct. A part that's only
+/// in one of the values is listed as a whole. If `a` and `b` differ as
+/// a whole, such as different variants of an enum, the only path listed is
+/// the empty one.
+///
+/// ```
+/// use visit_diff::{filter::changed_paths, Diff};
+///
+/// #[derive(Diff, Debug)]
+/// struct Team {
+///     name: &'static str,
+///     scores: Vec<u32>,
+/// }
+///
+/// let a = Team { name: "red", scores: vec![1, 2] };
+/// let b = Team { name: "blue", scores: vec![1, 3, 4] };
+/// assert_eq!(changed_paths(&a, &b), ["name", "scores[1]", "scores[2]"]);
+/// ```
+///
+/// [`Filter::focus`]: struct.Filter.html#method.focus
+pub fn changed_paths<T>(a: &T, b: &T) -> Vec<String>
+where
+    T: Diff + ?Sized,
+{
+    let mut changed = vec![];
+    let out = PathCollector {
+        changed: &mut changed,
+        path: vec![],
+    };
+    Diff::diff(a, b, out).void_unwrap();
+    changed
+}
+
+/// Writes a path as in patterns, without the leading `.`.
+fn path_text(path: &[Segment]) -> String {
+    let mut text = String::new();
+    for segment in path {
+        match segment {
+            Segment::Field(name) if text.is_empty() => text.push_str(name),
+            Segment::Field(name) => {
+                text.push('.');
+                text.push_str(name);
+            }
+            // Quoted, so that it's matched exactly rather than read as a
+            // glob or cut short.
+            Segment::Key(key)
+                if key.contains([']', '*']) || key.starts_with('"') =>
+            {
+                text.push_str("[\"");
+                for c in key.chars() {
+                    if c == '\\' || c == '"' {
+                        text.push('\\');
+                    }
+                    text.push(c);
+                }
+                text.push_str("\"]");
+            }
+            Segment::Key(key) => {
+                text.push('[');
+                text.push_str(key);
+                text.push(']');
+            }
+        }
+    }
+    text
+}
+
+/// A `Differ` that collects the paths of the parts that differ, for
+/// [`changed_paths`].
+///
+/// [`changed_paths`]: fn.changed_paths.html
+struct PathCollector<'a> {
+    changed: &'a mut Vec<String>,
+    path: Vec<Segment>,
+}
+
+impl<'a> PathCollector<'a> {
+    fn begin(self) -> PathPart<'a> {
+        PathPart {
+            changed: self.changed,
+            path: self.path,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> Differ for PathCollector<'a> {
+    type Ok = ();
+    type Err = Void;
+
+    type StructDiffer = PathPart<'a>;
+    type StructVariantDiffer = PathPart<'a>;
+    type TupleDiffer = PathPart<'a>;
+    type TupleVariantDiffer = PathPart<'a>;
+    type SeqDiffer = PathPart<'a>;
+    type MapDiffer = PathPart<'a>;
+    type SetDiffer = PathPart<'a>;
+
+    fn difference(self, _: &dyn Debug, _: &dyn Debug) -> Result<(), Void> {
+        self.changed.push(path_text(&self.path));
+        Ok(())
+    }
+
+    fn same(self, _: &dyn Debug, _: &dyn Debug) -> Result<(), Void> {
+        Ok(())
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        _: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<(), Void>
+    where
+        T: Diff,
+    {
+        let mut part = self.begin();
+        part.pair(Segment::Field("0".to_string()), a, b);
+        Ok(())
+    }
+
+    fn begin_struct(self, _: &'static str) -> Self::StructDiffer {
+        self.begin()
+    }
+
+    fn begin_struct_variant(
+        self,
+        _: &'static str,
+        _: &'static str,
+    ) -> Self::StructVariantDiffer {
+        self.begin()
+    }
+
+    fn begin_tuple(self, _: &'static str) -> Self::TupleDiffer {
+        self.begin()
+    }
+
+    fn begin_tuple_variant(
+        self,
+        _: &'static str,
+        _: &'static str,
+    ) -> Self::TupleVariantDiffer {
+        self.begin()
+    }
+
+    fn begin_seq(self) -> Self::SeqDiffer {
+        self.begin()
+    }
+
+    fn begin_map(self) -> Self::MapDiffer {
+        self.begin()
+    }
+
+    fn begin_set(self) -> Self::SetDiffer {
+        self.begin()
+    }
+}
+
+/// The sub-differs of [`PathCollector`].
+///
+/// [`PathCollector`]: struct.PathCollector.html
+struct PathPart<'a> {
+    changed: &'a mut Vec<String>,
+    path: Vec<Segment>,
+    /// Position of the next tuple field or sequence element.
+    index: usize,
+}
+
+impl<'a> PathPart<'a> {
+    fn next_index(&mut self) -> String {
+        self.index += 1;
+        (self.index - 1).to_string()
+    }
+
+    fn pair<T: ?Sized + Diff>(&mut self, segment: Segment, a: &T, b: &T) {
+        let mut path = self.path.clone();
+        path.push(segment);
+        let out = PathCollector {
+            changed: &mut *self.changed,
+            path,
+        };
+        Diff::diff(a, b, out).void_unwrap()
+    }
+
+    fn one_sided(&mut self, segment: Segment) {
+        let mut path = self.path.clone();
+        path.push(segment);
+        self.changed.push(path_text(&path));
+    }
+}
+
+impl<'a> StructDiffer for PathPart<'a> {
+    type Ok = ();
+    type Err = Void;
+
+    fn diff_field<T: ?Sized>(&mut self, name: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.pair(Segment::Field(name.to_string()), a, b)
+    }
+
+    fn end(self) -> Result<(), Void> {
+        Ok(())
+    }
+}
+
+impl<'a> TupleDiffer for PathPart<'a> {
+    type Ok = ();
+    type Err = Void;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let segment = Segment::Field(self.next_index());
+        self.pair(segment, a, b)
+    }
+
+    fn skip_field<T: ?Sized>(&mut self) {
+        self.index += 1;
+    }
+
+    fn end(self) -> Result<(), Void> {
+        Ok(())
+    }
+}
+
+impl<'a> SeqDiffer for PathPart<'a> {
+    type Ok = ();
+    type Err = Void;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let segment = Segment::Key(self.next_index());
+        self.pair(segment, a, b)
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, _: &T)
+    where
+        T: Diff,
+    {
+        let segment = Segment::Key(self.next_index());
+        self.one_sided(segment)
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, _: &T)
+    where
+        T: Diff,
+    {
+        let segment = Segment::Key(self.next_index());
+        self.one_sided(segment)
+    }
+
+    fn end(self) -> Result<(), Void> {
+        Ok(())
+    }
+}
+
+impl<'a> MapDiffer for PathPart<'a> {
+    type Ok = ();
+    type Err = Void;
+
+    fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.pair(Segment::Key(key_text(&key)), a, b)
+    }
+
+    fn only_in_left<K, V>(&mut self, key: &K, _: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.one_sided(Segment::Key(key_text(&key)))
+    }
+
+    fn only_in_right<K, V>(&mut self, key: &K, _: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.one_sided(Segment::Key(key_text(&key)))
+    }
+
+    fn end(self) -> Result<(), Void> {
+        Ok(())
+    }
+}
+
+impl<'a> SetDiffer for PathPart<'a> {
+    type Ok = ();
+    type Err = Void;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.pair(Segment::Key(key_text(&a)), a, b)
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.one_sided(Segment::Key(key_text(&a)))
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.one_sided(Segment::Key(key_text(&b)))
+    }
+
+    fn end(self) -> Result<(), Void> {
+        Ok(())
+    }
+}
@@ -0,0 +1,475 @@
+//! Report differences as a self-contained HTML document.
+//!
+//! The document shows the structure of the values as a tree of collapsible
+//! nodes. Nodes containing differences start out expanded, and unchanged ones
+//! collapsed; changed values are shown struck out in red (left) and
+//! underlined in green (right). Every node has an anchor named after its
+//! path, so that a particular difference can be linked to.
+
+use core::fmt::{Debug, Write};
+
+use crate::{
+    Diff, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer, TupleDiffer,
+};
+
+use super::change::{Change, Tally};
+use super::debug::Whole;
+
+/// Renders the differences between `a` and `b` as an HTML document.
+///
+/// ```
+/// use visit_diff::{Diff, html_diff};
+///
+/// #[derive(Diff, Debug)]
+/// struct ExampleStruct {
+///     name: &'static str,
+///     age: usize,
+/// }
+///
+/// let left = ExampleStruct { name: "Bob", age: 4 };
+/// let right = ExampleStruct { name: "Bob", age: 5 };
+///
+/// let html = html_diff(&left, &right);
+/// assert!(html.contains("<del>4</del> <ins>5</ins>"));
+/// // The field can be linked to as `#diff.age`.
+/// assert!(html.contains(r#"id="diff.age""#));
+/// ```
+pub fn html_diff<T>(a: &T, b: &T) -> String
+where
+    T: Diff + ?Sized,
+{
+    let mut page = Page {
+        text: String::from(HEADER),
+        path: String::new(),
+    };
+    page.text.push_str("<ul class=\"root\">\n");
+    entry(&mut page, "", "", a, b);
+    page.text.push_str("</ul>\n</body>\n</html>\n");
+    page.text
+}
+
+const HEADER: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Diff</title>
+<style>
+body { font-family: monospace; }
+ul { list-style: none; margin: 0; padding-left: 2ch; }
+ul.root { padding-left: 0; }
+summary { cursor: pointer; }
+details:not([open]) > summary::after { content: " \2026 " attr(data-close); }
+del, ins { text-decoration: none; white-space: pre-wrap; }
+del { background: #fdd; color: #a00; }
+ins { background: #dfd; color: #070; }
+.same { color: #555; }
+.anchor { color: inherit; text-decoration: none; }
+.anchor:hover { text-decoration: underline; }
+:target { outline: 2px solid #fc0; }
+</style>
+</head>
+<body>
+"#;
+
+/// The document being written, and the path of the node being written.
+struct Page {
+    text: String,
+    path: String,
+}
+
+impl Page {
+    /// Produces an HTML id for the current path, using only characters that
+    /// need no escaping in attributes or URL fragments.
+    fn id(&self) -> String {
+        let mut id = String::from("diff");
+        for c in self.path.chars() {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                id.push(c);
+            } else {
+                for b in c.encode_utf8(&mut [0; 4]).bytes() {
+                    write!(id, "_{:02x}", b).unwrap();
+                }
+            }
+        }
+        id
+    }
+
+    /// Writes `value` with HTML special characters escaped.
+    fn escaped(&mut self, value: core::fmt::Arguments) {
+        let text = value.to_string();
+        for c in text.chars() {
+            match c {
+                '&' => self.text.push_str("&amp;"),
+                '<' => self.text.push_str("&lt;"),
+                '>' => self.text.push_str("&gt;"),
+                '"' => self.text.push_str("&quot;"),
+                '\'' => self.text.push_str("&#39;"),
+                c => self.text.push(c),
+            }
+        }
+    }
+
+    /// Writes a link to the current node, showing `label`.
+    fn anchor(&mut self, label: &str) {
+        if !label.is_empty() {
+            let id = self.id();
+            write!(self.text, "<a class=\"anchor\" href=\"#{}\">", id).unwrap();
+            self.escaped(format_args!("{}", label));
+            self.text.push_str("</a> ");
+        }
+    }
+}
+
+/// Renders one node of the tree, `segment` down from the current path.
+///
+/// The node is broken down as it's rendered. Afterwards, it's expanded if
+/// some of it differs, or replaced by both sides whole if all of it does.
+fn entry<T>(page: &mut Page, segment: &str, label: &str, a: &T, b: &T) -> Change
+where
+    T: Diff + ?Sized,
+{
+    let len = page.path.len();
+    page.path.push_str(segment);
+    let id = page.id();
+    write!(page.text, "<li id=\"{}\">", id).unwrap();
+
+    let mark = page.text.len();
+    let out = HtmlDiffer {
+        page: &mut *page,
+        label,
+    };
+    let change = Diff::diff(a, b, out).unwrap_or_else(|e| match e {});
+    match change {
+        Change::Entirely => {
+            page.text.truncate(mark);
+            let out = HtmlDiffer {
+                page: &mut *page,
+                label,
+            };
+            let _ = out.difference(&Whole(a), &Whole(b));
+        }
+        Change::Partly if page.text[mark..].starts_with(COLLAPSED) => {
+            page.text
+                .replace_range(mark..mark + COLLAPSED.len(), EXPANDED);
+        }
+        _ => (),
+    }
+
+    page.text.push_str("</li>\n");
+    page.path.truncate(len);
+    change
+}
+
+/// How a compound node's `<details>` tag starts, before and after expanding
+/// it. They're the same length, so that expanding a node doesn't move what
+/// comes after it.
+const COLLAPSED: &str = "<details     >";
+const EXPANDED: &str = "<details open>";
+
+/// Renders a node that only exists on one side, `segment` down from the
+/// current path.
+fn one_sided(
+    page: &mut Page,
+    segment: &str,
+    label: &str,
+    tag: &str,
+    value: &dyn Debug,
+) {
+    let len = page.path.len();
+    page.path.push_str(segment);
+    let id = page.id();
+    write!(page.text, "<li id=\"{}\">", id).unwrap();
+    page.anchor(label);
+    write!(page.text, "<{}>", tag).unwrap();
+    page.escaped(format_args!("{:#?}", value));
+    writeln!(page.text, "</{}></li>", tag).unwrap();
+    page.path.truncate(len);
+}
+
+/// Adapts a `Page` into a `Differ`, which renders the node whose `<li>` was
+/// just opened.
+struct HtmlDiffer<'a, 'l> {
+    page: &'a mut Page,
+    label: &'l str,
+}
+
+impl<'a, 'l> HtmlDiffer<'a, 'l> {
+    fn begin(self, name: &str, opener: &str, close: &'static str) -> Html<'a> {
+        self.page.text.push_str(COLLAPSED);
+        write!(self.page.text, "<summary data-close=\"{}\">", close).unwrap();
+        self.page.anchor(self.label);
+        self.page.escaped(format_args!("{}{}", name, opener));
+        self.page.text.push_str("</summary>\n<ul>\n");
+        Html {
+            page: self.page,
+            close,
+            index: 0,
+            tally: Tally::new(),
+        }
+    }
+}
+
+impl<'a, 'l> Differ for HtmlDiffer<'a, 'l> {
+    type Ok = Change;
+    type Err = core::convert::Infallible;
+
+    type StructDiffer = Html<'a>;
+    type StructVariantDiffer = Html<'a>;
+    type TupleDiffer = Html<'a>;
+    type TupleVariantDiffer = Html<'a>;
+    type SeqDiffer = Html<'a>;
+    type MapDiffer = Html<'a>;
+    type SetDiffer = Html<'a>;
+
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
+        self.page.anchor(self.label);
+        self.page.text.push_str("<del>");
+        self.page.escaped(format_args!("{:#?}", a));
+        self.page.text.push_str("</del> <ins>");
+        self.page.escaped(format_args!("{:#?}", b));
+        self.page.text.push_str("</ins>");
+        Ok(Change::Entirely)
+    }
+
+    fn same(self, a: &dyn Debug, _: &dyn Debug) -> Result<Self::Ok, Self::Err> {
+        self.page.anchor(self.label);
+        self.page.text.push_str("<span class=\"same\">");
+        self.page.escaped(format_args!("{:?}", a));
+        self.page.text.push_str("</span>");
+        Ok(Change::Unchanged)
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        ty: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<Self::Ok, Self::Err>
+    where
+        T: Diff,
+    {
+        // A newtype differs as much as what it wraps.
+        let out = self.begin(ty, "(", ")");
+        let change = entry(out.page, ".0", "0:", a, b);
+        out.finish()?;
+        Ok(change)
+    }
+
+    fn begin_struct(self, ty: &'static str) -> Self::StructDiffer {
+        self.begin(ty, " {", "}")
+    }
+
+    fn begin_struct_variant(
+        self,
+        _: &'static str,
+        var: &'static str,
+    ) -> Self::StructVariantDiffer {
+        self.begin(var, " {", "}")
+    }
+
+    fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+        self.begin(ty, "(", ")")
+    }
+
+    fn begin_tuple_variant(
+        self,
+        _: &'static str,
+        var: &'static str,
+    ) -> Self::TupleVariantDiffer {
+        self.begin(var, "(", ")")
+    }
+
+    fn begin_seq(self) -> Self::SeqDiffer {
+        self.begin("", "[", "]")
+    }
+
+    fn begin_map(self) -> Self::MapDiffer {
+        self.begin("", "{", "}")
+    }
+
+    fn begin_set(self) -> Self::SetDiffer {
+        self.begin("", "{", "}")
+    }
+}
+
+/// Renders the children of a compound node.
+struct Html<'a> {
+    page: &'a mut Page,
+    close: &'static str,
+    /// Position of the next tuple field, sequence element, or set element.
+    index: usize,
+    tally: Tally,
+}
+
+impl<'a> Html<'a> {
+    fn next_index(&mut self) -> usize {
+        self.index += 1;
+        self.index - 1
+    }
+
+    fn finish(self) -> Result<Change, core::convert::Infallible> {
+        write!(self.page.text, "</ul>{}</details>", self.close).unwrap();
+        Ok(self.tally.change())
+    }
+}
+
+impl<'a> StructDiffer for Html<'a> {
+    type Ok = Change;
+    type Err = core::convert::Infallible;
+
+    fn diff_field<T: ?Sized>(&mut self, name: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let segment = format!(".{}", name);
+        let change = entry(self.page, &segment, &format!("{}:", name), a, b);
+        self.tally.part(change)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> TupleDiffer for Html<'a> {
+    type Ok = Change;
+    type Err = core::convert::Infallible;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let i = self.next_index();
+        let change =
+            entry(self.page, &format!(".{}", i), &format!("{}:", i), a, b);
+        self.tally.part(change)
+    }
+
+    fn skip_field<T: ?Sized>(&mut self) {
+        self.index += 1;
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> SeqDiffer for Html<'a> {
+    type Ok = Change;
+    type Err = core::convert::Infallible;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let i = self.next_index();
+        let change =
+            entry(self.page, &format!("[{}]", i), &format!("{}:", i), a, b);
+        self.tally.part(change)
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, a: &T)
+    where
+        T: Diff,
+    {
+        let i = self.next_index();
+        let (segment, label) = (format!("[{}]", i), format!("{}:", i));
+        one_sided(self.page, &segment, &label, "del", &Whole(a));
+        self.tally.one_sided(true)
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, b: &T)
+    where
+        T: Diff,
+    {
+        let i = self.next_index();
+        let (segment, label) = (format!("[{}]", i), format!("{}:", i));
+        one_sided(self.page, &segment, &label, "ins", &Whole(b));
+        self.tally.one_sided(true)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> MapDiffer for Html<'a> {
+    type Ok = Change;
+    type Err = core::convert::Infallible;
+
+    fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let (segment, label) = (format!("[{:?}]", key), format!("{:?}:", key));
+        let change = entry(self.page, &segment, &label, a, b);
+        self.tally.part(change)
+    }
+
+    fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let (segment, label) = (format!("[{:?}]", key), format!("{:?}:", key));
+        one_sided(self.page, &segment, &label, "del", &Whole(a));
+        self.tally.one_sided(false)
+    }
+
+    fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let (segment, label) = (format!("[{:?}]", key), format!("{:?}:", key));
+        one_sided(self.page, &segment, &label, "ins", &Whole(b));
+        self.tally.one_sided(false)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+/// Set elements don't have positions of their own, so they're numbered in
+/// the order the `Diff` impl visits them.
+impl<'a> SetDiffer for Html<'a> {
+    type Ok = Change;
+    type Err = core::convert::Infallible;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let i = self.next_index();
+        let change = entry(self.page, &format!("[{}]", i), "", a, b);
+        self.tally.part(change)
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let i = self.next_index();
+        one_sided(self.page, &format!("[{}]", i), "", "del", &Whole(a));
+        self.tally.one_sided(false)
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let i = self.next_index();
+        one_sided(self.page, &format!("[{}]", i), "", "ins", &Whole(b));
+        self.tally.one_sided(false)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::debug::Whole;
 
 ////////////////////////////////////////////////////////////////////////////////
 // Unit-shaped things
@@ -42,6 +43,9 @@ macro_rules! tuple_impl {
     };
 }
 
+// `Diff` requires `Debug`, and tuples only implement `Debug` up to 12
+// elements, so that's as far as we go. Longer tuples can't implement `Diff`
+// without dropping that requirement; wrap them in a struct instead.
 tuple_impl!(A / 0);
 tuple_impl!(A / 0, B / 1);
 tuple_impl!(A / 0, B / 1, C / 2);
@@ -61,6 +65,45 @@ tuple_impl!(
     H / 7,
     I / 8
 );
+tuple_impl!(
+    A / 0,
+    B / 1,
+    C / 2,
+    D / 3,
+    E / 4,
+    F / 5,
+    G / 6,
+    H / 7,
+    I / 8,
+    J / 9
+);
+tuple_impl!(
+    A / 0,
+    B / 1,
+    C / 2,
+    D / 3,
+    E / 4,
+    F / 5,
+    G / 6,
+    H / 7,
+    I / 8,
+    J / 9,
+    K / 10
+);
+tuple_impl!(
+    A / 0,
+    B / 1,
+    C / 2,
+    D / 3,
+    E / 4,
+    F / 5,
+    G / 6,
+    H / 7,
+    I / 8,
+    J / 9,
+    K / 10,
+    L / 11
+);
 
 ////////////////////////////////////////////////////////////////////////////////
 // Slice and array boilerplate
@@ -79,56 +122,18 @@ where
     }
 }
 
-macro_rules! array_impl {
-    ($n:tt) => {
-        impl<T> Diff for [T; $n]
-        where
-            T: Diff,
-        {
-            fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
-            where
-                D: Differ,
-            {
-                Diff::diff(a as &[T], b as &[T], out)
-            }
-        }
-    };
+impl<T, const N: usize> Diff for [T; N]
+where
+    T: Diff,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(a as &[T], b as &[T], out)
+    }
 }
 
-array_impl!(0);
-array_impl!(1);
-array_impl!(2);
-array_impl!(3);
-array_impl!(4);
-array_impl!(5);
-array_impl!(6);
-array_impl!(7);
-array_impl!(8);
-array_impl!(9);
-array_impl!(10);
-array_impl!(11);
-array_impl!(12);
-array_impl!(13);
-array_impl!(14);
-array_impl!(15);
-array_impl!(16);
-array_impl!(17);
-array_impl!(18);
-array_impl!(19);
-array_impl!(20);
-array_impl!(21);
-array_impl!(22);
-array_impl!(23);
-array_impl!(24);
-array_impl!(25);
-array_impl!(26);
-array_impl!(27);
-array_impl!(28);
-array_impl!(29);
-array_impl!(30);
-array_impl!(31);
-array_impl!(32);
-
 ////////////////////////////////////////////////////////////////////////////////
 // References
 
@@ -229,6 +234,27 @@ macro_rules! impl_diff_partial_eq {
     };
 }
 
+/// Floating point numbers are the same if they're within the
+/// `float_tolerance` of the `DiffOptions`, which is zero by default.
+macro_rules! impl_diff_float {
+    ($ty:ty) => {
+        impl Diff for $ty {
+            fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+            where
+                D: Differ,
+            {
+                let tolerance = out.options().float_tolerance;
+                let distance = if a > b { a - b } else { b - a };
+                if a == b || f64::from(distance) <= tolerance {
+                    out.same(a, b)
+                } else {
+                    out.difference(a, b)
+                }
+            }
+        }
+    };
+}
+
 impl_diff_partial_eq!(bool);
 impl_diff_partial_eq!(char);
 impl_diff_partial_eq!(u8);
@@ -243,8 +269,8 @@ impl_diff_partial_eq!(i32);
 impl_diff_partial_eq!(i64);
 impl_diff_partial_eq!(i128);
 impl_diff_partial_eq!(isize);
-impl_diff_partial_eq!(f32);
-impl_diff_partial_eq!(f64);
+impl_diff_float!(f32);
+impl_diff_float!(f64);
 impl_diff_partial_eq!(unsized str);
 impl_diff_partial_eq!(core::cmp::Ordering);
 impl_diff_partial_eq!(core::time::Duration);
@@ -257,6 +283,39 @@ impl_diff_partial_eq!(core::ops::RangeFrom<T> | T);
 impl_diff_partial_eq!(core::ops::RangeFull);
 impl_diff_partial_eq!(core::ops::RangeTo<T> | T);
 impl_diff_partial_eq!(core::ops::RangeToInclusive<T> | T);
+impl_diff_partial_eq!(core::ops::RangeInclusive<T> | T);
+
+impl_diff_partial_eq!(core::num::NonZeroU8);
+impl_diff_partial_eq!(core::num::NonZeroU16);
+impl_diff_partial_eq!(core::num::NonZeroU32);
+impl_diff_partial_eq!(core::num::NonZeroU64);
+impl_diff_partial_eq!(core::num::NonZeroU128);
+impl_diff_partial_eq!(core::num::NonZeroUsize);
+impl_diff_partial_eq!(core::num::NonZeroI8);
+impl_diff_partial_eq!(core::num::NonZeroI16);
+impl_diff_partial_eq!(core::num::NonZeroI32);
+impl_diff_partial_eq!(core::num::NonZeroI64);
+impl_diff_partial_eq!(core::num::NonZeroI128);
+impl_diff_partial_eq!(core::num::NonZeroIsize);
+
+impl_diff_partial_eq!(core::any::TypeId);
+impl_diff_partial_eq!(core::alloc::Layout);
+impl_diff_partial_eq!(core::convert::Infallible);
+
+// Error types are atomic. Their internals (where they have any) are private
+// and mostly exist to be printed.
+impl_diff_partial_eq!(core::fmt::Error);
+impl_diff_partial_eq!(core::alloc::LayoutError);
+impl_diff_partial_eq!(core::char::CharTryFromError);
+impl_diff_partial_eq!(core::char::DecodeUtf16Error);
+impl_diff_partial_eq!(core::char::ParseCharError);
+impl_diff_partial_eq!(core::char::TryFromCharError);
+impl_diff_partial_eq!(core::num::IntErrorKind);
+impl_diff_partial_eq!(core::num::ParseFloatError);
+impl_diff_partial_eq!(core::num::ParseIntError);
+impl_diff_partial_eq!(core::num::TryFromIntError);
+impl_diff_partial_eq!(core::str::ParseBoolError);
+impl_diff_partial_eq!(core::str::Utf8Error);
 
 /// Pointers diff by address, not by contents.
 impl<T: ?Sized> Diff for *const T {
@@ -264,7 +323,7 @@ impl<T: ?Sized> Diff for *const T {
     where
         D: Differ,
     {
-        if a != b {
+        if !core::ptr::eq(*a, *b) {
             out.difference(a, b)
         } else {
             out.same(a, b)
@@ -278,7 +337,7 @@ impl<T: ?Sized> Diff for *mut T {
     where
         D: Differ,
     {
-        if a != b {
+        if !core::ptr::eq(*a, *b) {
             out.difference(a, b)
         } else {
             out.same(a, b)
@@ -308,7 +367,7 @@ impl<T: ?Sized + Diff> Diff for core::mem::ManuallyDrop<T> {
         D: Differ,
     {
         let mut out = out.begin_struct("ManuallyDrop");
-        out.diff_field("value", &*a, &*b);
+        out.diff_field("value", &**a, &**b);
         out.end()
     }
 }
@@ -322,6 +381,40 @@ impl<T: Diff> Diff for core::num::Wrapping<T> {
     }
 }
 
+impl<T: Diff> Diff for core::num::Saturating<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(&a.0, &b.0, out)
+    }
+}
+
+impl<T: Diff> Diff for core::cmp::Reverse<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let mut out = out.begin_tuple("Reverse");
+        out.diff_field(&a.0, &b.0);
+        out.end()
+    }
+}
+
+/// Diff pins by dereferencing.
+impl<P> Diff for core::pin::Pin<P>
+where
+    P: core::ops::Deref + Debug,
+    P::Target: Diff,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(&**a, &**b, out)
+    }
+}
+
 /// Note that this *will* panic if the RefCell is mutably borrowed.
 impl<T: ?Sized + Diff> Diff for core::cell::RefCell<T> {
     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
@@ -334,6 +427,24 @@ impl<T: ?Sized + Diff> Diff for core::cell::RefCell<T> {
     }
 }
 
+/// An uninitialized cell is only the same as another uninitialized cell.
+impl<T: Diff> Diff for core::cell::OnceCell<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        match (a.get(), b.get()) {
+            (None, None) => out.same(a, b),
+            (Some(a), Some(b)) => {
+                let mut out = out.begin_tuple("OnceCell");
+                out.diff_field(a, b);
+                out.end()
+            }
+            _ => out.difference(&Whole(a), &Whole(b)),
+        }
+    }
+}
+
 impl<T: Diff> Diff for core::option::Option<T> {
     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
     where
@@ -346,7 +457,7 @@ impl<T: Diff> Diff for core::option::Option<T> {
                 out.diff_field(a, b);
                 out.end()
             }
-            _ => out.difference(a, b),
+            _ => out.difference(&Whole(a), &Whole(b)),
         }
     }
 }
@@ -367,11 +478,128 @@ impl<T: Diff, E: Diff> Diff for core::result::Result<T, E> {
                 out.diff_field(a, b);
                 out.end()
             }
-            _ => out.difference(a, b),
+            _ => out.difference(&Whole(a), &Whole(b)),
         }
     }
 }
 
+impl<T: Diff> Diff for core::ops::Bound<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        use core::ops::Bound::*;
+        match (a, b) {
+            (Included(a), Included(b)) => {
+                let mut out = out.begin_tuple_variant("Bound", "Included");
+                out.diff_field(a, b);
+                out.end()
+            }
+            (Excluded(a), Excluded(b)) => {
+                let mut out = out.begin_tuple_variant("Bound", "Excluded");
+                out.diff_field(a, b);
+                out.end()
+            }
+            (Unbounded, Unbounded) => out.same(a, b),
+            _ => out.difference(&Whole(a), &Whole(b)),
+        }
+    }
+}
+
+impl<B: Diff, C: Diff> Diff for core::ops::ControlFlow<B, C> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        use core::ops::ControlFlow::*;
+        match (a, b) {
+            (Continue(a), Continue(b)) => {
+                let mut out =
+                    out.begin_tuple_variant("ControlFlow", "Continue");
+                out.diff_field(a, b);
+                out.end()
+            }
+            (Break(a), Break(b)) => {
+                let mut out = out.begin_tuple_variant("ControlFlow", "Break");
+                out.diff_field(a, b);
+                out.end()
+            }
+            _ => out.difference(&Whole(a), &Whole(b)),
+        }
+    }
+}
+
+impl<T: Diff> Diff for core::task::Poll<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        use core::task::Poll::*;
+        match (a, b) {
+            (Ready(a), Ready(b)) => {
+                let mut out = out.begin_tuple_variant("Poll", "Ready");
+                out.diff_field(a, b);
+                out.end()
+            }
+            (Pending, Pending) => out.same(a, b),
+            _ => out.difference(&Whole(a), &Whole(b)),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Atomics. Each side is loaded once and then diffed as a plain value, which is
+// how their Debug impls present them.
+
+macro_rules! atomic_impl {
+    ($ty:ident) => {
+        impl Diff for core::sync::atomic::$ty {
+            fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+            where
+                D: Differ,
+            {
+                use core::sync::atomic::Ordering::Relaxed;
+                Diff::diff(&a.load(Relaxed), &b.load(Relaxed), out)
+            }
+        }
+    };
+}
+
+#[cfg(target_has_atomic = "8")]
+atomic_impl!(AtomicBool);
+#[cfg(target_has_atomic = "8")]
+atomic_impl!(AtomicU8);
+#[cfg(target_has_atomic = "8")]
+atomic_impl!(AtomicI8);
+#[cfg(target_has_atomic = "16")]
+atomic_impl!(AtomicU16);
+#[cfg(target_has_atomic = "16")]
+atomic_impl!(AtomicI16);
+#[cfg(target_has_atomic = "32")]
+atomic_impl!(AtomicU32);
+#[cfg(target_has_atomic = "32")]
+atomic_impl!(AtomicI32);
+#[cfg(target_has_atomic = "64")]
+atomic_impl!(AtomicU64);
+#[cfg(target_has_atomic = "64")]
+atomic_impl!(AtomicI64);
+#[cfg(target_has_atomic = "ptr")]
+atomic_impl!(AtomicUsize);
+#[cfg(target_has_atomic = "ptr")]
+atomic_impl!(AtomicIsize);
+
+/// Atomic pointers diff by address, like the pointers they contain.
+#[cfg(target_has_atomic = "ptr")]
+impl<T> Diff for core::sync::atomic::AtomicPtr<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        use core::sync::atomic::Ordering::Relaxed;
+        Diff::diff(&a.load(Relaxed), &b.load(Relaxed), out)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -406,6 +634,7 @@ mod tests {
         }
     }
 
+    #[allow(unused)]
     #[derive(Clone, Debug)]
     pub struct TestStruct {
         pub distance: usize,
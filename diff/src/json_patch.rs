@@ -0,0 +1,882 @@
+//! Describes differences as a JSON Patch ([RFC 6902]) or a JSON Merge Patch
+//! ([RFC 7386]), which other programs can apply to their own copy of the
+//! left-hand value to bring it up to date.
+//!
+//! ```
+//! use serde_json::json;
+//! use visit_diff::Diff;
+//! use visit_diff::json_patch::{json_patch, merge_patch};
+//!
+//! #[derive(Debug, Diff)]
+//! struct Player {
+//!     name: &'static str,
+//!     scores: Vec<u32>,
+//! }
+//!
+//! let a = Player { name: "Ann", scores: vec![1, 2] };
+//! let b = Player { name: "Ann", scores: vec![1, 3, 4] };
+//!
+//! assert_eq!(
+//!     json_patch(&a, &b),
+//!     json!([
+//!         {"op": "replace", "path": "/scores/1", "value": 3},
+//!         {"op": "add", "path": "/scores/2", "value": 4},
+//!     ]),
+//! );
+//! assert_eq!(merge_patch(&a, &b), json!({"scores": [1, 3, 4]}));
+//! ```
+//!
+//! # How Rust values become JSON
+//!
+//! Paths are built from the structure reported to the [`Differ`]: struct
+//! fields and map keys become object members, and tuple fields and sequence
+//! elements become array indices. Tuple structs and variants with a single
+//! field, like `Some(x)`, are transparent, the way serde treats newtypes.
+//!
+//! Values, on the other hand, only ever reach a `Differ` through their `Debug`
+//! impls, so that's where they're taken from: [`debug_to_json`] reads derived
+//! `Debug` output back in, turning structs into objects, tuples and lists into
+//! arrays, `None`, `()` and `null` into `null`, and other names (such as unit
+//! variants) into strings. Placeholders such as `<redacted>`, which stand in
+//! for values that aren't shown, are strings too. Type and variant names are
+//! dropped. Sets become arrays, except that an empty set looks just like an
+//! empty map, and becomes `{}`. Text that can't be read is kept as a single
+//! string.
+//!
+//! # Moves
+//!
+//! When an entry is removed from a map or struct and an entry with an equal
+//! value is added under a different key, the JSON Patch uses a `move`
+//! operation rather than a `remove` and an `add`.
+//!
+//! [RFC 6902]: https://tools.ietf.org/html/rfc6902
+//! [RFC 7386]: https://tools.ietf.org/html/rfc7386
+//! [`Differ`]: ../trait.Differ.html
+//! [`debug_to_json`]: fn.debug_to_json.html
+
+use std::fmt::Debug;
+
+use serde_json::{json, Map, Value};
+use void::{ResultVoidExt, Void};
+
+use crate::debug::Whole;
+use crate::{
+    Diff, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer, TupleDiffer,
+};
+
+/// Produces a JSON Patch that turns `a` into `b`, as an array of operations.
+pub fn json_patch<T: Diff + ?Sized>(a: &T, b: &T) -> Value {
+    Diff::diff(a, b, PatchDiffer).void_unwrap().to_json_patch()
+}
+
+/// Produces a JSON Merge Patch that turns `a` into `b`.
+///
+/// Merge patches use `null` to mean "remove this member", so a member that
+/// changes *to* `null` can't be represented, and is removed instead.
+pub fn merge_patch<T: Diff + ?Sized>(a: &T, b: &T) -> Value {
+    Diff::diff(a, b, PatchDiffer).void_unwrap().to_merge_patch()
+}
+
+/// A `Differ` that works out which parts of a value changed, producing a
+/// [`Patch`].
+///
+/// [`Patch`]: struct.Patch.html
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PatchDiffer;
+
+/// Changes found by a [`PatchDiffer`], which can be written out in either
+/// patch format.
+///
+/// [`PatchDiffer`]: struct.PatchDiffer.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patch(Delta);
+
+impl Patch {
+    /// Checks whether anything changed.
+    pub fn is_empty(&self) -> bool {
+        self.0 == Delta::Same
+    }
+
+    /// Writes out the changes as a JSON Patch: an array of operations.
+    pub fn to_json_patch(&self) -> Value {
+        let mut ops = vec![];
+        self.0.json_patch(&mut String::new(), &mut ops);
+        Value::Array(ops)
+    }
+
+    /// Writes out the changes as a JSON Merge Patch.
+    pub fn to_merge_patch(&self) -> Value {
+        self.0.merge_patch()
+    }
+}
+
+/// How a value changed.
+#[derive(Clone, Debug, PartialEq)]
+enum Delta {
+    /// Not at all.
+    Same,
+    /// It was replaced wholesale; these are the old and new values.
+    Replace(Value, Value),
+    /// Members of an object changed.
+    Object(Vec<(String, Change)>),
+    /// Elements of an array changed. The new elements are kept for merge
+    /// patches, which can't describe changes within arrays.
+    Array(Vec<(usize, Change)>, Vec<Value>),
+}
+
+/// How a member of an object or element of an array changed.
+#[derive(Clone, Debug, PartialEq)]
+enum Change {
+    Modified(Delta),
+    Removed(Value),
+    Added(Value),
+}
+
+impl Delta {
+    fn json_patch(&self, path: &mut String, ops: &mut Vec<Value>) {
+        match self {
+            Delta::Same => (),
+            Delta::Replace(_, b) => ops.push(json!({
+                "op": "replace",
+                "path": path.as_str(),
+                "value": b,
+            })),
+            Delta::Object(members) => {
+                let moves = find_moves(members);
+                for (i, (key, change)) in members.iter().enumerate() {
+                    match moves.iter().find(|&&(from, _)| from == i) {
+                        Some(&(_, to)) => ops.push(json!({
+                            "op": "move",
+                            "from": member_path(path, key),
+                            "path": member_path(path, &members[to].0),
+                        })),
+                        None if moves.iter().any(|&(_, to)| to == i) => (),
+                        None => change.json_patch(path, key, ops),
+                    }
+                }
+            }
+            Delta::Array(elements, _) => {
+                // Elements removed from the end of an array are removed last
+                // first, so that each index is still valid when it's used.
+                let (removed, rest): (Vec<_>, Vec<_>) = elements
+                    .iter()
+                    .partition(|(_, c)| matches!(c, Change::Removed(_)));
+                for (i, change) in
+                    rest.into_iter().chain(removed.into_iter().rev())
+                {
+                    change.json_patch(path, &i.to_string(), ops);
+                }
+            }
+        }
+    }
+
+    fn merge_patch(&self) -> Value {
+        match self {
+            Delta::Same => Value::Object(Map::new()),
+            Delta::Replace(a, b) => merge_values(a, b),
+            Delta::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(key, change)| {
+                        let value = match change {
+                            Change::Modified(d) => d.merge_patch(),
+                            Change::Removed(_) => Value::Null,
+                            Change::Added(b) => b.clone(),
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            ),
+            Delta::Array(_, b) => Value::Array(b.clone()),
+        }
+    }
+}
+
+impl Change {
+    fn json_patch(&self, path: &mut String, key: &str, ops: &mut Vec<Value>) {
+        let len = path.len();
+        push_segment(path, key);
+        match self {
+            Change::Modified(d) => d.json_patch(path, ops),
+            Change::Removed(_) => {
+                ops.push(json!({"op": "remove", "path": path.as_str()}))
+            }
+            Change::Added(b) => ops.push(json!({
+                "op": "add",
+                "path": path.as_str(),
+                "value": b,
+            })),
+        }
+        path.truncate(len);
+    }
+}
+
+/// Pairs up removed and added members with equal values, returning their
+/// indices.
+fn find_moves(members: &[(String, Change)]) -> Vec<(usize, usize)> {
+    let mut moves: Vec<(usize, usize)> = vec![];
+    for (i, (_, change)) in members.iter().enumerate() {
+        if let Change::Removed(a) = change {
+            let to = members.iter().enumerate().position(|(j, (_, c))| {
+                matches!(c, Change::Added(b) if a == b)
+                    && !moves.iter().any(|&(_, to)| to == j)
+            });
+            if let Some(to) = to {
+                moves.push((i, to));
+            }
+        }
+    }
+    moves
+}
+
+fn push_segment(path: &mut String, key: &str) {
+    path.push('/');
+    path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+}
+
+fn member_path(path: &str, key: &str) -> String {
+    let mut path = path.to_string();
+    push_segment(&mut path, key);
+    path
+}
+
+/// Merge patch turning `a` into `b`, for values that have no structure in
+/// common as far as the `Diff` impl is concerned. If both are objects, they
+/// can still be merged member by member -- and must be, because a merge patch
+/// that's an object is never applied as a wholesale replacement.
+fn merge_values(a: &Value, b: &Value) -> Value {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut patch = Map::new();
+            for k in a.keys().filter(|k| !b.contains_key(*k)) {
+                patch.insert(k.clone(), Value::Null);
+            }
+            for (k, vb) in b {
+                match a.get(k) {
+                    Some(va) if va == vb => (),
+                    Some(va) => {
+                        patch.insert(k.clone(), merge_values(va, vb));
+                    }
+                    None => {
+                        patch.insert(k.clone(), vb.clone());
+                    }
+                }
+            }
+            Value::Object(patch)
+        }
+        _ => b.clone(),
+    }
+}
+
+impl Differ for PatchDiffer {
+    type Ok = Patch;
+    type Err = Void;
+
+    type StructDiffer = Members;
+    type StructVariantDiffer = Members;
+    type TupleDiffer = Elements;
+    type TupleVariantDiffer = Elements;
+    type SeqDiffer = Elements;
+    type MapDiffer = Members;
+    type SetDiffer = SetElements;
+
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
+        Ok(Patch(Delta::Replace(debug_to_json(a), debug_to_json(b))))
+    }
+
+    fn same(self, _: &dyn Debug, _: &dyn Debug) -> Result<Self::Ok, Self::Err> {
+        Ok(Patch(Delta::Same))
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        _: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<Self::Ok, Self::Err>
+    where
+        T: Diff,
+    {
+        Diff::diff(a, b, self)
+    }
+
+    fn begin_struct(self, _: &'static str) -> Self::StructDiffer {
+        Members::default()
+    }
+
+    fn begin_struct_variant(
+        self,
+        _: &'static str,
+        _: &'static str,
+    ) -> Self::StructVariantDiffer {
+        Members::default()
+    }
+
+    fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+        Elements::new(!ty.is_empty())
+    }
+
+    fn begin_tuple_variant(
+        self,
+        _: &'static str,
+        _: &'static str,
+    ) -> Self::TupleVariantDiffer {
+        Elements::new(true)
+    }
+
+    fn begin_seq(self) -> Self::SeqDiffer {
+        Elements::new(false)
+    }
+
+    fn begin_map(self) -> Self::MapDiffer {
+        Members::default()
+    }
+
+    fn begin_set(self) -> Self::SetDiffer {
+        SetElements::default()
+    }
+}
+
+fn diff<T: Diff + ?Sized>(a: &T, b: &T) -> Delta {
+    Diff::diff(a, b, PatchDiffer).void_unwrap().0
+}
+
+/// Collects changes to the members of structs and maps.
+#[derive(Default)]
+#[doc(hidden)]
+pub struct Members(Vec<(String, Change)>);
+
+impl Members {
+    fn finish(self) -> Result<Patch, Void> {
+        Ok(Patch(if self.0.is_empty() {
+            Delta::Same
+        } else {
+            Delta::Object(self.0)
+        }))
+    }
+
+    fn modified(&mut self, key: String, delta: Delta) {
+        if delta != Delta::Same {
+            self.0.push((key, Change::Modified(delta)));
+        }
+    }
+}
+
+impl StructDiffer for Members {
+    type Ok = Patch;
+    type Err = Void;
+
+    fn diff_field<T: ?Sized>(&mut self, name: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.modified(name.to_string(), diff(a, b))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl MapDiffer for Members {
+    type Ok = Patch;
+    type Err = Void;
+
+    fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.modified(key_to_string(&key), diff(a, b))
+    }
+
+    fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.0.push((
+            key_to_string(&key),
+            Change::Removed(debug_to_json(&Whole(a))),
+        ))
+    }
+
+    fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.0.push((
+            key_to_string(&key),
+            Change::Added(debug_to_json(&Whole(b))),
+        ))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+/// Collects changes to the elements of tuples and sequences.
+#[doc(hidden)]
+pub struct Elements {
+    /// Whether a single element should stand in for the whole, as for
+    /// `Some(x)`.
+    newtype: bool,
+    changes: Vec<(usize, Change)>,
+    right: Vec<Value>,
+    /// Index of the next element on the left; this stops counting up at the
+    /// end of the left sequence.
+    index: usize,
+}
+
+impl Elements {
+    fn new(newtype: bool) -> Self {
+        Elements {
+            newtype,
+            changes: vec![],
+            right: vec![],
+            index: 0,
+        }
+    }
+
+    fn element<T: Diff + ?Sized>(&mut self, a: &T, b: &T) {
+        let delta = diff(a, b);
+        if delta != Delta::Same {
+            self.changes.push((self.index, Change::Modified(delta)));
+        }
+        self.right.push(debug_to_json(&Whole(b)));
+        self.index += 1;
+    }
+
+    fn finish(mut self) -> Result<Patch, Void> {
+        if self.changes.is_empty() {
+            return Ok(Patch(Delta::Same));
+        }
+        if self.newtype && self.right.len() == 1 && self.index == 1 {
+            if let Some((_, Change::Modified(d))) = self.changes.pop() {
+                return Ok(Patch(d));
+            }
+        }
+        Ok(Patch(Delta::Array(self.changes, self.right)))
+    }
+}
+
+impl TupleDiffer for Elements {
+    type Ok = Patch;
+    type Err = Void;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.element(a, b)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl SeqDiffer for Elements {
+    type Ok = Patch;
+    type Err = Void;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.element(a, b)
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, a: &T)
+    where
+        T: Diff,
+    {
+        self.changes
+            .push((self.index, Change::Removed(debug_to_json(&Whole(a)))));
+        self.index += 1;
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, b: &T)
+    where
+        T: Diff,
+    {
+        let b = debug_to_json(&Whole(b));
+        self.changes
+            .push((self.right.len(), Change::Added(b.clone())));
+        self.right.push(b);
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+/// Collects the elements of sets. Set elements have no paths of their own, so
+/// a set that changed at all is replaced as a whole.
+#[derive(Default)]
+#[doc(hidden)]
+pub struct SetElements {
+    changed: bool,
+    left: Vec<Value>,
+    right: Vec<Value>,
+}
+
+impl SetDiffer for SetElements {
+    type Ok = Patch;
+    type Err = Void;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.changed |= diff(a, b) != Delta::Same;
+        self.left.push(debug_to_json(&Whole(a)));
+        self.right.push(debug_to_json(&Whole(b)));
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.changed = true;
+        self.left.push(debug_to_json(&Whole(a)));
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.changed = true;
+        self.right.push(debug_to_json(&Whole(b)));
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        Ok(Patch(if self.changed {
+            Delta::Replace(Value::Array(self.left), Value::Array(self.right))
+        } else {
+            Delta::Same
+        }))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Reading Debug output.
+
+/// Converts a value to JSON by reading its `Debug` output.
+///
+/// This understands the output of derived `Debug` impls and those in the
+/// standard library, as described in the [module docs]. Anything else becomes
+/// a JSON string holding the `Debug` output.
+///
+/// Since `Debug` output isn't meant to be read back in, this is guesswork,
+/// and it goes wrong in some cases:
+///
+/// - Hand-written `Debug` impls can print anything. Output that happens to
+///   look like derived output is read as if it were, so a type that prints
+///   `Point { x: 1 }` becomes an object whatever it really holds, and one
+///   that prints a bare word becomes a string.
+/// - Type names are dropped, so different types with the same fields, or an
+///   empty set and an empty map, come out the same.
+/// - Integers too big for JSON, infinities, and NaN are kept as strings.
+/// - Map keys that aren't strings are turned into their JSON text.
+/// - Values nested more than 128 levels deep are kept as a string.
+///
+/// It never panics: anything it can't read ends up as a string.
+///
+/// ```
+/// use serde_json::json;
+/// use visit_diff::json_patch::debug_to_json;
+///
+/// #[derive(Debug)]
+/// struct Point {
+///     x: i32,
+///     y: Option<f64>,
+/// }
+///
+/// assert_eq!(
+///     debug_to_json(&Point { x: 1, y: Some(2.5) }),
+///     json!({"x": 1, "y": 2.5}),
+/// );
+/// assert_eq!(debug_to_json(&vec!["a", "b"]), json!(["a", "b"]));
+/// ```
+///
+/// [module docs]: index.html
+pub fn debug_to_json(value: &dyn Debug) -> Value {
+    let text = format!("{:?}", value);
+    let mut p = Parser(&text, 0);
+    match p.value() {
+        Some(v) if p.at_end() => v,
+        _ => Value::String(text),
+    }
+}
+
+/// Map keys are used as-is if they're strings, and otherwise in their JSON
+/// form.
+fn key_to_string(key: &dyn Debug) -> String {
+    match debug_to_json(key) {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+/// How deeply values can be nested before `debug_to_json` gives up on reading
+/// them, rather than run out of stack.
+const MAX_DEPTH: usize = 128;
+
+/// A recursive-descent parser for `Debug` output, holding the input that's
+/// left and how deeply nested it is.
+struct Parser<'a>(&'a str, usize);
+
+impl<'a> Parser<'a> {
+    fn skip_space(&mut self) {
+        self.0 = self.0.trim_start();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_space();
+        self.0.is_empty()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.0.chars().next()
+    }
+
+    /// Consumes `token` if it's next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_space();
+        match self.0.strip_prefix(token) {
+            Some(rest) => {
+                self.0 = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Consumes a run of characters matching `f`, which may be empty.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let end = self.0.find(|c| !f(c)).unwrap_or(self.0.len());
+        let (taken, rest) = self.0.split_at(end);
+        self.0 = rest;
+        taken
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        if self.1 == MAX_DEPTH {
+            return None;
+        }
+        self.1 += 1;
+        let value = self.unnested_value();
+        self.1 -= 1;
+        value
+    }
+
+    fn unnested_value(&mut self) -> Option<Value> {
+        match self.peek()? {
+            '"' => self.quoted('"').map(Value::String),
+            '\'' => self.quoted('\'').map(Value::String),
+            '[' => {
+                self.eat("[");
+                self.items("]").map(Value::Array)
+            }
+            '(' => {
+                self.eat("(");
+                let items = self.items(")")?;
+                Some(if items.is_empty() {
+                    Value::Null
+                } else {
+                    Value::Array(items)
+                })
+            }
+            '{' => {
+                self.eat("{");
+                self.braces()
+            }
+            '<' => {
+                // A placeholder, such as `<redacted>`.
+                let end = self.0.find('>')? + 1;
+                let text = &self.0[..end];
+                self.0 = &self.0[end..];
+                Some(Value::String(text.to_string()))
+            }
+            c if c == '-' || c.is_ascii_digit() => self.number(),
+            c if c.is_alphabetic() || c == '_' => self.named(),
+            _ => None,
+        }
+    }
+
+    /// Parses comma-separated values up to `close`, allowing a trailing
+    /// comma.
+    fn items(&mut self, close: &str) -> Option<Vec<Value>> {
+        let mut items = vec![];
+        while !self.eat(close) {
+            items.push(self.value()?);
+            if !self.eat(",") {
+                return if self.eat(close) { Some(items) } else { None };
+            }
+        }
+        Some(items)
+    }
+
+    /// Parses the contents of braces that don't follow a name: either a map,
+    /// or a set.
+    fn braces(&mut self) -> Option<Value> {
+        if self.eat("}") {
+            return Some(Value::Object(Map::new()));
+        }
+        let first = self.value()?;
+        if !self.eat(":") {
+            let mut items = vec![first];
+            if self.eat(",") {
+                items.extend(self.items("}")?);
+            } else if !self.eat("}") {
+                return None;
+            }
+            return Some(Value::Array(items));
+        }
+
+        let mut map = Map::new();
+        let mut key = first;
+        loop {
+            let value = self.value()?;
+            map.insert(
+                match key {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                },
+                value,
+            );
+            if !self.eat(",") {
+                return if self.eat("}") {
+                    Some(Value::Object(map))
+                } else {
+                    None
+                };
+            }
+            if self.eat("}") {
+                return Some(Value::Object(map));
+            }
+            key = self.value()?;
+            if !self.eat(":") {
+                return None;
+            }
+        }
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let text = self.take_while(|c| {
+            c.is_ascii_alphanumeric() || c == '-' || c == '+' || c == '.'
+        });
+        let is_float = text.contains(['.', 'e', 'E']);
+        if let Ok(x) = text.parse::<i64>() {
+            Some(x.into())
+        } else if let Ok(x) = text.parse::<u64>() {
+            Some(x.into())
+        } else {
+            match text.parse::<f64>() {
+                Ok(x) if is_float && x.is_finite() => Some(x.into()),
+                // Integers too big for JSON numbers, infinities, and other
+                // oddities are kept as written.
+                _ if !text.is_empty() => Some(Value::String(text.to_string())),
+                _ => None,
+            }
+        }
+    }
+
+    /// Parses something that starts with a name: a keyword, a unit variant, or
+    /// a struct or tuple with its type name.
+    fn named(&mut self) -> Option<Value> {
+        let name =
+            self.take_while(|c| c.is_alphanumeric() || c == '_' || c == ':');
+        if self.eat("{") {
+            let mut map = Map::new();
+            loop {
+                if self.eat("}") {
+                    break;
+                }
+                // Non-exhaustive structs end in `..`.
+                if self.eat("..") {
+                    continue;
+                }
+                self.skip_space();
+                let field = self.take_while(|c| {
+                    c.is_alphanumeric() || c == '_' || c == '#'
+                });
+                if field.is_empty() || !self.eat(":") {
+                    return None;
+                }
+                map.insert(field.to_string(), self.value()?);
+                if !self.eat(",") {
+                    if self.eat("}") {
+                        break;
+                    }
+                    return None;
+                }
+            }
+            return Some(Value::Object(map));
+        }
+        if self.eat("(") {
+            let mut items = self.items(")")?;
+            return Some(if items.len() == 1 {
+                items.pop().unwrap()
+            } else {
+                Value::Array(items)
+            });
+        }
+        Some(match name {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "None" | "null" => Value::Null,
+            _ => Value::String(name.to_string()),
+        })
+    }
+
+    /// Parses a string or character literal, undoing `Debug` escaping.
+    fn quoted(&mut self, quote: char) -> Option<String> {
+        let mut chars = self.0.strip_prefix(quote)?.char_indices();
+        let mut out = String::new();
+        loop {
+            let (i, c) = chars.next()?;
+            if c == quote {
+                self.0 = &self.0[i + 2 * quote.len_utf8()..];
+                return Some(out);
+            }
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            out.push(match chars.next()?.1 {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                'u' => {
+                    if chars.next()?.1 != '{' {
+                        return None;
+                    }
+                    let mut code = 0u32;
+                    loop {
+                        match chars.next()?.1 {
+                            '}' => break,
+                            c => {
+                                code = code
+                                    .checked_mul(16)?
+                                    .checked_add(c.to_digit(16)?)?
+                            }
+                        }
+                    }
+                    std::char::from_u32(code)?
+                }
+                c @ ('\\' | '"' | '\'') => c,
+                _ => return None,
+            });
+        }
+    }
+}
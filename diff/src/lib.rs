@@ -11,7 +11,24 @@
 //! - [`any_difference`] and [`all_different`] scan values for differences and
 //!   return a `bool`.
 //!
-//! You can derive [`Diff`] for any custom type that implements `Debug`.
+//! You can derive [`Diff`] for any custom type that implements `Debug`. Fields
+//! marked `#[diff(redact)]` are compared, but shown only as `<redacted>`; see
+//! [`Redacted`].
+//!
+//! Derived impls give the `Differ` names as they're written in the source, but
+//! without any `r#`. `#[diff(rename = "...")]` renames a field or variant,
+//! `#[diff(name = "...")]` renames the type, and `#[diff(rename_all = "...")]`
+//! renames all fields of a struct or variant, or all variants of an enum,
+//! following one of serde's conventions, like `"camelCase"`. Unit variants are
+//! shown using `Debug`, so they keep their names.
+//!
+//! Tuple structs with one field are presented as newtypes, using
+//! [`Differ::diff_newtype`]. A struct with one field marked
+//! `#[diff(transparent)]` is diffed as though it were that field, without
+//! being mentioned at all.
+//!
+//! Types from other crates can be diffed through a mirror of their definition
+//! marked `#[diff(remote = "...")]`; see [`DiffRemote`].
 //!
 //! # Under the hood
 //!
@@ -56,16 +73,76 @@
 //! This crate is `no_std` compatible, in case you want to diff data structures
 //! in a deeply-embedded system.
 //!
+//! # Types from other crates
+//!
+//! [`Diff`] impls for types from some popular crates can be turned on with
+//! cargo features named after those crates: `arrayvec`, `bytes`, `chrono`,
+//! `indexmap`, `ordered-float`, `smallvec`, `time`, `url`, and `uuid`.
+//!
+//! The `serde_json`, `serde_yaml`, and `toml` features do the same for the
+//! dynamic `Value` types those crates use to represent whole documents.
+//! Objects are diffed as maps and arrays as sequences, so comparing two JSON
+//! responses with [`debug_diff`] points at the parts that changed. These
+//! features turn on `std` too.
+//!
+//! Types that implement `serde::Serialize` but not [`Diff`] can still be
+//! diffed through the [`serde`] module, which requires the `serde` feature.
+//!
+//! The [`limit`] module bounds how much of a diff is reported, which helps
+//! when diffing large values for a panic message or log. The [`filter`]
+//! module ignores parts of values, such as timestamps, for a single diff.
+//! Both can be set, along with a tolerance for comparing floating point
+//! numbers, in a [`DiffOptions`] passed to [`debug_diff_with`] and the other
+//! `*_with` functions.
+//!
+//! To count differences instead of describing them, for logging or metrics,
+//! use [`diff_stats`]. To score how alike two values are, from 0.0 to 1.0,
+//! use [`similarity`].
+//!
+//! With the `std` feature, [`tree_diff`] marks changed lines with `-` and `+`,
+//! like a unified diff, [`html_diff`] renders differences as a standalone
+//! HTML page, with unchanged parts of the value collapsed, and
+//! [`side_by_side_diff`] lays out the two values in aligned columns of text.
+//! The [`snapshot`] module checks values against snapshots stored on disk,
+//! showing how they differ when they don't match.
+//!
+//! The `proptest` feature adds the [`proptest`] module, with assertions for
+//! property tests that show how values differ, and checks that a type's
+//! [`Diff`] impl agrees with its `PartialEq` impl.
+//!
+//! The `json_patch` feature adds the [`json_patch`] module, which describes
+//! differences between any two values as a JSON Patch or JSON Merge Patch.
+//!
 //! [`Diff`]: trait.Diff.html
 //! [`Differ`]: trait.Differ.html
+//! [`Differ::diff_newtype`]: trait.Differ.html#tymethod.diff_newtype
+//! [`serde`]: serde/index.html
+//! [`limit`]: limit/index.html
+//! [`filter`]: filter/index.html
+//! [`snapshot`]: snapshot/index.html
+//! [`proptest`]: proptest/index.html
+//! [`json_patch`]: json_patch/index.html
 //! [`any_difference`]: fn.any_difference.html
 //! [`all_different`]: fn.all_different.html
 //! [`debug_diff`]: fn.debug_diff.html
+//! [`debug_diff_with`]: fn.debug_diff_with.html
+//! [`DiffOptions`]: struct.DiffOptions.html
+//! [`Redacted`]: struct.Redacted.html
+//! [`DiffRemote`]: trait.DiffRemote.html
+//! [`diff_stats`]: fn.diff_stats.html
+//! [`html_diff`]: fn.html_diff.html
+//! [`similarity`]: fn.similarity.html
+//! [`side_by_side_diff`]: fn.side_by_side_diff.html
+//! [`tree_diff`]: fn.tree_diff.html
 //! [Visitor Pattern]: https://en.wikipedia.org/wiki/Visitor_pattern
 //! [double dispatch]: https://en.wikipedia.org/wiki/Double_dispatch
 //! [`difference`]: trait.Differ.html#tymethod.difference
 
 #![cfg_attr(not(feature = "std"), no_std)]
+// The Differ traits deliberately split `?Sized` from the trait bounds on their
+// generic methods, and the tests like spelling out `false`.
+#![allow(clippy::multiple_bound_locations)]
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
 
 #[cfg(feature = "visit_diff_derive")]
 pub use visit_diff_derive::*;
@@ -73,19 +150,79 @@ pub use visit_diff_derive::*;
 mod debug;
 mod detect;
 mod unit;
+pub mod combinators;
 pub mod constant;
+#[cfg(feature = "std")]
+pub mod filter;
+pub mod limit;
+mod options;
+mod redact;
+mod remote;
+mod similar;
+#[cfg(feature = "std")]
+pub mod snapshot;
+mod stats;
 #[macro_use]
 mod impls;
 #[cfg(feature = "std")]
 mod std_impls;
+mod ext_impls;
+#[cfg(any(
+    feature = "serde_json",
+    feature = "serde_yaml",
+    feature = "toml"
+))]
+mod dynamic_impls;
 
+#[cfg(feature = "std")]
+mod change;
+#[cfg(feature = "std")]
+mod html;
+#[cfg(feature = "std")]
 pub mod record;
+#[cfg(feature = "std")]
+mod side_by_side;
+#[cfg(feature = "std")]
+mod tree;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "json_patch")]
+pub mod json_patch;
 
 use core::fmt::Debug;
 use itertools::{EitherOrBoth, Itertools};
 
-pub use debug::debug_diff;
-pub use detect::{all_different, any_difference};
+pub use debug::{debug_diff, debug_differ};
+#[doc(hidden)]
+pub use debug::assert_failed as __assert_failed;
+#[doc(hidden)]
+pub use debug::Whole as __Whole;
+pub use detect::{
+    all_different, all_different_detector, any_difference,
+    difference_detector,
+};
+pub use options::{
+    all_different_with, any_difference_with, debug_diff_with,
+    ConfiguredValue, DiffOptions,
+};
+#[cfg(feature = "std")]
+pub use similar::edit_similarity;
+pub use redact::{Redacted, RedactedHash};
+pub use remote::{DiffRemote, Remote};
+pub use similar::similarity;
+pub use stats::{diff_stats, DiffStats};
+#[cfg(feature = "std")]
+pub use html::html_diff;
+#[cfg(feature = "std")]
+pub use side_by_side::side_by_side_diff;
+#[cfg(feature = "std")]
+pub use std_impls::Sorted;
+#[cfg(feature = "std")]
+pub use tree::tree_diff;
+#[cfg(feature = "indexmap")]
+pub use ext_impls::Ordered;
 
 /// A type that can be compared structurally to discover differences.
 ///
@@ -300,13 +437,30 @@ pub trait Differ {
     /// The type we turn into when diffing an abstract set.
     type SetDiffer: SetDiffer<Ok = Self::Ok, Err = Self::Err>;
 
+    /// Returns the options this diff is being made with, so that `Diff` impls
+    /// can respect them.
+    ///
+    /// The default impl returns the default options. Differs that wrap
+    /// another differ should forward this to it, and the `*_with` functions,
+    /// such as [`debug_diff_with`], arrange for it to return the options they
+    /// were given.
+    ///
+    /// [`debug_diff_with`]: fn.debug_diff_with.html
+    fn options(&self) -> &DiffOptions {
+        &options::DEFAULT
+    }
+
     /// Two atomic values have been discovered to be different, such as
     /// different numbers or different variants of an enum.
-    fn difference(self, a: &Debug, b: &Debug) -> Result<Self::Ok, Self::Err>;
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err>;
 
     /// Two atomic values are the same, such as equal numbers or identical unit
     /// variants of an enum.
-    fn same(self, a: &Debug, b: &Debug) -> Result<Self::Ok, Self::Err>;
+    fn same(self, a: &dyn Debug, b: &dyn Debug) -> Result<Self::Ok, Self::Err>;
 
     /// Encounter a newtype. `a` and `b` are the contents of the sole fields of
     /// the left-hand and right-hand value, respectively.
@@ -564,6 +718,20 @@ pub trait StructDiffer {
 
     /// Completes traversal of the struct.
     fn end(self) -> Result<Self::Ok, Self::Err>;
+
+    /// Completes traversal of a struct that has more to it than the fields
+    /// visited, such as a `Mutex` that only shows its data and poison flag.
+    ///
+    /// This corresponds to `DebugStruct::finish_non_exhaustive`. By default it
+    /// is the same as [`end`].
+    ///
+    /// [`end`]: #tymethod.end
+    fn end_non_exhaustive(self) -> Result<Self::Ok, Self::Err>
+    where
+        Self: Sized,
+    {
+        self.end()
+    }
 }
 
 /// A type that can do something with information about differences in a
@@ -0,0 +1,967 @@
+//! Bounding how much of a diff is reported.
+//!
+//! Diffing huge or deeply nested values can produce enormous output, which is
+//! a problem when the diff is going into a panic message or a log. The
+//! [`Limited`] adapter wraps any [`Differ`] and cuts the report short
+//! according to some [`Limits`], leaving a marker like `… 4,812 more
+//! elements` where things were left out.
+//!
+//! Most of the time it's easier to wrap the values than the differ, using
+//! [`Limits::wrap`], which works with every function that takes values to
+//! diff:
+//!
+//! ```
+//! use visit_diff::{debug_diff, limit::Limits};
+//!
+//! let limits = Limits {
+//!     max_elements: 3,
+//!     ..Limits::default()
+//! };
+//! let a: Vec<u32> = (0..10_000).collect();
+//! let mut b = a.clone();
+//! b[1] = 99;
+//!
+//! assert_eq!(
+//!     format!("{:?}", debug_diff(limits.wrap(&a), limits.wrap(&b))),
+//!     "[0, DIFF { L: 1, R: 99 }, 2, … 9,997 more elements]",
+//! );
+//! ```
+//!
+//! Parts that were left out still count when deciding whether the values
+//! differ. If any of them do, they get a marker of their own, which is shown
+//! as a difference:
+//!
+//! ```
+//! # use visit_diff::{debug_diff, limit::Limits};
+//! # let limits = Limits { max_elements: 3, ..Limits::default() };
+//! let (a, b) = (vec![1, 2, 3, 4, 5], vec![1, 2, 3, 4, 6]);
+//! assert_eq!(
+//!     format!("{:?}", debug_diff(limits.wrap(&a), limits.wrap(&b))),
+//!     "[1, 2, 3, DIFF { L: … 1 more element, R: … 1 more element }, \
+//!      … 1 more element]",
+//! );
+//! ```
+//!
+//! [`Limited`]: struct.Limited.html
+//! [`Limits`]: struct.Limits.html
+//! [`Limits::wrap`]: struct.Limits.html#method.wrap
+//! [`Differ`]: ../trait.Differ.html
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter, Write};
+use core::ops::Deref;
+use core::ptr;
+
+use crate::options::any_difference_within;
+use crate::{
+    Diff, DiffOptions, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer,
+    TupleDiffer,
+};
+
+/// Bounds on how much of a diff is reported. Each defaults to unlimited.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Limits {
+    /// Structs, tuples, sequences, maps and sets nested more deeply than
+    /// this are summarized (as `Name { … }`, `[…]` and so on) rather than
+    /// taken apart. The values being diffed are at depth 0.
+    pub max_depth: usize,
+    /// Sequences, maps and sets show at most this many elements or entries.
+    /// With 0, they show only the markers counting what was left out.
+    pub max_elements: usize,
+    /// At most this many differences are reported. Once they've been used
+    /// up, the rest of each enclosing value is left out.
+    ///
+    /// Each pair of different leaves, and each element or entry only on one
+    /// side, counts as one difference.
+    ///
+    /// With 0, only a summary is reported: the parts that are the same are
+    /// shown, and the rest are left out, or shown as `…` if the values
+    /// differ as a whole.
+    pub max_differences: usize,
+    /// The `Debug` text of each value is cut short, ending in `…`, if it's
+    /// longer than this many characters.
+    pub max_text: usize,
+}
+
+/// No limits at all.
+pub(crate) const UNLIMITED: Limits = Limits {
+    max_depth: usize::MAX,
+    max_elements: usize::MAX,
+    max_differences: usize::MAX,
+    max_text: usize::MAX,
+};
+
+impl Default for Limits {
+    fn default() -> Self {
+        UNLIMITED
+    }
+}
+
+impl Limits {
+    /// Wraps `value` so that diffing it, with any `Differ`, is subject to
+    /// these limits.
+    ///
+    /// Formatting the wrapped value with `Debug` also applies the limits.
+    pub fn wrap<T>(self, value: &T) -> LimitedValue<'_, T>
+    where
+        T: Diff + ?Sized,
+    {
+        LimitedValue {
+            value,
+            limits: self,
+            depth: 0,
+            context: None,
+            checked: false,
+            start: self.max_differences,
+        }
+    }
+}
+
+/// Given two values that can be diffed, returns an object that will describe
+/// their differences, within `limits`, when formatted using `Debug`.
+///
+/// This is [`debug_diff`] for values that may be too big to show in full,
+/// such as in panic messages.
+///
+/// ```
+/// use visit_diff::{Diff, limit::{self, Limits}};
+///
+/// #[derive(Diff, Debug)]
+/// struct Node {
+///     name: &'static str,
+///     children: Vec<Node>,
+/// }
+///
+/// let leaf = |name| Node { name, children: vec![] };
+/// let a = Node { name: "root", children: vec![leaf("a")] };
+/// let b = Node { name: "root", children: vec![leaf("b")] };
+///
+/// let limits = Limits { max_depth: 1, ..Limits::default() };
+/// assert_eq!(
+///     format!("{:?}", limit::debug_diff(&a, &b, limits)),
+///     "Node { name: \"root\", children: DIFF { L: […], R: […] } }",
+/// );
+/// ```
+///
+/// [`debug_diff`]: ../fn.debug_diff.html
+pub fn debug_diff<'a, T>(a: &'a T, b: &'a T, limits: Limits) -> impl Debug + 'a
+where
+    T: Diff + ?Sized,
+{
+    crate::debug_diff(limits.wrap(a), limits.wrap(b))
+}
+
+/// A value wrapped by [`Limits::wrap`].
+///
+/// [`Limits::wrap`]: struct.Limits.html#method.wrap
+pub struct LimitedValue<'a, T: ?Sized> {
+    value: &'a T,
+    limits: Limits,
+    /// How deep the value is nested.
+    depth: usize,
+    /// What's shared with the rest of the diff, or `None` if the value is
+    /// the one passed to `wrap`.
+    context: Option<&'a Context>,
+    /// Whether the value is known to be the same on both sides.
+    checked: bool,
+    /// How many differences could be reported when the value was reached.
+    start: usize,
+}
+
+impl<'a, T> Diff for LimitedValue<'a, T>
+where
+    T: Diff + ?Sized,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let out = match a.context {
+            Some(context) => {
+                // Something may go over the value more than once, such as
+                // `debug_diff` checking whether it's all different before
+                // showing it. Each time uses up the same differences, except
+                // for showing it whole, which has none to use up.
+                if !ptr::eq(a, b) {
+                    context.budget.set(a.start);
+                }
+                Limited {
+                    inner: out,
+                    limits: a.limits,
+                    depth: a.depth,
+                    context: Shared::Borrowed(context),
+                    checked: a.checked,
+                }
+            }
+            // Showing a value whole, such as when `debug_diff` finds it all
+            // different, would show its differences.
+            None if ptr::eq(a, b)
+                && !a.checked
+                && a.limits.max_differences == 0 =>
+            {
+                return out.same(&Marker::Bare, &Marker::Bare);
+            }
+            None => Limited {
+                checked: a.checked,
+                ..Limited::new(out, a.limits)
+            },
+        };
+        Diff::diff(a.value, b.value, out)
+    }
+}
+
+impl<'a, T> Debug for LimitedValue<'a, T>
+where
+    T: Diff + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Diffing the value with itself prints it, through the limits.
+        let value = LimitedValue {
+            checked: true,
+            ..*self
+        };
+        let diff = crate::debug_diff(&value, &value);
+        diff.fmt(f)
+    }
+}
+
+/// What's shared by all the parts of a diff within some `Limits`.
+struct Context {
+    /// Number of differences that can still be reported.
+    budget: Cell<usize>,
+    /// The options to compare parts that aren't shown with.
+    options: DiffOptions,
+}
+
+impl Context {
+    fn exhausted(&self) -> bool {
+        self.budget.get() == 0
+    }
+
+    /// Uses up one of the differences that can be reported.
+    fn spend(&self) {
+        let n = self.budget.get();
+        if n != usize::MAX && n > 0 {
+            self.budget.set(n - 1);
+        }
+    }
+
+    fn differs<T: ?Sized + Diff>(&self, a: &T, b: &T) -> bool {
+        any_difference_within(a, b, &self.options)
+    }
+}
+
+/// A `Context`, owned by the outermost value of a diff and borrowed by its
+/// parts.
+enum Shared<'c> {
+    Owned(Context),
+    Borrowed(&'c Context),
+}
+
+impl<'c> Deref for Shared<'c> {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        match self {
+            Shared::Owned(context) => context,
+            Shared::Borrowed(context) => context,
+        }
+    }
+}
+
+/// Adapts a `Differ` so that it reports differences within some [`Limits`].
+///
+/// [`Limits`]: struct.Limits.html
+pub struct Limited<'c, D> {
+    inner: D,
+    limits: Limits,
+    depth: usize,
+    context: Shared<'c>,
+    checked: bool,
+}
+
+impl<D: Differ> Limited<'static, D> {
+    /// Wraps `inner` to apply `limits`.
+    pub fn new(inner: D, limits: Limits) -> Self {
+        let context = Context {
+            budget: Cell::new(limits.max_differences),
+            options: inner.options().clone(),
+        };
+        Limited {
+            inner,
+            limits,
+            depth: 0,
+            context: Shared::Owned(context),
+            checked: false,
+        }
+    }
+}
+
+impl<'c, D: Differ> Limited<'c, D> {
+    /// Starts on a compound value, which is either taken apart by the inner
+    /// differ, or summarized if it's too deep.
+    fn begin<S>(
+        self,
+        summary: Summary,
+        max_shown: usize,
+        begin: impl FnOnce(D) -> S,
+    ) -> LimitedPart<'c, S, D> {
+        if self.depth >= self.limits.max_depth {
+            LimitedPart(Part::Summarized {
+                inner: self.inner,
+                summary,
+                context: self.context,
+                different: false,
+                checked: self.checked,
+            })
+        } else {
+            LimitedPart(Part::Open {
+                inner: begin(self.inner),
+                limits: self.limits,
+                depth: self.depth + 1,
+                context: self.context,
+                checked: self.checked,
+                max_shown,
+                shown: 0,
+                left_out: 0,
+                left_out_different: 0,
+            })
+        }
+    }
+}
+
+impl<'c, D: Differ> Differ for Limited<'c, D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    type StructDiffer = LimitedPart<'c, D::StructDiffer, D>;
+    type StructVariantDiffer = LimitedPart<'c, D::StructVariantDiffer, D>;
+    type TupleDiffer = LimitedPart<'c, D::TupleDiffer, D>;
+    type TupleVariantDiffer = LimitedPart<'c, D::TupleVariantDiffer, D>;
+    type SeqDiffer = LimitedPart<'c, D::SeqDiffer, D>;
+    type MapDiffer = LimitedPart<'c, D::MapDiffer, D>;
+    type SetDiffer = LimitedPart<'c, D::SetDiffer, D>;
+
+    fn options(&self) -> &DiffOptions {
+        self.inner.options()
+    }
+
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
+        // Only differences outside any part can be reached with none left.
+        if self.context.exhausted() {
+            return self.inner.difference(&Marker::Bare, &Marker::Bare);
+        }
+        self.context.spend();
+        let max = self.limits.max_text;
+        self.inner.difference(&Clipped(a, max), &Clipped(b, max))
+    }
+
+    fn same(self, a: &dyn Debug, b: &dyn Debug) -> Result<Self::Ok, Self::Err> {
+        let max = self.limits.max_text;
+        self.inner.same(&Clipped(a, max), &Clipped(b, max))
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        ty: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<Self::Ok, Self::Err>
+    where
+        T: Diff,
+    {
+        if self.depth >= self.limits.max_depth {
+            let summary = Summary::Tuple(ty);
+            if !self.checked && self.context.differs(a, b) {
+                self.inner.difference(&summary, &summary)
+            } else {
+                self.inner.same(&summary, &summary)
+            }
+        } else {
+            let (limits, depth, checked) =
+                (self.limits, self.depth + 1, self.checked);
+            let start = self.context.budget.get();
+            let context = Some(&*self.context);
+            let wrap = |value| LimitedValue {
+                value,
+                limits,
+                depth,
+                context,
+                checked,
+                start,
+            };
+            self.inner.diff_newtype(ty, &wrap(a), &wrap(b))
+        }
+    }
+
+    fn begin_struct(self, ty: &'static str) -> Self::StructDiffer {
+        self.begin(Summary::Struct(ty), usize::MAX, |d| d.begin_struct(ty))
+    }
+
+    fn begin_struct_variant(
+        self,
+        ty: &'static str,
+        var: &'static str,
+    ) -> Self::StructVariantDiffer {
+        self.begin(Summary::Struct(var), usize::MAX, |d| {
+            d.begin_struct_variant(ty, var)
+        })
+    }
+
+    fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+        self.begin(Summary::Tuple(ty), usize::MAX, |d| d.begin_tuple(ty))
+    }
+
+    fn begin_tuple_variant(
+        self,
+        ty: &'static str,
+        var: &'static str,
+    ) -> Self::TupleVariantDiffer {
+        self.begin(Summary::Tuple(var), usize::MAX, |d| {
+            d.begin_tuple_variant(ty, var)
+        })
+    }
+
+    fn begin_seq(self) -> Self::SeqDiffer {
+        let max = self.limits.max_elements;
+        self.begin(Summary::Seq, max, |d| d.begin_seq())
+    }
+
+    fn begin_map(self) -> Self::MapDiffer {
+        let max = self.limits.max_elements;
+        self.begin(Summary::Braces, max, |d| d.begin_map())
+    }
+
+    fn begin_set(self) -> Self::SetDiffer {
+        let max = self.limits.max_elements;
+        self.begin(Summary::Braces, max, |d| d.begin_set())
+    }
+}
+
+/// The sub-differs of [`Limited`], wrapping those of the inner differ `D`.
+///
+/// [`Limited`]: struct.Limited.html
+pub struct LimitedPart<'c, S, D>(Part<'c, S, D>);
+
+enum Part<'c, S, D> {
+    /// The value is being taken apart by `inner`.
+    Open {
+        inner: S,
+        limits: Limits,
+        /// Depth of the parts.
+        depth: usize,
+        context: Shared<'c>,
+        /// Whether the value is known to be the same on both sides.
+        checked: bool,
+        /// Number of parts that can be shown.
+        max_shown: usize,
+        shown: usize,
+        /// Number of parts left out.
+        left_out: usize,
+        /// How many of the parts left out differ.
+        left_out_different: usize,
+    },
+    /// The value is too deep, and will be summarized by `inner` as a whole.
+    Summarized {
+        inner: D,
+        summary: Summary,
+        context: Shared<'c>,
+        different: bool,
+        checked: bool,
+    },
+}
+
+impl<'c, S, D: Differ> LimitedPart<'c, S, D> {
+    /// Offers a pair of parts to be shown, calling `show` with them wrapped
+    /// up if they can be.
+    fn pair<T: ?Sized + Diff>(
+        &mut self,
+        a: &T,
+        b: &T,
+        show: impl FnOnce(&mut S, &LimitedValue<T>, &LimitedValue<T>),
+    ) {
+        match &mut self.0 {
+            Part::Open {
+                inner,
+                limits,
+                depth,
+                context,
+                checked,
+                max_shown,
+                shown,
+                left_out,
+                left_out_different,
+            } => {
+                // Once anything is left out, everything after it is too, so
+                // the marker for them can go at the end.
+                if *left_out > 0 || *shown >= *max_shown {
+                    *left_out += 1;
+                    if !*checked && context.differs(a, b) {
+                        *left_out_different += 1;
+                    }
+                    return;
+                }
+                // With no differences left to report, a part can only be
+                // shown if it has none. Checking it once here saves checking
+                // its parts again.
+                let same =
+                    *checked || (context.exhausted() && !context.differs(a, b));
+                if context.exhausted() && !same {
+                    *left_out += 1;
+                    *left_out_different += 1;
+                    return;
+                }
+                *shown += 1;
+                let wrap = |value| LimitedValue {
+                    value,
+                    limits: *limits,
+                    depth: *depth,
+                    context: Some(&**context),
+                    checked: same,
+                    start: context.budget.get(),
+                };
+                show(inner, &wrap(a), &wrap(b))
+            }
+            Part::Summarized {
+                context,
+                different,
+                checked,
+                ..
+            } => {
+                if !*checked && !*different {
+                    *different = context.differs(a, b);
+                }
+            }
+        }
+    }
+
+    /// Offers a part only on one side to be shown, calling `show` with it
+    /// wrapped up if it can be.
+    fn one_sided<T: ?Sized + Diff>(
+        &mut self,
+        x: &T,
+        show: impl FnOnce(&mut S, &LimitedValue<T>),
+    ) {
+        match &mut self.0 {
+            Part::Open {
+                inner,
+                limits,
+                depth,
+                context,
+                max_shown,
+                shown,
+                left_out,
+                left_out_different,
+                ..
+            } => {
+                if *left_out > 0 || *shown >= *max_shown || context.exhausted()
+                {
+                    *left_out += 1;
+                    *left_out_different += 1;
+                    return;
+                }
+                context.spend();
+                *shown += 1;
+                // The value is shown by diffing it with itself.
+                let x = LimitedValue {
+                    value: x,
+                    limits: *limits,
+                    depth: *depth,
+                    context: Some(&**context),
+                    checked: true,
+                    start: context.budget.get(),
+                };
+                show(inner, &x)
+            }
+            Part::Summarized { different, .. } => *different = true,
+        }
+    }
+
+    /// Finishes the value, calling `mark` to add markers for parts left out,
+    /// with whether they differ, and `end` to finish it off.
+    fn finish(
+        self,
+        mut mark: impl FnMut(&mut S, usize, bool),
+        end: impl FnOnce(S) -> Result<D::Ok, D::Err>,
+    ) -> Result<D::Ok, D::Err> {
+        match self.0 {
+            Part::Open {
+                mut inner,
+                left_out,
+                left_out_different,
+                ..
+            } => {
+                // Parts that differ and parts that don't are marked
+                // separately, so that neither hides the other.
+                if left_out_different > 0 {
+                    mark(&mut inner, left_out_different, true);
+                }
+                if left_out > left_out_different {
+                    mark(&mut inner, left_out - left_out_different, false);
+                }
+                end(inner)
+            }
+            Part::Summarized {
+                inner,
+                summary,
+                different,
+                ..
+            } => {
+                if different {
+                    inner.difference(&summary, &summary)
+                } else {
+                    inner.same(&summary, &summary)
+                }
+            }
+        }
+    }
+}
+
+impl<'c, S, D> StructDiffer for LimitedPart<'c, S, D>
+where
+    S: StructDiffer<Ok = D::Ok, Err = D::Err>,
+    D: Differ,
+{
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn diff_field<T: ?Sized>(&mut self, name: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.pair(a, b, |s, a, b| s.diff_field(name, a, b))
+    }
+
+    fn skip_field<T: ?Sized>(&mut self, name: &'static str) {
+        if let Part::Open { inner, .. } = &mut self.0 {
+            inner.skip_field::<T>(name)
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish(Self::mark_fields, |s| s.end())
+    }
+
+    fn end_non_exhaustive(self) -> Result<Self::Ok, Self::Err> {
+        self.finish(Self::mark_fields, |s| s.end_non_exhaustive())
+    }
+}
+
+impl<'c, S, D> TupleDiffer for LimitedPart<'c, S, D>
+where
+    S: TupleDiffer<Ok = D::Ok, Err = D::Err>,
+    D: Differ,
+{
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.pair(a, b, |s, a, b| s.diff_field(a, b))
+    }
+
+    fn skip_field<T: ?Sized>(&mut self) {
+        if let Part::Open { inner, .. } = &mut self.0 {
+            inner.skip_field::<T>()
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish(
+            |s, n, different| {
+                let marker = LeftOut(Marker::Ellipsis(n, FIELDS), different);
+                s.diff_field(&marker, &marker)
+            },
+            |s| s.end(),
+        )
+    }
+}
+
+impl<'c, S, D> SeqDiffer for LimitedPart<'c, S, D>
+where
+    S: SeqDiffer<Ok = D::Ok, Err = D::Err>,
+    D: Differ,
+{
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.pair(a, b, |s, a, b| s.diff_element(a, b))
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, a: &T)
+    where
+        T: Diff,
+    {
+        self.one_sided(a, |s, a| s.left_excess(a))
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, b: &T)
+    where
+        T: Diff,
+    {
+        self.one_sided(b, |s, b| s.right_excess(b))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish(
+            |s, n, different| {
+                let marker = LeftOut(Marker::Ellipsis(n, ELEMENTS), different);
+                s.diff_element(&marker, &marker)
+            },
+            |s| s.end(),
+        )
+    }
+}
+
+impl<'c, S, D> MapDiffer for LimitedPart<'c, S, D>
+where
+    S: MapDiffer<Ok = D::Ok, Err = D::Err>,
+    D: Differ,
+{
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let max = self.limits().max_text;
+        self.pair(a, b, |s, a, b| s.diff_entry(&Clipped(&key, max), a, b))
+    }
+
+    fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let max = self.limits().max_text;
+        self.one_sided(a, |s, a| s.only_in_left(&Clipped(&key, max), a))
+    }
+
+    fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let max = self.limits().max_text;
+        self.one_sided(b, |s, b| s.only_in_right(&Clipped(&key, max), b))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish(
+            |s, n, different| {
+                let marker = LeftOut(Marker::Count(n, ENTRIES), different);
+                s.diff_entry(&Marker::Bare, &marker, &marker)
+            },
+            |s| s.end(),
+        )
+    }
+}
+
+impl<'c, S, D> SetDiffer for LimitedPart<'c, S, D>
+where
+    S: SetDiffer<Ok = D::Ok, Err = D::Err>,
+    D: Differ,
+{
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.pair(a, b, |s, a, b| s.diff_equal(a, b))
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.one_sided(a, |s, a| s.only_in_left(a))
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.one_sided(b, |s, b| s.only_in_right(b))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish(
+            |s, n, different| {
+                let marker = LeftOut(Marker::Ellipsis(n, ELEMENTS), different);
+                s.diff_equal(&marker, &marker)
+            },
+            |s| s.end(),
+        )
+    }
+}
+
+impl<'c, S, D> LimitedPart<'c, S, D>
+where
+    S: StructDiffer,
+{
+    /// Marks fields left out of a struct, as a field of its own.
+    fn mark_fields(s: &mut S, n: usize, different: bool) {
+        let marker = LeftOut(Marker::Count(n, FIELDS), different);
+        s.diff_field("…", &marker, &marker)
+    }
+}
+
+impl<'c, S, D> LimitedPart<'c, S, D> {
+    fn limits(&self) -> Limits {
+        match &self.0 {
+            Part::Open { limits, .. } => *limits,
+            Part::Summarized { .. } => Limits::default(),
+        }
+    }
+}
+
+/// Stands in for a compound value that's too deep to show.
+#[derive(Copy, Clone)]
+enum Summary {
+    Struct(&'static str),
+    Tuple(&'static str),
+    Seq,
+    Braces,
+}
+
+impl Debug for Summary {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Summary::Struct(name) => write!(f, "{} {{ … }}", name),
+            Summary::Tuple(name) => write!(f, "{}(…)", name),
+            Summary::Seq => f.write_str("[…]"),
+            Summary::Braces => f.write_str("{…}"),
+        }
+    }
+}
+
+/// The singular and plural of what was left out.
+type Noun = (&'static str, &'static str);
+
+const FIELDS: Noun = ("field", "fields");
+const ELEMENTS: Noun = ("element", "elements");
+const ENTRIES: Noun = ("entry", "entries");
+
+/// Stands in for parts of a value that were left out.
+enum Marker {
+    /// `… 12 more things`
+    Ellipsis(usize, Noun),
+    /// `12 more things`
+    Count(usize, Noun),
+    /// `…`
+    Bare,
+}
+
+impl Debug for Marker {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (n, what) = match self {
+            Marker::Ellipsis(n, what) => {
+                f.write_str("… ")?;
+                (*n, *what)
+            }
+            Marker::Count(n, what) => (*n, *what),
+            Marker::Bare => return f.write_str("…"),
+        };
+        write_grouped(f, n)?;
+        let (one, many) = what;
+        write!(f, " more {}", if n == 1 { one } else { many })
+    }
+}
+
+/// A `Marker` for parts of a value that were left out, and whether any of
+/// them differ. It's reported as a difference if so, so that leaving parts
+/// out doesn't hide that the values differ.
+struct LeftOut(Marker, bool);
+
+impl Debug for LeftOut {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Diff for LeftOut {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        if a.1 {
+            out.difference(a, b)
+        } else {
+            out.same(a, b)
+        }
+    }
+}
+
+/// Writes `n` with commas between groups of three digits.
+fn write_grouped(f: &mut Formatter, n: usize) -> fmt::Result {
+    if n >= 1000 {
+        write_grouped(f, n / 1000)?;
+        write!(f, ",{:03}", n % 1000)
+    } else {
+        write!(f, "{}", n)
+    }
+}
+
+/// Formats a value with `Debug`, cutting it short after some number of
+/// characters.
+struct Clipped<'a>(&'a dyn Debug, usize);
+
+impl<'a> Debug for Clipped<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.1 == usize::MAX {
+            return self.0.fmt(f);
+        }
+        let mut out = Clipper {
+            f,
+            remaining: self.1,
+            clipped: false,
+        };
+        let result = if out.f.alternate() {
+            write!(out, "{:#?}", self.0)
+        } else {
+            write!(out, "{:?}", self.0)
+        };
+        match result {
+            Err(_) if out.clipped => out.f.write_str("…"),
+            result => result,
+        }
+    }
+}
+
+/// Passes text on to a `Formatter` until it's had enough, and then fails.
+struct Clipper<'a, 'b> {
+    f: &'a mut Formatter<'b>,
+    remaining: usize,
+    clipped: bool,
+}
+
+impl<'a, 'b> Write for Clipper<'a, 'b> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match s.char_indices().nth(self.remaining) {
+            None => {
+                self.remaining -= s.chars().count();
+                self.f.write_str(s)
+            }
+            Some((i, _)) => {
+                self.f.write_str(&s[..i])?;
+                self.remaining = 0;
+                self.clipped = true;
+                Err(fmt::Error)
+            }
+        }
+    }
+}
@@ -0,0 +1,518 @@
+//! Settings for a diff, shared by every entry point and `Diff` impl.
+
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(feature = "std")]
+use crate::filter::Filter;
+use crate::limit::{self, Limited, Limits};
+use crate::{
+    Diff, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer, TupleDiffer,
+};
+
+/// Settings for a diff, as taken by the `*_with` variants of the diffing
+/// functions, such as [`debug_diff_with`].
+///
+/// While diffing, the settings are available to `Diff` impls through
+/// [`Differ::options`], so that hand-written impls can respect them too. For
+/// example, here's a type that only compares its value to the nearest whole
+/// number if told to be tolerant of rounding:
+///
+/// ```
+/// use visit_diff::{any_difference_with, Diff, DiffOptions, Differ};
+///
+/// #[derive(Debug)]
+/// struct Reading(f64);
+///
+/// impl Diff for Reading {
+///     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+///     where
+///         D: Differ,
+///     {
+///         if out.options().float_tolerance >= 1.0 {
+///             Diff::diff(&a.0.round(), &b.0.round(), out)
+///         } else {
+///             Diff::diff(&a.0, &b.0, out)
+///         }
+///     }
+/// }
+///
+/// let options = DiffOptions {
+///     float_tolerance: 1.0,
+///     ..DiffOptions::default()
+/// };
+/// assert!(!any_difference_with(&Reading(2.2), &Reading(1.9), &options));
+/// ```
+///
+/// [`debug_diff_with`]: fn.debug_diff_with.html
+/// [`Differ::options`]: trait.Differ.html#method.options
+#[derive(Clone, Debug)]
+pub struct DiffOptions {
+    /// How far apart two `f32` or `f64` values can be while still counting as
+    /// the same. The default is zero, so that only equal values are the same.
+    pub float_tolerance: f64,
+    /// How much of the diff to report.
+    pub limits: Limits,
+    /// Which parts of values to compare.
+    #[cfg(feature = "std")]
+    pub filter: Filter,
+}
+
+/// The options used when none are given.
+pub(crate) static DEFAULT: DiffOptions = DiffOptions::new();
+
+impl DiffOptions {
+    /// Creates the default options, which compare everything exactly and
+    /// report it all.
+    pub const fn new() -> Self {
+        DiffOptions {
+            float_tolerance: 0.0,
+            limits: limit::UNLIMITED,
+            #[cfg(feature = "std")]
+            filter: Filter::new(),
+        }
+    }
+
+    /// Wraps `value` so that diffing it, with any `Differ`, uses these
+    /// options.
+    ///
+    /// Formatting the wrapped value with `Debug` also applies the options.
+    pub fn wrap<'a, T>(&'a self, value: &'a T) -> ConfiguredValue<'a, T>
+    where
+        T: Diff + ?Sized,
+    {
+        ConfiguredValue {
+            value,
+            options: self,
+            limited: true,
+        }
+    }
+
+    /// Wraps `value` to be compared using these options. Only the settings
+    /// that decide what counts as a difference are applied: the limits only
+    /// cut short how differences are reported.
+    fn compare<'a, T>(&'a self, value: &'a T) -> ConfiguredValue<'a, T>
+    where
+        T: Diff + ?Sized,
+    {
+        ConfiguredValue {
+            limited: false,
+            ..self.wrap(value)
+        }
+    }
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions::new()
+    }
+}
+
+/// Like [`debug_diff`], but using `options`.
+///
+/// ```
+/// use visit_diff::{debug_diff_with, filter::Filter, DiffOptions};
+///
+/// let options = DiffOptions {
+///     filter: Filter::new().ignore("[1]"),
+///     ..DiffOptions::default()
+/// };
+/// assert_eq!(
+///     format!("{:?}", debug_diff_with(&[1, 2, 3], &[1, 5, 6], &options)),
+///     "[1, _, DIFF { L: 3, R: 6 }]",
+/// );
+/// ```
+///
+/// [`debug_diff`]: fn.debug_diff.html
+pub fn debug_diff_with<'a, T>(
+    a: &'a T,
+    b: &'a T,
+    options: &'a DiffOptions,
+) -> impl Debug + 'a
+where
+    T: Diff + ?Sized,
+{
+    crate::debug_diff(options.wrap(a), options.wrap(b))
+}
+
+/// Like [`any_difference`], but using `options`.
+///
+/// The `limits` in `options` are ignored, since they only affect how much of
+/// a diff is reported, not whether there's a difference.
+///
+/// [`any_difference`]: fn.any_difference.html
+pub fn any_difference_with<T>(a: &T, b: &T, options: &DiffOptions) -> bool
+where
+    T: Diff + ?Sized,
+{
+    crate::any_difference(&options.compare(a), &options.compare(b))
+}
+
+/// Like [`all_different`], but using `options`.
+///
+/// As with [`any_difference_with`], the `limits` in `options` are ignored.
+///
+/// [`all_different`]: fn.all_different.html
+/// [`any_difference_with`]: fn.any_difference_with.html
+pub fn all_different_with<T>(a: &T, b: &T, options: &DiffOptions) -> bool
+where
+    T: Diff + ?Sized,
+{
+    crate::all_different(&options.compare(a), &options.compare(b))
+}
+
+/// Like [`any_difference_with`], for parts of values that are already being
+/// diffed using `options`, and so have been wrapped up in the filter.
+///
+/// [`any_difference_with`]: fn.any_difference_with.html
+pub(crate) fn any_difference_within<T>(
+    a: &T,
+    b: &T,
+    options: &DiffOptions,
+) -> bool
+where
+    T: Diff + ?Sized,
+{
+    let wrap = |value| ConfiguredPart { value, options };
+    crate::any_difference(&wrap(a), &wrap(b))
+}
+
+/// A value wrapped by [`DiffOptions::wrap`].
+///
+/// [`DiffOptions::wrap`]: struct.DiffOptions.html#method.wrap
+pub struct ConfiguredValue<'a, T: ?Sized> {
+    value: &'a T,
+    options: &'a DiffOptions,
+    /// Whether to apply the limits, rather than just the settings that
+    /// decide what counts as a difference.
+    limited: bool,
+}
+
+impl<'a, T> Diff for ConfiguredValue<'a, T>
+where
+    T: Diff + ?Sized,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let options = a.options;
+        let out = Configured {
+            inner: out,
+            options,
+        };
+        // The limits and filter are applied from here, and carried down by
+        // their own adapters.
+        let limits = if a.limited {
+            options.limits
+        } else {
+            limit::UNLIMITED
+        };
+        #[cfg(feature = "std")]
+        {
+            let (a, b) =
+                (options.filter.wrap(a.value), options.filter.wrap(b.value));
+            Diff::diff(&a, &b, Limited::new(out, limits))
+        }
+        #[cfg(not(feature = "std"))]
+        Diff::diff(a.value, b.value, Limited::new(out, limits))
+    }
+}
+
+impl<'a, T> Debug for ConfiguredValue<'a, T>
+where
+    T: Diff + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Diffing the value with itself prints it, using the options.
+        crate::debug_diff(self, self).fmt(f)
+    }
+}
+
+/// Part of a [`ConfiguredValue`], which the limits and filter have already
+/// been applied to.
+///
+/// [`ConfiguredValue`]: struct.ConfiguredValue.html
+struct ConfiguredPart<'a, T: ?Sized> {
+    value: &'a T,
+    options: &'a DiffOptions,
+}
+
+impl<'a, T> Diff for ConfiguredPart<'a, T>
+where
+    T: Diff + ?Sized,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let out = Configured {
+            inner: out,
+            options: a.options,
+        };
+        Diff::diff(a.value, b.value, out)
+    }
+}
+
+impl<'a, T> Debug for ConfiguredPart<'a, T>
+where
+    T: Diff + ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // The limits and filter have already been applied to the value.
+        self.value.fmt(f)
+    }
+}
+
+/// Adapts a `Differ` so that `Diff` impls see `options` through
+/// [`Differ::options`], without otherwise changing it.
+///
+/// [`Differ::options`]: trait.Differ.html#method.options
+struct Configured<'a, D> {
+    inner: D,
+    options: &'a DiffOptions,
+}
+
+impl<'a, D> Configured<'a, D> {
+    fn begin<S>(self, begin: impl FnOnce(D) -> S) -> Configured<'a, S> {
+        Configured {
+            inner: begin(self.inner),
+            options: self.options,
+        }
+    }
+
+    fn wrap<'v, T: ?Sized>(&self, value: &'v T) -> ConfiguredPart<'v, T>
+    where
+        'a: 'v,
+    {
+        ConfiguredPart {
+            value,
+            options: self.options,
+        }
+    }
+}
+
+impl<'a, D: Differ> Differ for Configured<'a, D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    type StructDiffer = Configured<'a, D::StructDiffer>;
+    type StructVariantDiffer = Configured<'a, D::StructVariantDiffer>;
+    type TupleDiffer = Configured<'a, D::TupleDiffer>;
+    type TupleVariantDiffer = Configured<'a, D::TupleVariantDiffer>;
+    type SeqDiffer = Configured<'a, D::SeqDiffer>;
+    type MapDiffer = Configured<'a, D::MapDiffer>;
+    type SetDiffer = Configured<'a, D::SetDiffer>;
+
+    fn options(&self) -> &DiffOptions {
+        self.options
+    }
+
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
+        self.inner.difference(a, b)
+    }
+
+    fn same(self, a: &dyn Debug, b: &dyn Debug) -> Result<Self::Ok, Self::Err> {
+        self.inner.same(a, b)
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        ty: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<Self::Ok, Self::Err>
+    where
+        T: Diff,
+    {
+        let (a, b) = (self.wrap(a), self.wrap(b));
+        self.inner.diff_newtype(ty, &a, &b)
+    }
+
+    fn begin_struct(self, ty: &'static str) -> Self::StructDiffer {
+        self.begin(|d| d.begin_struct(ty))
+    }
+
+    fn begin_struct_variant(
+        self,
+        ty: &'static str,
+        var: &'static str,
+    ) -> Self::StructVariantDiffer {
+        self.begin(|d| d.begin_struct_variant(ty, var))
+    }
+
+    fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+        self.begin(|d| d.begin_tuple(ty))
+    }
+
+    fn begin_tuple_variant(
+        self,
+        ty: &'static str,
+        var: &'static str,
+    ) -> Self::TupleVariantDiffer {
+        self.begin(|d| d.begin_tuple_variant(ty, var))
+    }
+
+    fn begin_seq(self) -> Self::SeqDiffer {
+        self.begin(|d| d.begin_seq())
+    }
+
+    fn begin_map(self) -> Self::MapDiffer {
+        self.begin(|d| d.begin_map())
+    }
+
+    fn begin_set(self) -> Self::SetDiffer {
+        self.begin(|d| d.begin_set())
+    }
+}
+
+impl<'a, S: StructDiffer> StructDiffer for Configured<'a, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_field<T: ?Sized>(&mut self, name: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let (a, b) = (self.wrap(a), self.wrap(b));
+        self.inner.diff_field(name, &a, &b)
+    }
+
+    fn skip_field<T: ?Sized>(&mut self, name: &'static str) {
+        self.inner.skip_field::<T>(name)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+
+    fn end_non_exhaustive(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end_non_exhaustive()
+    }
+}
+
+impl<'a, S: TupleDiffer> TupleDiffer for Configured<'a, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let (a, b) = (self.wrap(a), self.wrap(b));
+        self.inner.diff_field(&a, &b)
+    }
+
+    fn skip_field<T: ?Sized>(&mut self) {
+        self.inner.skip_field::<T>()
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+}
+
+impl<'a, S: SeqDiffer> SeqDiffer for Configured<'a, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let (a, b) = (self.wrap(a), self.wrap(b));
+        self.inner.diff_element(&a, &b)
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, a: &T)
+    where
+        T: Diff,
+    {
+        let a = self.wrap(a);
+        self.inner.left_excess(&a)
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, b: &T)
+    where
+        T: Diff,
+    {
+        let b = self.wrap(b);
+        self.inner.right_excess(&b)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+}
+
+impl<'a, S: MapDiffer> MapDiffer for Configured<'a, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let (a, b) = (self.wrap(a), self.wrap(b));
+        self.inner.diff_entry(key, &a, &b)
+    }
+
+    fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let a = self.wrap(a);
+        self.inner.only_in_left(key, &a)
+    }
+
+    fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let b = self.wrap(b);
+        self.inner.only_in_right(key, &b)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+}
+
+impl<'a, S: SetDiffer> SetDiffer for Configured<'a, S> {
+    type Ok = S::Ok;
+    type Err = S::Err;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let (a, b) = (self.wrap(a), self.wrap(b));
+        self.inner.diff_equal(&a, &b)
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let a = self.wrap(a);
+        self.inner.only_in_left(&a)
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let b = self.wrap(b);
+        self.inner.only_in_right(&b)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.inner.end()
+    }
+}
@@ -0,0 +1,259 @@
+//! Helpers for property tests written with the `proptest` crate.
+//!
+//! When a property test fails, proptest shrinks the input to a smaller one
+//! that still fails, but for large structures even the shrunk values can be
+//! hard to compare by eye. [`prop_assert_eq_diff!`] works like proptest's
+//! `prop_assert_eq!`, but shows where its arguments differ, both as a list of
+//! [changed paths] and as a [`debug_diff`].
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use visit_diff::{prop_assert_eq_diff, Diff};
+//!
+//! #[derive(Diff, Debug, PartialEq)]
+//! struct Range {
+//!     start: u32,
+//!     end: u32,
+//! }
+//!
+//! fn normalize(r: Range) -> Range {
+//!     Range { start: r.start.min(r.end), end: r.start.max(r.end) }
+//! }
+//!
+//! proptest! {
+//!     fn normalize_is_idempotent(start in 0..100u32, end in 0..100u32) {
+//!         let once = normalize(Range { start, end });
+//!         let twice = normalize(Range { ..once });
+//!         prop_assert_eq_diff!(once, twice);
+//!     }
+//! }
+//! # normalize_is_idempotent();
+//! ```
+//!
+//! [`check_diff_laws`] and [`assert_diff_laws`] check that a type's [`Diff`]
+//! impl agrees with its `PartialEq` impl, on values generated by a strategy
+//! or by the type's `Arbitrary` impl.
+//!
+//! [`prop_assert_eq_diff!`]: ../macro.prop_assert_eq_diff.html
+//! [changed paths]: ../filter/fn.changed_paths.html
+//! [`debug_diff`]: ../fn.debug_diff.html
+//! [`check_diff_laws`]: fn.check_diff_laws.html
+//! [`assert_diff_laws`]: fn.assert_diff_laws.html
+//! [`Diff`]: ../trait.Diff.html
+
+use std::fmt::{self, Write};
+
+use ::proptest::arbitrary::{any, Arbitrary};
+use ::proptest::strategy::Strategy;
+use ::proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
+
+use crate::filter::changed_paths;
+use crate::{any_difference, debug_diff, Diff};
+
+/// Replacement for proptest's `prop_assert_eq!` macro that describes how its
+/// arguments differ on failure.
+///
+/// Like `prop_assert_eq!`, this returns a `TestCaseError` from the enclosing
+/// function, so it's meant for use in `proptest!` tests. The error lists the
+/// [changed paths] between the arguments, and shows a [`debug_diff`] of them.
+///
+/// [changed paths]: filter/fn.changed_paths.html
+/// [`debug_diff`]: fn.debug_diff.html
+#[macro_export]
+macro_rules! prop_assert_eq_diff {
+    ($left:expr, $right:expr $(,)?) => ({
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(*left_val == *right_val) {
+                    return ::core::result::Result::Err(
+                        $crate::proptest::eq_failure(
+                            left_val,
+                            right_val,
+                            ::core::option::Option::None,
+                        ),
+                    );
+                }
+            }
+        }
+    });
+    ($left:expr, $right:expr, $($arg:tt)+) => ({
+        match (&($left), &($right)) {
+            (left_val, right_val) => {
+                if !(*left_val == *right_val) {
+                    return ::core::result::Result::Err(
+                        $crate::proptest::eq_failure(
+                            left_val,
+                            right_val,
+                            ::core::option::Option::Some(
+                                format_args!($($arg)+),
+                            ),
+                        ),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Builds the error reported by [`prop_assert_eq_diff!`] when `left` and
+/// `right` aren't equal.
+///
+/// [`prop_assert_eq_diff!`]: ../macro.prop_assert_eq_diff.html
+pub fn eq_failure<T>(
+    left: &T,
+    right: &T,
+    message: Option<fmt::Arguments>,
+) -> TestCaseError
+where
+    T: Diff + ?Sized,
+{
+    let mut text = String::from("assertion failed: `(left == right)`\n");
+    if let Some(message) = message {
+        writeln!(text, "{}", message).unwrap();
+    }
+    write_changes(&mut text, left, right);
+    TestCaseError::fail(text)
+}
+
+/// The error from a test run on pairs of `T`s, such as [`check_diff_laws`].
+///
+/// [`check_diff_laws`]: fn.check_diff_laws.html
+pub type PairError<T> = TestError<(T, T)>;
+
+/// Lists the [changed paths] between the two values in a failing pair, as
+/// reported by [`check_diff_laws`] or any other test run on pairs.
+///
+/// Since proptest shrinks the pair before reporting it, this is usually the
+/// smallest set of changes that makes the test fail. Returns `None` if the
+/// test was aborted rather than failing.
+///
+/// [changed paths]: ../filter/fn.changed_paths.html
+/// [`check_diff_laws`]: fn.check_diff_laws.html
+pub fn failing_paths<T>(error: &PairError<T>) -> Option<Vec<String>>
+where
+    T: Diff,
+{
+    match error {
+        TestError::Abort(_) => None,
+        TestError::Fail(_, (a, b)) => Some(changed_paths(a, b)),
+    }
+}
+
+/// Checks that the [`Diff`] impl of the values generated by `strategy` agrees
+/// with their `PartialEq` impl.
+///
+/// Pairs of values are generated, half of them equal, and each pair is
+/// checked to make sure that:
+///
+/// - [`any_difference`] finds a difference exactly when the values aren't
+///   equal, and
+/// - it finds the same when the values are swapped.
+///
+/// On failure, the returned error holds the smallest failing pair proptest
+/// could find. [`failing_paths`] lists where it differs.
+///
+/// ```
+/// use proptest::prelude::*;
+/// use visit_diff::proptest::check_diff_laws;
+///
+/// assert!(check_diff_laws(prop::collection::vec(0..10u8, 0..5)).is_ok());
+/// ```
+///
+/// [`Diff`]: ../trait.Diff.html
+/// [`any_difference`]: ../fn.any_difference.html
+/// [`failing_paths`]: fn.failing_paths.html
+pub fn check_diff_laws<S>(strategy: S) -> Result<(), PairError<S::Value>>
+where
+    S: Strategy + 'static,
+    S::Value: Diff + PartialEq + Clone,
+{
+    let values = strategy.boxed();
+    let pairs =
+        (values.clone(), values, any::<bool>()).prop_map(|(a, b, same)| {
+            if same {
+                (a.clone(), a)
+            } else {
+                (a, b)
+            }
+        });
+    // Failures are reported to the caller, not saved for later runs.
+    let mut runner = TestRunner::new(Config {
+        failure_persistence: None,
+        ..Config::default()
+    });
+    runner.run(&pairs, |(a, b)| check_pair(&a, &b))
+}
+
+/// Checks that `T`'s [`Diff`] impl agrees with its `PartialEq` impl on values
+/// generated by its `Arbitrary` impl, as in [`check_diff_laws`].
+///
+/// # Panics
+///
+/// If they don't agree, describing the smallest values found where they
+/// don't.
+///
+/// [`Diff`]: ../trait.Diff.html
+/// [`check_diff_laws`]: fn.check_diff_laws.html
+#[track_caller]
+pub fn assert_diff_laws<T>()
+where
+    T: Arbitrary + Diff + PartialEq + Clone + 'static,
+{
+    if let Err(e) = check_diff_laws(any::<T>()) {
+        panic!("{}", e);
+    }
+}
+
+fn check_pair<T>(a: &T, b: &T) -> Result<(), TestCaseError>
+where
+    T: Diff + PartialEq,
+{
+    let equal = a == b;
+    let (forward, backward) = (any_difference(a, b), any_difference(b, a));
+    if forward == equal {
+        Err(law_failure(
+            if equal {
+                "values are equal, but Diff found a difference"
+            } else {
+                "values are not equal, but Diff found no difference"
+            },
+            a,
+            b,
+        ))
+    } else if forward != backward {
+        Err(law_failure(
+            "Diff found a difference in only one direction",
+            a,
+            b,
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn law_failure<T>(law: &str, a: &T, b: &T) -> TestCaseError
+where
+    T: Diff + ?Sized,
+{
+    let mut text = format!("{}\n", law);
+    write_changes(&mut text, a, b);
+    TestCaseError::fail(text)
+}
+
+/// Appends the changed paths and a `debug_diff` of `a` and `b` to `text`.
+fn write_changes<T>(text: &mut String, a: &T, b: &T)
+where
+    T: Diff + ?Sized,
+{
+    let paths = changed_paths(a, b);
+    if paths.is_empty() {
+        text.push_str("changed: (none found by Diff)\n");
+    } else {
+        let paths: Vec<&str> = paths
+            .iter()
+            .map(|p| if p.is_empty() { "(the whole value)" } else { p })
+            .collect();
+        writeln!(text, "changed: {}", paths.join(", ")).unwrap();
+    }
+    write!(text, "difference:\n{:#?}", debug_diff(a, b)).unwrap();
+}
@@ -7,13 +7,24 @@
 use std::fmt::Debug;
 use void::{ResultVoidExt, Void};
 
-use crate::{Diff, Differ, StructDiffer, TupleDiffer, SeqDiffer, SetDiffer, MapDiffer};
+use crate::debug::Whole;
+use crate::{Diff, DiffOptions, Differ, StructDiffer, TupleDiffer, SeqDiffer, SetDiffer, MapDiffer};
 
 /// Produces a `Value` describing differences between `a` and `b`.
 pub fn record_diff<T: Diff>(a: &T, b: &T) -> Value {
     Diff::diff(a, b, ValueRecorder).void_unwrap()
 }
 
+/// Like [`record_diff`], but using `options`.
+///
+/// [`record_diff`]: fn.record_diff.html
+pub fn record_diff_with<T>(a: &T, b: &T, options: &DiffOptions) -> Value
+where
+    T: Diff,
+{
+    record_diff(&options.wrap(a), &options.wrap(b))
+}
+
 /// A representation of differences between two values of a single Rust type.
 ///
 /// Atomic values are flattened into `String` using their `Debug`
@@ -98,7 +109,12 @@ pub enum Element {
     Both(Value),
 }
 
-struct ValueRecorder;
+/// A `Differ` that records a diff as a [`Value`], as used by
+/// [`record_diff`].
+///
+/// [`Value`]: enum.Value.html
+/// [`record_diff`]: fn.record_diff.html
+pub struct ValueRecorder;
 
 impl Differ for ValueRecorder {
     type Ok = Value;
@@ -112,11 +128,15 @@ impl Differ for ValueRecorder {
     type MapDiffer = MapRecorder;
     type SetDiffer = SequenceRecorder;
 
-    fn difference(self, a: &Debug, b: &Debug) -> Result<Self::Ok, Self::Err> {
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
         Ok(Value::Difference(format!("{:?}", a), format!("{:?}", b)))
     }
 
-    fn same(self, a: &Debug, b: &Debug) -> Result<Self::Ok, Self::Err> {
+    fn same(self, a: &dyn Debug, b: &dyn Debug) -> Result<Self::Ok, Self::Err> {
         Ok(Value::Same(format!("{:?}", a), format!("{:?}", b)))
     }
 
@@ -192,7 +212,10 @@ enum OutputStyle {
     VariantOf(&'static str),
 }
 
-struct StructRecorder(Struct, OutputStyle);
+/// The struct and struct variant differ of [`ValueRecorder`].
+///
+/// [`ValueRecorder`]: struct.ValueRecorder.html
+pub struct StructRecorder(Struct, OutputStyle);
 
 impl StructDiffer for StructRecorder {
     type Ok = Value;
@@ -221,7 +244,10 @@ impl StructDiffer for StructRecorder {
     }
 }
 
-struct TupleRecorder(Tuple, OutputStyle);
+/// The tuple and tuple variant differ of [`ValueRecorder`].
+///
+/// [`ValueRecorder`]: struct.ValueRecorder.html
+pub struct TupleRecorder(Tuple, OutputStyle);
 
 impl TupleDiffer for TupleRecorder {
     type Ok = Value;
@@ -250,7 +276,10 @@ impl TupleDiffer for TupleRecorder {
     }
 }
 
-struct SequenceRecorder(Vec<Element>);
+/// The sequence and set differ of [`ValueRecorder`].
+///
+/// [`ValueRecorder`]: struct.ValueRecorder.html
+pub struct SequenceRecorder(Vec<Element>);
 
 impl SeqDiffer for SequenceRecorder {
     type Ok = Value;
@@ -267,14 +296,14 @@ impl SeqDiffer for SequenceRecorder {
     where
         T: Diff
     {
-        self.0.push(Element::LeftOnly(format!("{:?}", a)))
+        self.0.push(Element::LeftOnly(format!("{:?}", Whole(a))))
     }
 
     fn right_excess<T: ?Sized>(&mut self, a: &T)
     where
         T: Diff
     {
-        self.0.push(Element::RightOnly(format!("{:?}", a)))
+        self.0.push(Element::RightOnly(format!("{:?}", Whole(a))))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Err> {
@@ -297,14 +326,14 @@ impl SetDiffer for SequenceRecorder {
     where
         T: Diff
     {
-        self.0.push(Element::LeftOnly(format!("{:?}", a)))
+        self.0.push(Element::LeftOnly(format!("{:?}", Whole(a))))
     }
 
     fn only_in_right<T: ?Sized>(&mut self, a: &T)
     where
         T: Diff
     {
-        self.0.push(Element::RightOnly(format!("{:?}", a)))
+        self.0.push(Element::RightOnly(format!("{:?}", Whole(a))))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Err> {
@@ -312,7 +341,10 @@ impl SetDiffer for SequenceRecorder {
     }
 }
 
-struct MapRecorder(Vec<(String, Element)>);
+/// The map differ of [`ValueRecorder`].
+///
+/// [`ValueRecorder`]: struct.ValueRecorder.html
+pub struct MapRecorder(Vec<(String, Element)>);
 
 impl MapDiffer for MapRecorder {
     type Ok = Value;
@@ -334,7 +366,7 @@ impl MapDiffer for MapRecorder {
         V: ?Sized + Diff,
     {
         let key = format!("{:?}", key);
-        self.0.push((key, Element::LeftOnly(format!("{:?}", a))))
+        self.0.push((key, Element::LeftOnly(format!("{:?}", Whole(a)))))
     }
 
     fn only_in_right<K, V>(&mut self, key: &K, a: &V)
@@ -343,7 +375,7 @@ impl MapDiffer for MapRecorder {
         V: ?Sized + Diff,
     {
         let key = format!("{:?}", key);
-        self.0.push((key, Element::RightOnly(format!("{:?}", a))))
+        self.0.push((key, Element::RightOnly(format!("{:?}", Whole(a)))))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Err> {
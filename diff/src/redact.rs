@@ -0,0 +1,114 @@
+//! Keeping secrets out of diffs.
+
+use core::fmt::{self, Debug, Formatter, Write};
+
+use crate::options::any_difference_within;
+use crate::{Diff, Differ};
+
+/// Wraps a value so that diffs tell whether it changed, but not what it is.
+///
+/// The wrapped values are compared as usual, but the `Differ` is only told
+/// whether they're the same or different, with both shown as `<redacted>`.
+/// `Redacted` values are also shown as `<redacted>` when formatted using
+/// `Debug`, so their contents don't get out that way either.
+///
+/// Deriving `Diff` wraps fields marked `#[diff(redact)]` in `Redacted`.
+///
+/// ```
+/// use visit_diff::{debug_diff, Diff, Redacted};
+///
+/// #[derive(Diff, Debug)]
+/// struct Login {
+///     user: &'static str,
+///     #[diff(redact)]
+///     password: &'static str,
+/// }
+///
+/// let a = Login { user: "ann", password: "hunter2" };
+/// let b = Login { user: "ann", password: "letmein" };
+/// assert_eq!(
+///     format!("{:?}", debug_diff(&a, &b)),
+///     "Login { user: \"ann\", \
+///      password: DIFF { L: <redacted>, R: <redacted> } }",
+/// );
+///
+/// assert_eq!(format!("{:?}", Redacted("hunter2")), "<redacted>");
+/// ```
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Redacted<T>(pub T);
+
+impl<T> Debug for Redacted<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl<T: Diff> Diff for Redacted<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        if any_difference_within(&a.0, &b.0, out.options()) {
+            out.difference(a, b)
+        } else {
+            out.same(a, b)
+        }
+    }
+}
+
+/// Like [`Redacted`], but shows a short hash of the value alongside
+/// `<redacted>`, such as `<redacted 3f2a9c01>`.
+///
+/// Equal values have equal hashes, even between runs of a program, so this
+/// shows *which* of several known values a secret has changed to or from.
+/// The hash is of the value's `Debug` representation, and is only 32 bits of
+/// a hash that isn't cryptographically secure, so it makes short or guessable
+/// secrets easy to recover. Use [`Redacted`] for those.
+///
+/// Deriving `Diff` wraps fields marked `#[diff(redact = "hash")]` in
+/// `RedactedHash`.
+///
+/// [`Redacted`]: struct.Redacted.html
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct RedactedHash<T>(pub T);
+
+impl<T: Debug> Debug for RedactedHash<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut hasher = Fnv1a::default();
+        // Fnv1a can't fail, but the value's Debug impl might.
+        write!(hasher, "{:?}", self.0)?;
+        write!(f, "<redacted {:08x}>", hasher.0 >> 32)
+    }
+}
+
+impl<T: Diff> Diff for RedactedHash<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        if any_difference_within(&a.0, &b.0, out.options()) {
+            out.difference(a, b)
+        } else {
+            out.same(a, b)
+        }
+    }
+}
+
+/// The 64-bit FNV-1a hash of the text written to it, which is simple, needs
+/// no allocation, and doesn't change between runs or platforms.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Write for Fnv1a {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &byte in s.as_bytes() {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+//! Diffing types from other crates.
+
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+
+use crate::{Diff, Differ};
+
+/// Diffs values of a type `T` defined elsewhere, which can't implement
+/// [`Diff`] itself.
+///
+/// This is implemented by deriving `Diff` for a mirror of `T`'s definition,
+/// marked `#[diff(remote = "path::to::T")]`. The mirror must have the same
+/// fields as `T`, by name, and with the same types. Fields that aren't public
+/// can be read through a getter method or function instead, by marking them
+/// `#[diff(getter = "path::to::function")]`.
+///
+/// The mirror can then be used through [`Remote`], or on fields of type `T`
+/// in other derived impls by marking them `#[diff(with = "Mirror")]`.
+/// Mirrors are never built, so they need `#[allow(dead_code)]` to keep the
+/// compiler from saying so.
+///
+/// ```
+/// use visit_diff::{debug_diff, Diff, Remote};
+///
+/// mod other_crate {
+///     #[derive(Debug)]
+///     pub struct Span {
+///         pub start: u32,
+///         len: u32,
+///     }
+///
+///     impl Span {
+///         pub fn new(start: u32, len: u32) -> Self {
+///             Span { start, len }
+///         }
+///
+///         pub fn len(&self) -> u32 {
+///             self.len
+///         }
+///     }
+/// }
+///
+/// use other_crate::Span;
+///
+/// #[derive(Diff)]
+/// #[diff(remote = "Span")]
+/// #[allow(dead_code)]
+/// struct SpanDef {
+///     start: u32,
+///     #[diff(getter = "Span::len")]
+///     len: u32,
+/// }
+///
+/// #[derive(Diff, Debug)]
+/// struct Token {
+///     text: &'static str,
+///     #[diff(with = "SpanDef")]
+///     span: Span,
+/// }
+///
+/// let a = Token { text: "x", span: Span::new(0, 1) };
+/// let b = Token { text: "x", span: Span::new(0, 2) };
+/// assert_eq!(
+///     format!("{:?}", debug_diff(&a, &b)),
+///     "Token { text: \"x\", span: Span { start: 0, len: DIFF { L: 1, R: 2 } } }",
+/// );
+///
+/// let (a, b) = (Span::new(0, 1), Span::new(3, 1));
+/// assert_eq!(
+///     format!("{:?}", debug_diff(Remote::<SpanDef, _>::new(&a), Remote::new(&b))),
+///     "Span { start: DIFF { L: 0, R: 3 }, len: 1 }",
+/// );
+/// ```
+///
+/// [`Diff`]: trait.Diff.html
+/// [`Remote`]: struct.Remote.html
+pub trait DiffRemote<T: ?Sized> {
+    /// Inspect `a` and `b` and tell `out` about any differences, as in
+    /// [`Diff::diff`].
+    ///
+    /// [`Diff::diff`]: trait.Diff.html#tymethod.diff
+    fn diff<D>(a: &T, b: &T, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ;
+}
+
+/// Wraps a reference to a `T` so that it implements [`Diff`] through `R`, a
+/// [`DiffRemote`] mirror of `T`.
+///
+/// `Remote` values are formatted using `T`'s own `Debug` impl.
+///
+/// [`Diff`]: trait.Diff.html
+/// [`DiffRemote`]: trait.DiffRemote.html
+pub struct Remote<'a, R, T: ?Sized> {
+    value: &'a T,
+    mirror: PhantomData<fn() -> R>,
+}
+
+impl<'a, R, T: ?Sized> Remote<'a, R, T> {
+    /// Wraps `value`.
+    pub fn new(value: &'a T) -> Self {
+        Remote {
+            value,
+            mirror: PhantomData,
+        }
+    }
+
+    /// Gets back the wrapped reference.
+    pub fn get(&self) -> &'a T {
+        self.value
+    }
+}
+
+impl<'a, R, T: ?Sized> Clone for Remote<'a, R, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, R, T: ?Sized> Copy for Remote<'a, R, T> {}
+
+impl<'a, R, T> Debug for Remote<'a, R, T>
+where
+    T: ?Sized + Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<'a, R, T> Diff for Remote<'a, R, T>
+where
+    R: DiffRemote<T>,
+    T: ?Sized + Debug,
+{
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        R::diff(a.value, b.value, out)
+    }
+}
@@ -0,0 +1,724 @@
+//! Diffing types that implement `serde::Serialize` but not [`Diff`].
+//!
+//! Both values are serialized into an intermediate tree, which is then walked
+//! in parallel: serde's structs, tuples, newtypes, enum variants, sequences and
+//! maps each turn into the corresponding [`Differ`] calls. This means any
+//! `Differ` -- including [`debug_diff`] and [`record_diff`] -- works on serde
+//! types without anybody having to `#[derive(Diff)]` for them.
+//!
+//! ```
+//! use serde::Serialize;
+//! use visit_diff::debug_diff;
+//! use visit_diff::serde::SerdeDiff;
+//!
+//! #[derive(Debug, Serialize)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! let a = SerdeDiff(Point { x: 1, y: 2 });
+//! let b = SerdeDiff(Point { x: 1, y: 3 });
+//! assert_eq!(
+//!     format!("{:?}", debug_diff(&a, &b)),
+//!     "Point { x: 1, y: DIFF { L: 2, R: 3 } }",
+//! );
+//! ```
+//!
+//! The tree only knows what the `Serialize` impl tells it, so diffs follow the
+//! serialized form rather than the Rust type where the two disagree. Two
+//! values of a struct that serialize different sets of fields (for example,
+//! because of `#[serde(skip_serializing_if)]`) are reported as different
+//! without looking at the fields, and a value that can't be serialized at all
+//! is always reported as different.
+//!
+//! [`Diff`]: ../trait.Diff.html
+//! [`Differ`]: ../trait.Differ.html
+//! [`debug_diff`]: ../fn.debug_diff.html
+//! [`record_diff`]: ../record/fn.record_diff.html
+
+use core::fmt::{self, Debug, Display};
+use core::ops::{Deref, DerefMut};
+use std::collections::HashMap;
+
+use ::serde::ser::{self, Serialize};
+
+use crate::{Diff, Differ, MapDiffer, SeqDiffer, StructDiffer, TupleDiffer};
+
+/// Diffs two values of a serializable type, reporting the results to `out`.
+///
+/// This is the serde equivalent of [`Diff::diff`].
+///
+/// [`Diff::diff`]: ../trait.Diff.html#tymethod.diff
+pub fn diff_serialize<T, D>(a: &T, b: &T, out: D) -> Result<D::Ok, D::Err>
+where
+    T: Serialize + Debug + ?Sized,
+    D: Differ,
+{
+    match (a.serialize(Serializer), b.serialize(Serializer)) {
+        (Ok(na), Ok(nb)) => Diff::diff(&na, &nb, out),
+        _ => out.difference(&a, &b),
+    }
+}
+
+/// Wrapper that implements [`Diff`] for any serializable type, using
+/// [`diff_serialize`].
+///
+/// `SerdeDiff` has the same `Debug` representation as the type it wraps. It
+/// also works on references, so you can write `SerdeDiff(&value)` to diff
+/// something you don't own.
+///
+/// [`Diff`]: ../trait.Diff.html
+/// [`diff_serialize`]: fn.diff_serialize.html
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SerdeDiff<T>(pub T);
+
+impl<T> Deref for SerdeDiff<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SerdeDiff<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Debug> Debug for SerdeDiff<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl<T: Serialize + Debug> Diff for SerdeDiff<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        diff_serialize(&a.0, &b.0, out)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// The intermediate tree.
+
+/// A serialized value. There's a variant for each part of the serde data
+/// model, and its `Debug` impl tries to match what `#[derive(Debug)]` would
+/// print for the original value.
+#[derive(Clone, PartialEq)]
+enum Node {
+    Unit,
+    Bool(bool),
+    I64(i64),
+    I128(i128),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Node>),
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, &'static str),
+    NewtypeStruct(&'static str, Box<Node>),
+    NewtypeVariant(&'static str, &'static str, Box<Node>),
+    Seq(Vec<Node>),
+    Tuple(Vec<Node>),
+    TupleStruct(&'static str, Vec<Node>),
+    TupleVariant(&'static str, &'static str, Vec<Node>),
+    Map(Vec<(Node, Node)>),
+    Struct(&'static str, Vec<(&'static str, Node)>),
+    StructVariant(&'static str, &'static str, Vec<(&'static str, Node)>),
+}
+
+impl Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Node::Unit => f.write_str("()"),
+            Node::Bool(x) => Debug::fmt(x, f),
+            Node::I64(x) => Debug::fmt(x, f),
+            Node::I128(x) => Debug::fmt(x, f),
+            Node::U64(x) => Debug::fmt(x, f),
+            Node::U128(x) => Debug::fmt(x, f),
+            Node::F32(x) => Debug::fmt(x, f),
+            Node::F64(x) => Debug::fmt(x, f),
+            Node::Char(x) => Debug::fmt(x, f),
+            Node::Str(x) => Debug::fmt(x, f),
+            Node::Bytes(x) => Debug::fmt(x, f),
+            Node::None => f.write_str("None"),
+            Node::Some(x) => f.debug_tuple("Some").field(x).finish(),
+            Node::UnitStruct(name) | Node::UnitVariant(_, name) => {
+                f.write_str(name)
+            }
+            Node::NewtypeStruct(name, x) | Node::NewtypeVariant(_, name, x) => {
+                f.debug_tuple(name).field(x).finish()
+            }
+            Node::Seq(xs) => f.debug_list().entries(xs).finish(),
+            Node::Tuple(xs) => fmt_tuple(f, "", xs),
+            Node::TupleStruct(name, xs) | Node::TupleVariant(_, name, xs) => {
+                fmt_tuple(f, name, xs)
+            }
+            Node::Map(entries) => f
+                .debug_map()
+                .entries(entries.iter().map(|(k, v)| (k, v)))
+                .finish(),
+            Node::Struct(name, fields)
+            | Node::StructVariant(_, name, fields) => {
+                let mut s = f.debug_struct(name);
+                for (k, v) in fields {
+                    s.field(k, v);
+                }
+                s.finish()
+            }
+        }
+    }
+}
+
+fn fmt_tuple(f: &mut fmt::Formatter, name: &str, xs: &[Node]) -> fmt::Result {
+    let mut t = f.debug_tuple(name);
+    for x in xs {
+        t.field(x);
+    }
+    t.finish()
+}
+
+impl Diff for Node {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        match (a, b) {
+            (Node::Some(x), Node::Some(y)) => {
+                let mut out = out.begin_tuple_variant("Option", "Some");
+                out.diff_field(&**x, &**y);
+                out.end()
+            }
+            (Node::NewtypeStruct(n, x), Node::NewtypeStruct(m, y))
+                if n == m =>
+            {
+                out.diff_newtype(n, &**x, &**y)
+            }
+            (Node::NewtypeVariant(t, n, x), Node::NewtypeVariant(u, m, y))
+                if t == u && n == m =>
+            {
+                let mut out = out.begin_tuple_variant(t, n);
+                out.diff_field(&**x, &**y);
+                out.end()
+            }
+            (Node::Seq(xs), Node::Seq(ys)) => {
+                let mut out = out.begin_seq();
+                out.diff_elements(xs, ys);
+                out.end()
+            }
+            (Node::Tuple(xs), Node::Tuple(ys)) if xs.len() == ys.len() => {
+                let mut out = out.begin_tuple("");
+                for (x, y) in xs.iter().zip(ys) {
+                    out.diff_field(x, y);
+                }
+                out.end()
+            }
+            (Node::TupleStruct(n, xs), Node::TupleStruct(m, ys))
+                if n == m && xs.len() == ys.len() =>
+            {
+                let mut out = out.begin_tuple(n);
+                for (x, y) in xs.iter().zip(ys) {
+                    out.diff_field(x, y);
+                }
+                out.end()
+            }
+            (Node::TupleVariant(t, n, xs), Node::TupleVariant(u, m, ys))
+                if t == u && n == m && xs.len() == ys.len() =>
+            {
+                let mut out = out.begin_tuple_variant(t, n);
+                for (x, y) in xs.iter().zip(ys) {
+                    out.diff_field(x, y);
+                }
+                out.end()
+            }
+            (Node::Map(xs), Node::Map(ys)) => {
+                // Keys can't be hashed, since they may hold floats, so the
+                // right-hand keys are indexed by how they print, and then
+                // compared for real.
+                let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+                for (j, (k, _)) in ys.iter().enumerate() {
+                    index.entry(format!("{:?}", k)).or_default().push(j);
+                }
+                let mut paired = vec![false; ys.len()];
+
+                let mut out = out.begin_map();
+                for (k, x) in xs {
+                    let found = index.get(&format!("{:?}", k)).and_then(|js| {
+                        js.iter()
+                            .copied()
+                            .find(|&j| !paired[j] && ys[j].0 == *k)
+                    });
+                    match found {
+                        Some(j) => {
+                            paired[j] = true;
+                            out.diff_entry(k, x, &ys[j].1)
+                        }
+                        None => out.only_in_left(k, x),
+                    }
+                }
+                for ((k, y), paired) in ys.iter().zip(paired) {
+                    if !paired {
+                        out.only_in_right(k, y);
+                    }
+                }
+                out.end()
+            }
+            (Node::Struct(n, xs), Node::Struct(m, ys))
+                if n == m && same_fields(xs, ys) =>
+            {
+                let mut out = out.begin_struct(n);
+                for ((k, x), (_, y)) in xs.iter().zip(ys) {
+                    out.diff_field(k, x, y);
+                }
+                out.end()
+            }
+            (Node::StructVariant(t, n, xs), Node::StructVariant(u, m, ys))
+                if t == u && n == m && same_fields(xs, ys) =>
+            {
+                let mut out = out.begin_struct_variant(t, n);
+                for ((k, x), (_, y)) in xs.iter().zip(ys) {
+                    out.diff_field(k, x, y);
+                }
+                out.end()
+            }
+            // Floats go through their own impls, which allow for the
+            // `float_tolerance`.
+            (Node::F32(x), Node::F32(y)) => Diff::diff(x, y, out),
+            (Node::F64(x), Node::F64(y)) => Diff::diff(x, y, out),
+            _ if a == b => out.same(a, b),
+            _ => out.difference(a, b),
+        }
+    }
+}
+
+fn same_fields(a: &[(&'static str, Node)], b: &[(&'static str, Node)]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|((j, _), (k, _))| j == k)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Building the tree.
+
+#[derive(Debug)]
+struct Error(String);
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Node;
+    type Error = Error;
+
+    type SerializeSeq = Elements;
+    type SerializeTuple = Elements;
+    type SerializeTupleStruct = Elements;
+    type SerializeTupleVariant = Elements;
+    type SerializeMap = Entries;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Fields;
+
+    fn serialize_bool(self, v: bool) -> Result<Node, Error> {
+        Ok(Node::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Node, Error> {
+        Ok(Node::I64(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Node, Error> {
+        Ok(Node::I64(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Node, Error> {
+        Ok(Node::I64(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Node, Error> {
+        Ok(Node::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Node, Error> {
+        Ok(Node::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Node, Error> {
+        Ok(Node::U64(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Node, Error> {
+        Ok(Node::U64(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Node, Error> {
+        Ok(Node::U64(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Node, Error> {
+        Ok(Node::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Node, Error> {
+        Ok(Node::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Node, Error> {
+        Ok(Node::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Node, Error> {
+        Ok(Node::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Node, Error> {
+        Ok(Node::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Node, Error> {
+        Ok(Node::Str(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Node, Error> {
+        Ok(Node::Bytes(v.to_owned()))
+    }
+
+    fn serialize_none(self) -> Result<Node, Error> {
+        Ok(Node::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Node, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(Node::Some(Box::new(value.serialize(Serializer)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Node, Error> {
+        Ok(Node::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Node, Error> {
+        Ok(Node::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Node, Error> {
+        Ok(Node::UnitVariant(name, variant))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Node, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = value.serialize(Serializer)?;
+        Ok(Node::NewtypeStruct(name, Box::new(value)))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Node, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = value.serialize(Serializer)?;
+        Ok(Node::NewtypeVariant(name, variant, Box::new(value)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Elements, Error> {
+        Ok(Elements::new(Kind::Seq, len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Elements, Error> {
+        Ok(Elements::new(Kind::Tuple, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Elements, Error> {
+        Ok(Elements::new(Kind::TupleStruct(name), len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Elements, Error> {
+        Ok(Elements::new(Kind::TupleVariant(name, variant), len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Entries, Error> {
+        Ok(Entries {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Fields, Error> {
+        Ok(Fields {
+            name,
+            variant: None,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Fields, Error> {
+        Ok(Fields {
+            name,
+            variant: Some(variant),
+            fields: Vec::with_capacity(len),
+        })
+    }
+}
+
+/// Which sort of `Node` a sequence of elements is going to become.
+enum Kind {
+    Seq,
+    Tuple,
+    TupleStruct(&'static str),
+    TupleVariant(&'static str, &'static str),
+}
+
+/// Collects the elements of sequences and all flavors of tuple.
+struct Elements {
+    kind: Kind,
+    items: Vec<Node>,
+}
+
+impl Elements {
+    fn new(kind: Kind, len: usize) -> Self {
+        Elements {
+            kind,
+            items: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Node, Error> {
+        Ok(match self.kind {
+            Kind::Seq => Node::Seq(self.items),
+            Kind::Tuple => Node::Tuple(self.items),
+            Kind::TupleStruct(name) => Node::TupleStruct(name, self.items),
+            Kind::TupleVariant(name, variant) => {
+                Node::TupleVariant(name, variant, self.items)
+            }
+        })
+    }
+}
+
+impl ser::SerializeSeq for Elements {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Elements {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Elements {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Elements {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        self.finish()
+    }
+}
+
+/// Collects the entries of a map.
+struct Entries {
+    entries: Vec<(Node, Node)>,
+    /// Key passed to `serialize_key`, waiting for its value.
+    key: Option<Node>,
+}
+
+impl ser::SerializeMap for Entries {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(key.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self.key.take().ok_or_else(|| {
+            Error("serialize_value called before serialize_key".to_owned())
+        })?;
+        self.entries.push((key, value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Map(self.entries))
+    }
+}
+
+/// Collects the fields of a struct or struct variant.
+struct Fields {
+    name: &'static str,
+    variant: Option<&'static str>,
+    fields: Vec<(&'static str, Node)>,
+}
+
+impl Fields {
+    fn push<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.fields.push((key, value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Node, Error> {
+        Ok(match self.variant {
+            None => Node::Struct(self.name, self.fields),
+            Some(variant) => {
+                Node::StructVariant(self.name, variant, self.fields)
+            }
+        })
+    }
+}
+
+impl ser::SerializeStruct for Fields {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Fields {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        self.finish()
+    }
+}
@@ -0,0 +1,441 @@
+//! Report differences as two aligned columns of text, like `diff -y`.
+//!
+//! Both values are pretty-printed as `{:#?}` would, with the left value in
+//! the left column and the right value in the right column. Each row is
+//! marked in the gutter between them: `|` for lines that differ, `<` for
+//! lines only on the left, and `>` for lines only on the right. Sequence
+//! elements that are mostly the same are shown next to each other. Elements
+//! and map entries that only exist on one side are set against blank rows,
+//! so that what follows them still lines up.
+
+use core::fmt::{Debug, Write};
+use itertools::EitherOrBoth;
+
+use crate::debug::Whole;
+use crate::similar::pair_up;
+use crate::{
+    Diff, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer, TupleDiffer,
+};
+
+/// Renders the differences between `a` and `b` side by side, in lines no
+/// wider than `width` characters.
+///
+/// Lines too long for their column are cut short and end in `…`.
+///
+/// ```
+/// use visit_diff::{Diff, side_by_side_diff};
+///
+/// #[derive(Diff, Debug)]
+/// struct ExampleStruct {
+///     name: &'static str,
+///     age: usize,
+/// }
+///
+/// let left = ExampleStruct { name: "Bob", age: 4 };
+/// let right = ExampleStruct { name: "Bob", age: 5 };
+///
+/// assert_eq!(
+///     side_by_side_diff(&left, &right, 40),
+///     "ExampleStruct {      ExampleStruct {\n\
+///     \x20   name: \"Bob\",         name: \"Bob\",\n\
+///     \x20   age: 4,        |     age: 5,\n\
+///      }                    }\n",
+/// );
+/// ```
+pub fn side_by_side_diff<T>(a: &T, b: &T, width: usize) -> String
+where
+    T: Diff + ?Sized,
+{
+    let mut rows = Rows {
+        rows: Vec::new(),
+        depth: 0,
+    };
+    entry(&mut rows, String::new(), "", a, b);
+
+    // Each column gets half of what's left after the gutter.
+    let column = width.saturating_sub(3) / 2;
+    let mut text = String::new();
+    for row in rows.rows {
+        let mark = match (&row.left, &row.right) {
+            (Some(l), Some(r)) if l == r => ' ',
+            (Some(_), Some(_)) => '|',
+            (Some(_), None) => '<',
+            _ => '>',
+        };
+        let left = fit(row.left.as_deref().unwrap_or(""), column);
+        let right = fit(row.right.as_deref().unwrap_or(""), column);
+        let line = format!("{:column$} {} {}", left, mark, right);
+        writeln!(text, "{}", line.trim_end()).unwrap();
+    }
+    text
+}
+
+/// Cuts `line` short, if necessary, to fit in `width` characters.
+fn fit(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        line.to_string()
+    } else {
+        let mut cut: String =
+            line.chars().take(width.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+/// One line of output, with each side absent if it's blank for alignment.
+struct Row {
+    left: Option<String>,
+    right: Option<String>,
+}
+
+/// Accumulates rows of output.
+struct Rows {
+    rows: Vec<Row>,
+    depth: usize,
+}
+
+impl Rows {
+    /// Lays out a value, possibly spanning several lines, as it would appear
+    /// at the current depth following `label` and followed by `suffix`.
+    fn lines(
+        &self,
+        label: &str,
+        value: &dyn Debug,
+        suffix: &str,
+    ) -> Vec<String> {
+        let indent = 4 * self.depth;
+        let text = format!("{:#?}", value);
+        let mut lines: Vec<String> = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let label = if i == 0 { label } else { "" };
+                format!("{:indent$}{}{}", "", label, line)
+            })
+            .collect();
+        if let Some(last) = lines.last_mut() {
+            last.push_str(suffix);
+        }
+        lines
+    }
+
+    /// Adds rows pairing up `left` and `right` lines, with blanks on the
+    /// shorter side.
+    fn pair(&mut self, left: Vec<String>, right: Vec<String>) {
+        let mut left = left.into_iter();
+        let mut right = right.into_iter();
+        loop {
+            let row = Row {
+                left: left.next(),
+                right: right.next(),
+            };
+            if row.left.is_none() && row.right.is_none() {
+                break;
+            }
+            self.rows.push(row);
+        }
+    }
+
+    /// Adds a line that's the same on both sides.
+    fn both(&mut self, line: String) {
+        self.rows.push(Row {
+            left: Some(line.clone()),
+            right: Some(line),
+        })
+    }
+}
+
+/// Renders one value within a larger structure, starting with `label` (a
+/// field name or map key) and ending with `suffix` (a comma, if it's not the
+/// outermost value).
+fn entry<T>(rows: &mut Rows, label: String, suffix: &'static str, a: &T, b: &T)
+where
+    T: Diff + ?Sized,
+{
+    let out = Column {
+        rows,
+        label,
+        suffix,
+    };
+    let _ = Diff::diff(a, b, out);
+}
+
+/// A `Differ` that renders a single value.
+struct Column<'a> {
+    rows: &'a mut Rows,
+    label: String,
+    suffix: &'static str,
+}
+
+impl<'a> Column<'a> {
+    fn open(self, opener: &str, close: &'static str) -> Block<'a> {
+        let indent = 4 * self.rows.depth;
+        self.rows
+            .both(format!("{:indent$}{}{}", "", self.label, opener));
+        self.rows.depth += 1;
+        Block {
+            rows: self.rows,
+            close,
+            suffix: self.suffix,
+        }
+    }
+}
+
+impl<'a> Differ for Column<'a> {
+    type Ok = ();
+    type Err = core::convert::Infallible;
+
+    type StructDiffer = Block<'a>;
+    type StructVariantDiffer = Block<'a>;
+    type TupleDiffer = Block<'a>;
+    type TupleVariantDiffer = Block<'a>;
+    type SeqDiffer = Block<'a>;
+    type MapDiffer = Block<'a>;
+    type SetDiffer = Block<'a>;
+
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
+        let left = self.rows.lines(&self.label, a, self.suffix);
+        let right = self.rows.lines(&self.label, b, self.suffix);
+        self.rows.pair(left, right);
+        Ok(())
+    }
+
+    fn same(self, a: &dyn Debug, _: &dyn Debug) -> Result<Self::Ok, Self::Err> {
+        for line in self.rows.lines(&self.label, a, self.suffix) {
+            self.rows.both(line);
+        }
+        Ok(())
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        ty: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<Self::Ok, Self::Err>
+    where
+        T: Diff,
+    {
+        let mut out = self.open(&format!("{}(", ty), ")");
+        TupleDiffer::diff_field(&mut out, a, b);
+        out.finish()
+    }
+
+    fn begin_struct(self, ty: &'static str) -> Self::StructDiffer {
+        self.open(&format!("{} {{", ty), "}")
+    }
+
+    fn begin_struct_variant(
+        self,
+        _: &'static str,
+        var: &'static str,
+    ) -> Self::StructVariantDiffer {
+        self.open(&format!("{} {{", var), "}")
+    }
+
+    fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+        self.open(&format!("{}(", ty), ")")
+    }
+
+    fn begin_tuple_variant(
+        self,
+        _: &'static str,
+        var: &'static str,
+    ) -> Self::TupleVariantDiffer {
+        self.open(&format!("{}(", var), ")")
+    }
+
+    fn begin_seq(self) -> Self::SeqDiffer {
+        self.open("[", "]")
+    }
+
+    fn begin_map(self) -> Self::MapDiffer {
+        self.open("{", "}")
+    }
+
+    fn begin_set(self) -> Self::SetDiffer {
+        self.open("{", "}")
+    }
+}
+
+/// Renders the contents of a struct, tuple, sequence, map or set.
+struct Block<'a> {
+    rows: &'a mut Rows,
+    close: &'static str,
+    suffix: &'static str,
+}
+
+impl<'a> Block<'a> {
+    /// Adds a value that only exists on one side, against blank rows on the
+    /// other.
+    fn one_sided(&mut self, label: &str, value: &dyn Debug, left: bool) {
+        let lines = self.rows.lines(label, value, ",");
+        if left {
+            self.rows.pair(lines, vec![]);
+        } else {
+            self.rows.pair(vec![], lines);
+        }
+    }
+
+    fn finish(self) -> Result<(), core::convert::Infallible> {
+        self.rows.depth -= 1;
+        let indent = 4 * self.rows.depth;
+        self.rows
+            .both(format!("{:indent$}{}{}", "", self.close, self.suffix));
+        Ok(())
+    }
+}
+
+impl<'a> StructDiffer for Block<'a> {
+    type Ok = ();
+    type Err = core::convert::Infallible;
+
+    fn diff_field<T: ?Sized>(&mut self, name: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        entry(self.rows, format!("{}: ", name), ",", a, b)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> TupleDiffer for Block<'a> {
+    type Ok = ();
+    type Err = core::convert::Infallible;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        entry(self.rows, String::new(), ",", a, b)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> SeqDiffer for Block<'a> {
+    type Ok = ();
+    type Err = core::convert::Infallible;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        entry(self.rows, String::new(), ",", a, b)
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, a: &T)
+    where
+        T: Diff,
+    {
+        self.one_sided("", &Whole(a), true)
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, b: &T)
+    where
+        T: Diff,
+    {
+        self.one_sided("", &Whole(b), false)
+    }
+
+    /// Buffers the elements and pairs up those that are mostly the same, so
+    /// that inserted and removed ones can be set against blank rows rather
+    /// than against whatever is in their place.
+    fn diff_elements<T, I>(&mut self, a: I, b: I)
+    where
+        T: Diff,
+        I: IntoIterator<Item = T>,
+    {
+        let a: Vec<T> = a.into_iter().collect();
+        let b: Vec<T> = b.into_iter().collect();
+        for step in pair_up(&a, &b) {
+            match step {
+                EitherOrBoth::Both(i, j) => {
+                    entry(self.rows, String::new(), ",", &a[i], &b[j])
+                }
+                EitherOrBoth::Left(i) => {
+                    self.one_sided("", &Whole(&a[i]), true)
+                }
+                EitherOrBoth::Right(j) => {
+                    self.one_sided("", &Whole(&b[j]), false)
+                }
+            }
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> MapDiffer for Block<'a> {
+    type Ok = ();
+    type Err = core::convert::Infallible;
+
+    fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        entry(self.rows, format!("{:?}: ", key), ",", a, b)
+    }
+
+    fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.one_sided(&format!("{:?}: ", key), &Whole(a), true)
+    }
+
+    fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.one_sided(&format!("{:?}: ", key), &Whole(b), false)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> SetDiffer for Block<'a> {
+    type Ok = ();
+    type Err = core::convert::Infallible;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        entry(self.rows, String::new(), ",", a, b)
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.one_sided("", &Whole(a), true)
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.one_sided("", &Whole(b), false)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
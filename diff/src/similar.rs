@@ -0,0 +1,532 @@
+//! Scoring how similar two values are.
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::ops::AddAssign;
+use itertools::{EitherOrBoth, Itertools};
+use void::{ResultVoidExt, Void};
+
+use crate::{
+    Diff, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer, TupleDiffer,
+};
+
+/// Scores how similar `a` and `b` are, from 0.0 (nothing in common) to 1.0
+/// (no differences).
+///
+/// The score is the fraction of leaf values -- those the `Diff` impls report
+/// as a whole, such as numbers and strings -- that are the same. Each element
+/// or map entry present on only one side counts as different leaves, as many
+/// as it contains. Values of different shapes, such as different variants of
+/// an enum, count as a single different leaf.
+///
+/// This is useful for fuzzy matching, like finding the expected record
+/// closest to the actual one.
+///
+/// ```
+/// use visit_diff::{Diff, similarity};
+///
+/// #[derive(Diff, Debug)]
+/// struct ExampleStruct {
+///     name: &'static str,
+///     age: usize,
+/// }
+///
+/// let left = ExampleStruct { name: "Bob", age: 4 };
+/// let right = ExampleStruct { name: "Bob", age: 5 };
+///
+/// assert_eq!(similarity(&left, &left), 1.0);
+/// assert_eq!(similarity(&left, &right), 0.5);
+/// ```
+pub fn similarity<T>(a: &T, b: &T) -> f64
+where
+    T: Diff + ?Sized,
+{
+    score::<Exact, T>(a, b).ratio()
+}
+
+/// Scores how similar `a` and `b` are, like [`similarity`], but giving
+/// partial credit for near misses.
+///
+/// Different leaves score by the edit distance between their `Debug`
+/// representations, so `"kitten"` and `"sitting"` are more similar than
+/// `"kitten"` and `"puppy"`. Sequences are aligned to pair up the most
+/// similar elements, so that an insertion or removal near the start doesn't
+/// make everything after it different.
+///
+/// Aligning sequences compares every element of one with every element of the
+/// other, so this takes time quadratic in their length.
+///
+/// [`side_by_side_diff`] aligns sequences the same way to decide which
+/// elements to show next to each other. Other sequence diffs, such as the ones
+/// [`debug_diff`] shows, pair elements up by position.
+///
+/// ```
+/// use visit_diff::{edit_similarity, similarity};
+///
+/// let left = vec!["ann", "bob", "cat"];
+/// let right = vec!["bob", "cat"];
+///
+/// assert_eq!(similarity(&left, &right), 0.0);
+/// assert!(edit_similarity(&left, &right) > 0.6);
+/// ```
+///
+/// [`similarity`]: fn.similarity.html
+/// [`side_by_side_diff`]: fn.side_by_side_diff.html
+/// [`debug_diff`]: fn.debug_diff.html
+#[cfg(feature = "std")]
+pub fn edit_similarity<T>(a: &T, b: &T) -> f64
+where
+    T: Diff + ?Sized,
+{
+    score::<Edits, T>(a, b).ratio()
+}
+
+/// The similarity of some number of leaves.
+#[derive(Copy, Clone, Debug, Default)]
+struct Score {
+    /// Sum of the similarity of each leaf, from 0 to 1.
+    same: f64,
+    /// Number of leaves.
+    total: f64,
+}
+
+impl Score {
+    fn ratio(self) -> f64 {
+        if self.total == 0.0 {
+            1.0
+        } else {
+            self.same / self.total
+        }
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        self.same += other.same;
+        self.total += other.total;
+    }
+}
+
+fn score<M, T>(a: &T, b: &T) -> Score
+where
+    M: Measure,
+    T: Diff + ?Sized,
+{
+    Diff::diff(a, b, Scorer::<M>(PhantomData)).void_unwrap()
+}
+
+/// Counts the leaves in `a`, which is what it weighs when it's only on one
+/// side.
+fn weight<M, T>(a: &T) -> f64
+where
+    M: Measure,
+    T: Diff + ?Sized,
+{
+    score::<M, T>(a, a).total
+}
+
+/// How leaves and sequences are compared.
+trait Measure: Sized {
+    /// Scores two leaves that are different.
+    fn leaf(a: &dyn Debug, b: &dyn Debug) -> f64;
+
+    /// Scores two sequences of elements.
+    fn elements<T, I>(a: I, b: I) -> Score
+    where
+        T: Diff,
+        I: IntoIterator<Item = T>,
+    {
+        let mut tally = Tally::<Self>::default();
+        for ab in a.into_iter().zip_longest(b) {
+            match ab {
+                EitherOrBoth::Both(a, b) => tally.pair(&a, &b),
+                EitherOrBoth::Left(x) | EitherOrBoth::Right(x) => {
+                    tally.one_sided(&x)
+                }
+            }
+        }
+        tally.score
+    }
+}
+
+/// Different leaves have nothing in common; elements are compared in order.
+struct Exact;
+
+impl Measure for Exact {
+    fn leaf(_: &dyn Debug, _: &dyn Debug) -> f64 {
+        0.0
+    }
+}
+
+/// Different leaves are compared by edit distance; elements are aligned.
+#[cfg(feature = "std")]
+struct Edits;
+
+#[cfg(feature = "std")]
+impl Measure for Edits {
+    fn leaf(a: &dyn Debug, b: &dyn Debug) -> f64 {
+        let a: Vec<char> = format!("{:?}", a).chars().collect();
+        let b: Vec<char> = format!("{:?}", b).chars().collect();
+        let longest = a.len().max(b.len());
+        if longest == 0 {
+            return 1.0;
+        }
+        // Levenshtein distance, keeping one row of the table at a time.
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+        for (i, x) in a.iter().enumerate() {
+            let mut diagonal = row[0];
+            row[0] = i + 1;
+            for (j, y) in b.iter().enumerate() {
+                let substitute = diagonal + usize::from(x != y);
+                diagonal = row[j + 1];
+                row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+            }
+        }
+        1.0 - row[b.len()] as f64 / longest as f64
+    }
+
+    fn elements<T, I>(a: I, b: I) -> Score
+    where
+        T: Diff,
+        I: IntoIterator<Item = T>,
+    {
+        let a: Vec<T> = a.into_iter().collect();
+        let b: Vec<T> = b.into_iter().collect();
+        let pairs: Vec<Vec<Score>> = a
+            .iter()
+            .map(|x| b.iter().map(|y| score::<Edits, T>(x, y)).collect())
+            .collect();
+        let gains: Vec<Vec<f64>> = pairs
+            .iter()
+            .map(|row| row.iter().map(|pair| pair.same).collect())
+            .collect();
+        let mut score = Score::default();
+        for step in align(&gains, b.len()) {
+            match step {
+                EitherOrBoth::Both(i, j) => score += pairs[i][j],
+                EitherOrBoth::Left(i) => {
+                    score.total += weight::<Edits, T>(&a[i])
+                }
+                EitherOrBoth::Right(j) => {
+                    score.total += weight::<Edits, T>(&b[j])
+                }
+            }
+        }
+        score
+    }
+}
+
+/// Pairs up the elements of two sequences, in order, for showing how they
+/// differ.
+///
+/// Elements are only paired if they're at least half the same, going by
+/// [`edit_similarity`], so that an element that was replaced isn't shown as
+/// changed into something it has little in common with. Of the alignments
+/// that do that, the one with the most similar pairs is chosen.
+///
+/// [`edit_similarity`]: fn.edit_similarity.html
+#[cfg(feature = "std")]
+pub(crate) fn pair_up<T>(a: &[T], b: &[T]) -> Vec<EitherOrBoth<usize, usize>>
+where
+    T: Diff,
+{
+    let gains: Vec<Vec<f64>> = a
+        .iter()
+        .map(|x| {
+            b.iter()
+                .map(|y| {
+                    let pair = score::<Edits, T>(x, y);
+                    pair.same - (pair.total - pair.same)
+                })
+                .collect()
+        })
+        .collect();
+    align(&gains, b.len())
+}
+
+/// Pairs up the elements of two sequences, in order, so that the pairs are
+/// worth as much as possible. `gains[i][j]` is what pairing the `i`th element
+/// on the left with the `j`th of the `m` on the right is worth. Returns the
+/// steps through both sequences, as the indices of a pair or of an element
+/// left unpaired.
+#[cfg(feature = "std")]
+fn align(gains: &[Vec<f64>], m: usize) -> Vec<EitherOrBoth<usize, usize>> {
+    // best[i][j] is the most to be had from pairing up the elements from the
+    // ith on the left and the jth on the right, in order.
+    let n = gains.len();
+    let mut best = vec![vec![0.0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            best[i][j] = (gains[i][j] + best[i + 1][j + 1])
+                .max(best[i + 1][j])
+                .max(best[i][j + 1]);
+        }
+    }
+
+    // Retrace the best alignment, preferring to pair elements up when
+    // there's a tie, since a pair weighs less than its parts apart.
+    let mut steps = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if best[i][j] == gains[i][j] + best[i + 1][j + 1] {
+            steps.push(EitherOrBoth::Both(i, j));
+            i += 1;
+            j += 1;
+        } else if best[i][j] == best[i + 1][j] {
+            steps.push(EitherOrBoth::Left(i));
+            i += 1;
+        } else {
+            steps.push(EitherOrBoth::Right(j));
+            j += 1;
+        }
+    }
+    steps.extend((i..n).map(EitherOrBoth::Left));
+    steps.extend((j..m).map(EitherOrBoth::Right));
+    steps
+}
+
+/// A `Differ` that scores the similarity of two values.
+struct Scorer<M>(PhantomData<M>);
+
+impl<M: Measure> Differ for Scorer<M> {
+    type Ok = Score;
+    type Err = Void;
+
+    type StructDiffer = Tally<M>;
+    type StructVariantDiffer = Tally<M>;
+    type TupleDiffer = Tally<M>;
+    type TupleVariantDiffer = Tally<M>;
+    type SeqDiffer = Tally<M>;
+    type MapDiffer = Tally<M>;
+    type SetDiffer = Tally<M>;
+
+    fn difference(self, a: &dyn Debug, b: &dyn Debug) -> Result<Score, Void> {
+        Ok(Score {
+            same: M::leaf(a, b),
+            total: 1.0,
+        })
+    }
+
+    fn same(self, _: &dyn Debug, _: &dyn Debug) -> Result<Score, Void> {
+        Ok(Score {
+            same: 1.0,
+            total: 1.0,
+        })
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        _: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<Score, Void>
+    where
+        T: Diff,
+    {
+        Diff::diff(a, b, self)
+    }
+
+    fn begin_struct(self, _: &'static str) -> Tally<M> {
+        Tally::default()
+    }
+
+    fn begin_struct_variant(
+        self,
+        _: &'static str,
+        _: &'static str,
+    ) -> Tally<M> {
+        Tally::default()
+    }
+
+    fn begin_tuple(self, _: &'static str) -> Tally<M> {
+        Tally::default()
+    }
+
+    fn begin_tuple_variant(self, _: &'static str, _: &'static str) -> Tally<M> {
+        Tally::default()
+    }
+
+    fn begin_seq(self) -> Tally<M> {
+        Tally::default()
+    }
+
+    fn begin_map(self) -> Tally<M> {
+        Tally::default()
+    }
+
+    fn begin_set(self) -> Tally<M> {
+        Tally::default()
+    }
+}
+
+/// Adds up the scores of the parts of a compound value.
+struct Tally<M> {
+    score: Score,
+    _measure: PhantomData<M>,
+}
+
+impl<M> Default for Tally<M> {
+    fn default() -> Self {
+        Tally {
+            score: Score::default(),
+            _measure: PhantomData,
+        }
+    }
+}
+
+impl<M: Measure> Tally<M> {
+    fn pair<T: ?Sized + Diff>(&mut self, a: &T, b: &T) {
+        self.score += score::<M, T>(a, b);
+    }
+
+    fn one_sided<T: ?Sized + Diff>(&mut self, x: &T) {
+        self.score.total += weight::<M, T>(x);
+    }
+
+    fn finish(self) -> Result<Score, Void> {
+        // Something with no leaves at all, like an empty struct or vector,
+        // still counts as one leaf, so that it weighs something in its
+        // parent.
+        Ok(if self.score.total == 0.0 {
+            Score {
+                same: 1.0,
+                total: 1.0,
+            }
+        } else {
+            self.score
+        })
+    }
+}
+
+impl<M: Measure> StructDiffer for Tally<M> {
+    type Ok = Score;
+    type Err = Void;
+
+    fn diff_field<T: ?Sized>(&mut self, _: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.pair(a, b)
+    }
+
+    fn end(self) -> Result<Score, Void> {
+        self.finish()
+    }
+}
+
+impl<M: Measure> TupleDiffer for Tally<M> {
+    type Ok = Score;
+    type Err = Void;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.pair(a, b)
+    }
+
+    fn end(self) -> Result<Score, Void> {
+        self.finish()
+    }
+}
+
+impl<M: Measure> SeqDiffer for Tally<M> {
+    type Ok = Score;
+    type Err = Void;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        self.pair(a, b)
+    }
+
+    fn diff_elements<T, I>(&mut self, a: I, b: I)
+    where
+        T: Diff,
+        I: IntoIterator<Item = T>,
+    {
+        self.score += M::elements(a, b)
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, a: &T)
+    where
+        T: Diff,
+    {
+        self.one_sided(a)
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, b: &T)
+    where
+        T: Diff,
+    {
+        self.one_sided(b)
+    }
+
+    fn end(self) -> Result<Score, Void> {
+        self.finish()
+    }
+}
+
+impl<M: Measure> MapDiffer for Tally<M> {
+    type Ok = Score;
+    type Err = Void;
+
+    fn diff_entry<K, V>(&mut self, _: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.pair(a, b)
+    }
+
+    fn only_in_left<K, V>(&mut self, _: &K, a: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.one_sided(a)
+    }
+
+    fn only_in_right<K, V>(&mut self, _: &K, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.one_sided(b)
+    }
+
+    fn end(self) -> Result<Score, Void> {
+        self.finish()
+    }
+}
+
+impl<M: Measure> SetDiffer for Tally<M> {
+    type Ok = Score;
+    type Err = Void;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.pair(a, b)
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.one_sided(a)
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.one_sided(b)
+    }
+
+    fn end(self) -> Result<Score, Void> {
+        self.finish()
+    }
+}
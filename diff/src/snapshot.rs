@@ -0,0 +1,512 @@
+//! Snapshot testing: checking values against renderings stored on disk.
+//!
+//! [`assert_snapshot_diff!`] renders a value and compares it to the rendering
+//! stored under `tests/snapshots/` in the crate being tested, in a file named
+//! after the snapshot with `.snap` added:
+//!
+//! ```no_run
+//! use visit_diff::{assert_snapshot_diff, Diff};
+//!
+//! #[derive(Debug, Diff)]
+//! struct Config {
+//!     name: &'static str,
+//!     ports: Vec<u16>,
+//! }
+//!
+//! let config = Config { name: "web", ports: vec![80, 443] };
+//! assert_snapshot_diff!(config, "web_config");
+//! ```
+//!
+//! If the value doesn't match the stored snapshot, the check fails, showing a
+//! [`debug_diff`] between the snapshot and the new value. To accept the
+//! changes instead, set the environment variable
+//! `VISIT_DIFF_UPDATE_SNAPSHOTS=1`, which stores the new renderings. A
+//! snapshot that hasn't been stored yet fails the check too, unless that
+//! variable is set, so that a snapshot missing from version control isn't
+//! quietly taken to be whatever the code does now.
+//!
+//! # Renderings
+//!
+//! Values are rendered by recording their structure, as with
+//! [`record_diff`], into a [`Snapshot`], and formatting that with `{:#?}`.
+//! This looks a lot like formatting the value itself with `{:#?}`, but keeps
+//! atomic values, such as numbers, strings, and anything `Debug` formatted
+//! whole, on lines of their own, and respects the value's [`Diff`] impl, so
+//! that [`Redacted`] fields stay so.
+//!
+//! Whether a value matches its snapshot is decided by reading the stored
+//! rendering back in and diffing it with the value's structure, so the order
+//! of the entries of maps and sets doesn't matter, and a `HashMap` matches
+//! however its entries happen to be ordered. If an atomic value's `Debug`
+//! output looks enough like structure to be mistaken for it, it may be read
+//! back in wrongly, and so fail to match.
+//!
+//! [`assert_snapshot_diff!`]: ../macro.assert_snapshot_diff.html
+//! [`debug_diff`]: ../fn.debug_diff.html
+//! [`record_diff`]: ../record/fn.record_diff.html
+//! [`Snapshot`]: enum.Snapshot.html
+//! [`Diff`]: ../trait.Diff.html
+//! [`Redacted`]: ../struct.Redacted.html
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Debug, Formatter};
+use std::path::Path;
+use std::sync::Mutex;
+use std::{env, fs, io};
+
+use crate::record::{record_diff, Element, Value, Variant};
+use crate::{
+    any_difference, debug_diff, Diff, Differ, MapDiffer, SeqDiffer, SetDiffer,
+    StructDiffer, TupleDiffer,
+};
+
+/// Checks that a value matches the snapshot of the given name, stored under
+/// `tests/snapshots/` in the crate being tested. See the [`snapshot`] module
+/// for details.
+///
+/// [`snapshot`]: snapshot/index.html
+#[macro_export]
+macro_rules! assert_snapshot_diff {
+    ($value:expr, $name:expr $(,)?) => {
+        $crate::snapshot::assert_snapshot(
+            &$value,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+            $name,
+        )
+    };
+}
+
+/// The environment variable that, when set to anything but `0`, makes
+/// snapshot checks store new renderings instead of failing.
+pub const UPDATE_VAR: &str = "VISIT_DIFF_UPDATE_SNAPSHOTS";
+
+/// Checks that `value` matches the snapshot `name` stored in `dir`, as
+/// [`assert_snapshot_diff!`] does.
+///
+/// # Panics
+///
+/// If `value` doesn't match the snapshot, or there's no snapshot, and
+/// [`UPDATE_VAR`] isn't set, or if the snapshot can't be read or written.
+///
+/// [`assert_snapshot_diff!`]: ../macro.assert_snapshot_diff.html
+/// [`UPDATE_VAR`]: constant.UPDATE_VAR.html
+#[track_caller]
+pub fn assert_snapshot<T, P>(value: &T, dir: P, name: &str)
+where
+    T: Diff,
+    P: AsRef<Path>,
+{
+    let path = dir.as_ref().join(format!("{}.snap", name));
+    let new = Snapshot::of(value);
+    let update = env::var(UPDATE_VAR).is_ok_and(|v| v != "0");
+
+    let old = match fs::read_to_string(&path) {
+        Ok(old) => Snapshot::parse(&old.replace("\r\n", "\n")),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if !update {
+                panic!(
+                    "snapshot `{}` hasn't been stored (set {}=1 to store it)",
+                    name, UPDATE_VAR
+                );
+            }
+            store(&path, &new.render());
+            eprintln!("stored new snapshot `{}`", path.display());
+            return;
+        }
+        Err(e) => panic!("can't read snapshot `{}`: {}", path.display(), e),
+    };
+    if !any_difference(&old, &new) {
+        return;
+    }
+    if update {
+        store(&path, &new.render());
+        eprintln!("updated snapshot `{}`", path.display());
+        return;
+    }
+
+    panic!(
+        r#"snapshot `{}` doesn't match (set {}=1 to update it)
+difference:
+{:#?}"#,
+        name,
+        UPDATE_VAR,
+        debug_diff(&old, &new)
+    );
+}
+
+#[track_caller]
+fn store(path: &Path, rendered: &str) {
+    let stored = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, rendered));
+    if let Err(e) = stored {
+        panic!("can't store snapshot `{}`: {}", path.display(), e);
+    }
+}
+
+/// The structure of a value, as stored in a snapshot.
+///
+/// Snapshots implement [`Diff`], presenting the structure they hold. Their
+/// `Debug` impl shows that structure the way formatting the original value
+/// would, and formatting with `{:#?}` gives the rendering stored on disk.
+///
+/// [`Diff`]: ../trait.Diff.html
+#[derive(Clone, Eq, PartialEq)]
+pub enum Snapshot {
+    /// An atomic value, in `Debug` format.
+    Atom(String),
+    /// A struct or struct variant, with its name and fields.
+    Struct(String, Vec<(String, Snapshot)>),
+    /// A tuple, tuple struct or tuple variant, with its name and fields. Raw
+    /// tuples have an empty name.
+    Tuple(String, Vec<Snapshot>),
+    /// A sequence.
+    Sequence(Vec<Snapshot>),
+    /// A set.
+    Set(Vec<Snapshot>),
+    /// A map, with its keys in `Debug` format.
+    Map(Vec<(String, Snapshot)>),
+}
+
+impl Snapshot {
+    /// Records the structure of `value`.
+    pub fn of<T: Diff>(value: &T) -> Self {
+        Snapshot::from_value(record_diff(value, value))
+    }
+
+    /// Renders the snapshot as stored on disk.
+    pub fn render(&self) -> String {
+        format!("{:#?}\n", self)
+    }
+
+    /// Reads a snapshot back in from its rendering.
+    ///
+    /// Anything that doesn't look like structure is taken to be an atomic
+    /// value, so this can't fail, but it can be wrong about text that an
+    /// atomic value's `Debug` impl made to look like structure.
+    pub fn parse(rendered: &str) -> Self {
+        let mut lines = rendered.lines().map(str::trim);
+        let first = lines.next().unwrap_or("");
+        Snapshot::parse_node(first, &mut lines)
+    }
+
+    fn from_value(value: Value) -> Self {
+        // The value was diffed against itself, so anything reported as
+        // different -- like a NaN -- is shown by its left-hand side.
+        match value {
+            Value::Same(a, _) | Value::Difference(a, _) => {
+                Snapshot::Atom(a.replace('\n', "\\n"))
+            }
+            Value::Newtype(name, inner) => Snapshot::Tuple(
+                name.to_string(),
+                vec![Snapshot::from_value(*inner)],
+            ),
+            Value::Struct(s) => Snapshot::from_struct(s),
+            Value::Tuple(t) => Snapshot::from_tuple(t),
+            Value::Enum(e) => match e.variant {
+                Variant::Struct(s) => Snapshot::from_struct(s),
+                Variant::Tuple(t) => Snapshot::from_tuple(t),
+            },
+            Value::Sequence(elements) => {
+                Snapshot::Sequence(Snapshot::from_elements(elements))
+            }
+            Value::Set(elements) => {
+                Snapshot::Set(Snapshot::from_elements(elements))
+            }
+            Value::Map(entries) => Snapshot::Map(
+                entries
+                    .into_iter()
+                    .filter_map(|(k, e)| {
+                        Snapshot::from_element(e).map(|v| (k, v))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn from_struct(s: crate::record::Struct) -> Self {
+        let fields = s
+            .fields
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value.map(|v| (name.to_string(), Snapshot::from_value(v)))
+            })
+            .collect();
+        Snapshot::Struct(s.name.to_string(), fields)
+    }
+
+    fn from_tuple(t: crate::record::Tuple) -> Self {
+        // Skipped fields keep their places, so the others keep theirs.
+        let fields = t
+            .fields
+            .into_iter()
+            .map(|value| match value {
+                Some(v) => Snapshot::from_value(v),
+                None => Snapshot::Atom("_".to_string()),
+            })
+            .collect();
+        Snapshot::Tuple(t.name.to_string(), fields)
+    }
+
+    fn from_elements(elements: Vec<Element>) -> Vec<Self> {
+        elements
+            .into_iter()
+            .filter_map(Snapshot::from_element)
+            .collect()
+    }
+
+    fn from_element(element: Element) -> Option<Self> {
+        match element {
+            Element::Both(v) => Some(Snapshot::from_value(v)),
+            Element::LeftOnly(a) => Some(Snapshot::Atom(a)),
+            Element::RightOnly(_) => None,
+        }
+    }
+
+    /// Parses the node whose rendering starts with `first`, stripped of any
+    /// label, taking any lines it continues onto from `lines`.
+    fn parse_node<'a>(
+        first: &str,
+        lines: &mut impl Iterator<Item = &'a str>,
+    ) -> Self {
+        let first = first.strip_suffix(',').unwrap_or(first);
+        if first == "[]" {
+            return Snapshot::Sequence(vec![]);
+        }
+        if first == "[" {
+            let items = Snapshot::parse_items(lines, "]");
+            return Snapshot::Sequence(
+                items.into_iter().map(unlabel).collect(),
+            );
+        }
+        if first == "{" {
+            let items = Snapshot::parse_items(lines, "}");
+            return if items.iter().all(|(label, _)| label.is_some()) {
+                Snapshot::Map(
+                    items
+                        .into_iter()
+                        .map(|(label, v)| (label.unwrap_or_default(), v))
+                        .collect(),
+                )
+            } else {
+                Snapshot::Set(items.into_iter().map(unlabel).collect())
+            };
+        }
+        if let Some(name) = first.strip_suffix(" {") {
+            let items = Snapshot::parse_items(lines, "}");
+            let fields = items
+                .into_iter()
+                .map(|(label, v)| match label {
+                    Some(label) => (label, v),
+                    None => (String::new(), v),
+                })
+                .collect();
+            return Snapshot::Struct(name.to_string(), fields);
+        }
+        if let Some(name) = first.strip_suffix('(') {
+            let items = Snapshot::parse_items(lines, ")");
+            let fields = items.into_iter().map(unlabel).collect();
+            return Snapshot::Tuple(name.to_string(), fields);
+        }
+        Snapshot::Atom(first.to_string())
+    }
+
+    /// Parses the items of a container, up to the line that closes it.
+    fn parse_items<'a>(
+        lines: &mut impl Iterator<Item = &'a str>,
+        close: &str,
+    ) -> Vec<(Option<String>, Self)> {
+        let mut items = vec![];
+        while let Some(line) = lines.next() {
+            if line.strip_suffix(',').unwrap_or(line) == close {
+                break;
+            }
+            let (label, rest) = split_label(line);
+            let node = Snapshot::parse_node(rest, lines);
+            items.push((label.map(str::to_string), node));
+        }
+        items
+    }
+}
+
+/// Puts an item's label, if it has one, back in front of it. Labels are only
+/// found in front of items where they don't belong when an atomic value has
+/// `: ` in it, so the item is an atomic value too.
+fn unlabel((label, node): (Option<String>, Snapshot)) -> Snapshot {
+    match (label, node) {
+        (Some(label), Snapshot::Atom(a)) => {
+            Snapshot::Atom(format!("{}: {}", label, a))
+        }
+        (_, node) => node,
+    }
+}
+
+/// Splits a line at the first `: ` outside of quotes and brackets, giving the
+/// field name or map key in front of it, if any.
+fn split_label(line: &str) -> (Option<&str>, &str) {
+    let mut depth = 0_usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if quoted {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => quoted = false,
+                _ => (),
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 && line[i + 1..].starts_with(' ') => {
+                return (Some(&line[..i]), &line[i + 2..]);
+            }
+            _ => (),
+        }
+    }
+    (None, line)
+}
+
+/// Formats a string as is, without quotes.
+struct Raw<'a>(&'a str);
+
+impl<'a> Debug for Raw<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Debug for Snapshot {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Snapshot::Atom(a) => f.write_str(a),
+            Snapshot::Struct(name, fields) => {
+                let mut s = f.debug_struct(name);
+                for (name, value) in fields {
+                    s.field(name, value);
+                }
+                s.finish()
+            }
+            Snapshot::Tuple(name, fields) => {
+                let mut t = f.debug_tuple(name);
+                for value in fields {
+                    t.field(value);
+                }
+                t.finish()
+            }
+            Snapshot::Sequence(elements) => {
+                f.debug_list().entries(elements).finish()
+            }
+            Snapshot::Set(elements) => f.debug_set().entries(elements).finish(),
+            Snapshot::Map(entries) => f
+                .debug_map()
+                .entries(entries.iter().map(|(k, v)| (Raw(k), v)))
+                .finish(),
+        }
+    }
+}
+
+/// Gives a name from a snapshot the `'static` lifetime that a `Differ` needs.
+///
+/// Names are kept for good, but there are only as many as there are fields
+/// and types in the snapshots a test program checks, and each is kept once.
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    match names.get(name) {
+        Some(name) => name,
+        None => {
+            let name: &'static str = Box::leak(name.into());
+            names.insert(name);
+            name
+        }
+    }
+}
+
+impl Diff for Snapshot {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        match (a, b) {
+            (Snapshot::Atom(x), Snapshot::Atom(y)) if x == y => out.same(a, b),
+            (Snapshot::Struct(n, fa), Snapshot::Struct(m, fb)) if n == m => {
+                let names = |f: &[(String, Snapshot)]| {
+                    f.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>()
+                };
+                if names(fa) == names(fb) {
+                    let mut out = out.begin_struct(intern(n));
+                    for ((name, va), (_, vb)) in fa.iter().zip(fb) {
+                        out.diff_field(intern(name), va, vb);
+                    }
+                    out.end()
+                } else {
+                    // Fields were added or removed, which structs can't show,
+                    // so show them as a map from field names instead.
+                    diff_entries(fa, fb, out)
+                }
+            }
+            (Snapshot::Tuple(n, fa), Snapshot::Tuple(m, fb))
+                if n == m && fa.len() == fb.len() =>
+            {
+                let mut out = out.begin_tuple(intern(n));
+                for (va, vb) in fa.iter().zip(fb) {
+                    out.diff_field(va, vb);
+                }
+                out.end()
+            }
+            (Snapshot::Sequence(ea), Snapshot::Sequence(eb)) => {
+                let mut out = out.begin_seq();
+                out.diff_elements(ea, eb);
+                out.end()
+            }
+            (Snapshot::Set(ea), Snapshot::Set(eb)) => {
+                let mut out = out.begin_set();
+                for x in ea {
+                    match eb.iter().find(|&y| y == x) {
+                        Some(y) => out.diff_equal(x, y),
+                        None => out.only_in_left(x),
+                    }
+                }
+                for y in eb.iter().filter(|&y| !ea.contains(y)) {
+                    out.only_in_right(y);
+                }
+                out.end()
+            }
+            (Snapshot::Map(ea), Snapshot::Map(eb)) => diff_entries(ea, eb, out),
+            // Some different shapes look the same, like an empty set and an
+            // empty map, or a struct without fields and an atomic value, and
+            // can't be told apart once read back in.
+            _ if format!("{:?}", a) == format!("{:?}", b) => out.same(a, b),
+            _ => out.difference(a, b),
+        }
+    }
+}
+
+/// Diffs entries matched up by key, as a map.
+fn diff_entries<D>(
+    a: &[(String, Snapshot)],
+    b: &[(String, Snapshot)],
+    out: D,
+) -> Result<D::Ok, D::Err>
+where
+    D: Differ,
+{
+    let mut out = out.begin_map();
+    for (k, va) in a {
+        match b.iter().find(|(kb, _)| kb == k) {
+            Some((_, vb)) => out.diff_entry(&Raw(k), va, vb),
+            None => out.only_in_left(&Raw(k), va),
+        }
+    }
+    for (k, vb) in b {
+        if !a.iter().any(|(ka, _)| ka == k) {
+            out.only_in_right(&Raw(k), vb);
+        }
+    }
+    out.end()
+}
@@ -0,0 +1,383 @@
+//! Counting differences, rather than describing them.
+
+use core::fmt::{self, Debug, Display};
+use void::{ResultVoidExt, Void};
+
+use crate::{
+    Diff, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer, TupleDiffer,
+};
+
+/// Counts of what differs between two values, as produced by [`diff_stats`].
+///
+/// The `Display` impl summarizes the changes in one line, like
+/// `3 fields changed, 2 elements added, 1 key removed`.
+///
+/// [`diff_stats`]: fn.diff_stats.html
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DiffStats {
+    /// Number of leaf values (those reported through [`Differ::same`] or
+    /// [`Differ::difference`]) compared.
+    ///
+    /// [`Differ::same`]: trait.Differ.html#tymethod.same
+    /// [`Differ::difference`]: trait.Differ.html#tymethod.difference
+    pub leaves_compared: usize,
+    /// Number of leaf values compared that were different.
+    pub leaves_different: usize,
+    /// Number of struct and tuple fields containing some difference.
+    pub fields_changed: usize,
+    /// Number of struct and tuple fields skipped by their `Diff` impls.
+    pub fields_skipped: usize,
+    /// Number of sequence or set elements in both values, which differ.
+    pub elements_changed: usize,
+    /// Number of sequence or set elements only in the right value.
+    pub elements_added: usize,
+    /// Number of sequence or set elements only in the left value.
+    pub elements_removed: usize,
+    /// Number of map keys only in the right value.
+    pub keys_added: usize,
+    /// Number of map keys only in the left value.
+    pub keys_removed: usize,
+    /// Number of map keys in both values, whose values differ.
+    pub keys_changed: usize,
+    /// How deeply nested the deepest value compared was, where the values
+    /// passed to [`diff_stats`] are at depth 0, their fields or elements at
+    /// depth 1, and so on.
+    ///
+    /// [`diff_stats`]: fn.diff_stats.html
+    pub max_depth: usize,
+}
+
+impl DiffStats {
+    /// Checks whether any difference was counted.
+    pub fn is_empty(&self) -> bool {
+        self.leaves_different == 0
+            && self.elements_added == 0
+            && self.elements_removed == 0
+            && self.keys_added == 0
+            && self.keys_removed == 0
+    }
+}
+
+impl Display for DiffStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = [
+            (self.fields_changed, "field", "changed"),
+            (self.elements_changed, "element", "changed"),
+            (self.elements_added, "element", "added"),
+            (self.elements_removed, "element", "removed"),
+            (self.keys_added, "key", "added"),
+            (self.keys_removed, "key", "removed"),
+            (self.keys_changed, "key", "changed"),
+        ];
+        let mut first = true;
+        for &(n, noun, verb) in counts.iter().filter(|c| c.0 != 0) {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            let s = if n == 1 { "" } else { "s" };
+            write!(f, "{} {}{} {}", n, noun, s, verb)?;
+        }
+        if first {
+            // Nothing structural changed, which leaves either no changes, or
+            // values that differ at the top level.
+            match self.leaves_different {
+                0 => f.write_str("no changes")?,
+                1 => f.write_str("1 value changed")?,
+                n => write!(f, "{} values changed", n)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Counts the differences between `a` and `b`.
+///
+/// ```
+/// use visit_diff::{Diff, diff_stats};
+///
+/// #[derive(Diff, Debug)]
+/// struct ExampleStruct {
+///     name: &'static str,
+///     tags: Vec<u8>,
+/// }
+///
+/// let left = ExampleStruct { name: "Bob", tags: vec![1] };
+/// let right = ExampleStruct { name: "Rototron 3k", tags: vec![1, 2, 3] };
+///
+/// let stats = diff_stats(&left, &right);
+/// assert_eq!(stats.leaves_compared, 2);
+/// assert_eq!(stats.elements_added, 2);
+/// assert_eq!(
+///     stats.to_string(),
+///     "2 fields changed, 2 elements added",
+/// );
+/// ```
+pub fn diff_stats<T>(a: &T, b: &T) -> DiffStats
+where
+    T: Diff + ?Sized,
+{
+    let mut stats = DiffStats::default();
+    Diff::diff(a, b, Counter::new(&mut stats, 0)).void_unwrap();
+    stats
+}
+
+/// A `Differ` that adds to a `DiffStats`, and reports whether it found any
+/// difference, for use by its parent.
+struct Counter<'a> {
+    stats: &'a mut DiffStats,
+    depth: usize,
+    different: bool,
+}
+
+impl<'a> Counter<'a> {
+    fn new(stats: &'a mut DiffStats, depth: usize) -> Self {
+        Counter {
+            stats,
+            depth,
+            different: false,
+        }
+    }
+
+    /// Counts the differences in a nested value, returning whether there
+    /// were any.
+    fn nested<T>(&mut self, a: &T, b: &T) -> bool
+    where
+        T: ?Sized + Diff,
+    {
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        let different = Diff::diff(a, b, Counter::new(self.stats, self.depth))
+            .void_unwrap();
+        self.different |= different;
+        different
+    }
+
+    /// Notes a value nested within this one that is only on one side.
+    fn one_sided(&mut self) {
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        self.different = true;
+    }
+
+    fn begin(self) -> Self {
+        Counter::new(self.stats, self.depth + 1)
+    }
+
+    fn finish(self) -> Result<bool, Void> {
+        Ok(self.different)
+    }
+}
+
+impl<'a> Differ for Counter<'a> {
+    type Ok = bool;
+    type Err = Void;
+
+    type StructDiffer = Self;
+    type StructVariantDiffer = Self;
+    type TupleDiffer = Self;
+    type TupleVariantDiffer = Self;
+    type SeqDiffer = Self;
+    type MapDiffer = Self;
+    type SetDiffer = Self;
+
+    fn difference(self, _: &dyn Debug, _: &dyn Debug) -> Result<bool, Void> {
+        self.stats.leaves_compared += 1;
+        self.stats.leaves_different += 1;
+        Ok(true)
+    }
+
+    fn same(self, _: &dyn Debug, _: &dyn Debug) -> Result<bool, Void> {
+        self.stats.leaves_compared += 1;
+        Ok(false)
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        ty: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<bool, Void>
+    where
+        T: Diff,
+    {
+        let mut out = self.begin_tuple(ty);
+        TupleDiffer::diff_field(&mut out, a, b);
+        TupleDiffer::end(out)
+    }
+
+    fn begin_struct(self, _: &'static str) -> Self {
+        self.begin()
+    }
+
+    fn begin_struct_variant(self, _: &'static str, _: &'static str) -> Self {
+        self.begin()
+    }
+
+    fn begin_tuple(self, _: &'static str) -> Self {
+        self.begin()
+    }
+
+    fn begin_tuple_variant(self, _: &'static str, _: &'static str) -> Self {
+        self.begin()
+    }
+
+    fn begin_seq(self) -> Self {
+        self.begin()
+    }
+
+    fn begin_map(self) -> Self {
+        self.begin()
+    }
+
+    fn begin_set(self) -> Self {
+        self.begin()
+    }
+}
+
+impl<'a> StructDiffer for Counter<'a> {
+    type Ok = bool;
+    type Err = Void;
+
+    fn diff_field<T: ?Sized>(&mut self, _: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        if self.nested(a, b) {
+            self.stats.fields_changed += 1;
+        }
+    }
+
+    fn skip_field<T: ?Sized>(&mut self, _: &'static str) {
+        self.stats.fields_skipped += 1;
+    }
+
+    fn end(self) -> Result<bool, Void> {
+        self.finish()
+    }
+}
+
+impl<'a> TupleDiffer for Counter<'a> {
+    type Ok = bool;
+    type Err = Void;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        if self.nested(a, b) {
+            self.stats.fields_changed += 1;
+        }
+    }
+
+    fn skip_field<T: ?Sized>(&mut self) {
+        self.stats.fields_skipped += 1;
+    }
+
+    fn end(self) -> Result<bool, Void> {
+        self.finish()
+    }
+}
+
+impl<'a> SeqDiffer for Counter<'a> {
+    type Ok = bool;
+    type Err = Void;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        if self.nested(a, b) {
+            self.stats.elements_changed += 1;
+        }
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, _: &T)
+    where
+        T: Diff,
+    {
+        self.stats.elements_removed += 1;
+        self.one_sided();
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, _: &T)
+    where
+        T: Diff,
+    {
+        self.stats.elements_added += 1;
+        self.one_sided();
+    }
+
+    fn end(self) -> Result<bool, Void> {
+        self.finish()
+    }
+}
+
+impl<'a> MapDiffer for Counter<'a> {
+    type Ok = bool;
+    type Err = Void;
+
+    fn diff_entry<K, V>(&mut self, _: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        if self.nested(a, b) {
+            self.stats.keys_changed += 1;
+        }
+    }
+
+    fn only_in_left<K, V>(&mut self, _: &K, _: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.stats.keys_removed += 1;
+        self.one_sided();
+    }
+
+    fn only_in_right<K, V>(&mut self, _: &K, _: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.stats.keys_added += 1;
+        self.one_sided();
+    }
+
+    fn end(self) -> Result<bool, Void> {
+        self.finish()
+    }
+}
+
+impl<'a> SetDiffer for Counter<'a> {
+    type Ok = bool;
+    type Err = Void;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        if self.nested(a, b) {
+            self.stats.elements_changed += 1;
+        }
+    }
+
+    fn only_in_left<V>(&mut self, _: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.stats.elements_removed += 1;
+        self.one_sided();
+    }
+
+    fn only_in_right<V>(&mut self, _: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.stats.elements_added += 1;
+        self.one_sided();
+    }
+
+    fn end(self) -> Result<bool, Void> {
+        self.finish()
+    }
+}
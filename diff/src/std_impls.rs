@@ -1,9 +1,10 @@
 use super::*;
+use crate::debug::Whole;
 
 /// Diff boxes by dereferencing.
 impl<T> Diff for Box<T>
 where
-    T: Diff,
+    T: Diff + ?Sized,
 {
     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
     where
@@ -16,7 +17,7 @@ where
 /// Diff Rcs by dereferencing.
 impl<T> Diff for std::rc::Rc<T>
 where
-    T: Diff,
+    T: Diff + ?Sized,
 {
     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
     where
@@ -29,7 +30,7 @@ where
 /// Diff Arcs by dereferencing.
 impl<T> Diff for std::sync::Arc<T>
 where
-    T: Diff,
+    T: Diff + ?Sized,
 {
     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
     where
@@ -42,7 +43,8 @@ where
 /// Diff Cow by dereferencing.
 impl<'a, T> Diff for std::borrow::Cow<'a, T>
 where
-    T: Clone + Diff,
+    T: ToOwned + Diff + ?Sized,
+    T::Owned: Debug,
 {
     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
     where
@@ -52,6 +54,149 @@ where
     }
 }
 
+/// Weak references diff by identity, not by contents. They usually point back
+/// up a structure, and following them would go around in circles.
+impl<T: ?Sized> Diff for std::rc::Weak<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        if !std::rc::Weak::ptr_eq(a, b) {
+            out.difference(a, b)
+        } else {
+            out.same(a, b)
+        }
+    }
+}
+
+/// Weak references diff by identity, not by contents. They usually point back
+/// up a structure, and following them would go around in circles.
+impl<T: ?Sized> Diff for std::sync::Weak<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        if !std::sync::Weak::ptr_eq(a, b) {
+            out.difference(a, b)
+        } else {
+            out.same(a, b)
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Locks. These are acquired without blocking, and presented the way their Debug
+// impls present them: a struct showing the data and the poison flag.
+//
+// A lock that's held elsewhere can't be looked into, so its data is shown as a
+// placeholder and reported as different from whatever is on the other side.
+// Poisoning doesn't stop us from looking at the data, but it is reported as a
+// field of its own.
+
+/// The data behind a lock, or `None` if it couldn't be acquired.
+struct LockData<'a, T: ?Sized>(Option<&'a T>);
+
+impl<'a, T: ?Sized + Debug> Debug for LockData<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.0 {
+            Some(data) => data.fmt(f),
+            None => f.write_str("<locked>"),
+        }
+    }
+}
+
+impl<'a, T: ?Sized + Diff> Diff for LockData<'a, T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        match (a.0, b.0) {
+            (Some(a), Some(b)) => Diff::diff(a, b, out),
+            // A lock compared against itself, held elsewhere.
+            (None, None) if std::ptr::eq(a, b) => out.same(a, b),
+            _ => out.difference(a, b),
+        }
+    }
+}
+
+fn try_lock<T: ?Sized>(
+    m: &std::sync::Mutex<T>,
+) -> Option<std::sync::MutexGuard<'_, T>> {
+    match m.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(std::sync::TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        Err(std::sync::TryLockError::WouldBlock) => None,
+    }
+}
+
+fn try_read<T: ?Sized>(
+    l: &std::sync::RwLock<T>,
+) -> Option<std::sync::RwLockReadGuard<'_, T>> {
+    match l.try_read() {
+        Ok(guard) => Some(guard),
+        Err(std::sync::TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        Err(std::sync::TryLockError::WouldBlock) => None,
+    }
+}
+
+/// A mutex compared against itself is only locked once, and its data is
+/// diffed against itself.
+impl<T: ?Sized + Diff> Diff for std::sync::Mutex<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let same = std::ptr::eq(a, b);
+        let guard_a = try_lock(a);
+        let guard_b = if same { None } else { try_lock(b) };
+        let data_a = LockData(guard_a.as_deref());
+        let data_b = LockData(guard_b.as_deref());
+
+        let mut out = out.begin_struct("Mutex");
+        out.diff_field("data", &data_a, if same { &data_a } else { &data_b });
+        out.diff_field("poisoned", &a.is_poisoned(), &b.is_poisoned());
+        out.end_non_exhaustive()
+    }
+}
+
+/// A lock compared against itself is only locked once, and its data is diffed
+/// against itself.
+impl<T: ?Sized + Diff> Diff for std::sync::RwLock<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let same = std::ptr::eq(a, b);
+        let guard_a = try_read(a);
+        let guard_b = if same { None } else { try_read(b) };
+        let data_a = LockData(guard_a.as_deref());
+        let data_b = LockData(guard_b.as_deref());
+
+        let mut out = out.begin_struct("RwLock");
+        out.diff_field("data", &data_a, if same { &data_a } else { &data_b });
+        out.diff_field("poisoned", &a.is_poisoned(), &b.is_poisoned());
+        out.end_non_exhaustive()
+    }
+}
+
+/// An uninitialized cell is only the same as another uninitialized cell.
+impl<T: Diff> Diff for std::sync::OnceLock<T> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        match (a.get(), b.get()) {
+            (None, None) => out.same(a, b),
+            (Some(a), Some(b)) => {
+                let mut out = out.begin_tuple("OnceLock");
+                out.diff_field(a, b);
+                out.end()
+            }
+            _ => out.difference(&Whole(a), &Whole(b)),
+        }
+    }
+}
+
 impl_diff_partial_eq!(String);
 impl_diff_partial_eq!(std::io::ErrorKind);
 impl_diff_partial_eq!(std::io::SeekFrom);
@@ -61,6 +206,41 @@ impl_diff_partial_eq!(std::net::SocketAddrV4);
 impl_diff_partial_eq!(std::net::SocketAddrV6);
 impl_diff_partial_eq!(std::net::IpAddr);
 impl_diff_partial_eq!(std::net::SocketAddr);
+impl_diff_partial_eq!(unsized std::ffi::OsStr);
+impl_diff_partial_eq!(std::ffi::OsString);
+impl_diff_partial_eq!(unsized std::ffi::CStr);
+impl_diff_partial_eq!(std::ffi::CString);
+impl_diff_partial_eq!(std::time::SystemTime);
+impl_diff_partial_eq!(std::time::Instant);
+
+/// Paths that differ are diffed as a sequence of components, so that a change
+/// in one directory name doesn't drown out the rest of the path.
+impl Diff for std::path::Path {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        if a == b {
+            return out.same(&a, &b);
+        }
+
+        let mut out = out.begin_seq();
+        out.diff_elements(
+            a.components().map(std::path::Component::as_os_str),
+            b.components().map(std::path::Component::as_os_str),
+        );
+        out.end()
+    }
+}
+
+impl Diff for std::path::PathBuf {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(a.as_path(), b.as_path(), out)
+    }
+}
 
 impl<V> Diff for Vec<V>
 where
@@ -102,6 +282,47 @@ where
     }
 }
 
+/// Diffs two maps whose entries are produced in ascending key order.
+fn diff_sorted_map<'a, K, V, I, D>(a: I, b: I, out: D) -> Result<D::Ok, D::Err>
+where
+    K: Ord + Debug + 'a,
+    V: Diff + 'a,
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+    D: Differ,
+{
+    let mut out = out.begin_map();
+
+    for ab in a.into_iter().merge_join_by(b, |(i, _), (j, _)| i.cmp(j)) {
+        match ab {
+            EitherOrBoth::Left((k, v)) => out.only_in_left(k, v),
+            EitherOrBoth::Right((k, v)) => out.only_in_right(k, v),
+            EitherOrBoth::Both((k, a), (_, b)) => out.diff_entry(k, a, b),
+        }
+    }
+
+    out.end()
+}
+
+/// Diffs two sets whose elements are produced in ascending order.
+fn diff_sorted_set<'a, K, I, D>(a: I, b: I, out: D) -> Result<D::Ok, D::Err>
+where
+    K: Ord + Diff + 'a,
+    I: IntoIterator<Item = &'a K>,
+    D: Differ,
+{
+    let mut out = out.begin_set();
+
+    for ab in a.into_iter().merge_join_by(b, |i, j| i.cmp(j)) {
+        match ab {
+            EitherOrBoth::Left(a) => out.only_in_left(a),
+            EitherOrBoth::Right(a) => out.only_in_right(a),
+            EitherOrBoth::Both(a, b) => out.diff_equal(a, b),
+        }
+    }
+
+    out.end()
+}
+
 impl<K, V> Diff for std::collections::BTreeMap<K, V>
 where
     K: Ord + Debug,
@@ -111,24 +332,15 @@ where
     where
         D: Differ,
     {
-        let mut out = out.begin_map();
-
-        for ab in a.iter().merge_join_by(b, |(i, _), (j, _)| i.cmp(j)) {
-            match ab {
-                EitherOrBoth::Left((k, v)) => out.only_in_left(k, v),
-                EitherOrBoth::Right((k, v)) => out.only_in_right(k, v),
-                EitherOrBoth::Both((k, a), (_, b)) => out.diff_entry(k, a, b),
-            }
-        }
-
-        out.end()
+        diff_sorted_map(a, b, out)
     }
 }
 
-impl<K, V> Diff for std::collections::HashMap<K, V>
+impl<K, V, S> Diff for std::collections::HashMap<K, V, S>
 where
     K: Eq + std::hash::Hash + Debug,
     V: Diff,
+    S: std::hash::BuildHasher,
 {
     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
     where
@@ -162,23 +374,14 @@ where
     where
         D: Differ,
     {
-        let mut out = out.begin_set();
-
-        for ab in a.iter().merge_join_by(b, |i, j| i.cmp(j)) {
-            match ab {
-                EitherOrBoth::Left(a) => out.only_in_left(a),
-                EitherOrBoth::Right(a) => out.only_in_right(a),
-                EitherOrBoth::Both(a, b) => out.diff_equal(a, b),
-            }
-        }
-
-        out.end()
+        diff_sorted_set(a, b, out)
     }
 }
 
-impl<K> Diff for std::collections::HashSet<K>
+impl<K, S> Diff for std::collections::HashSet<K, S>
 where
     K: std::hash::Hash + Eq + Diff,
+    S: std::hash::BuildHasher,
 {
     fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
     where
@@ -201,3 +404,141 @@ where
         out.end()
     }
 }
+
+/// Wraps a `HashMap` or `HashSet` (or a reference to one) so that it's diffed,
+/// and formatted with `Debug`, in ascending key order rather than in hash
+/// order.
+///
+/// Hash order can change from one run of a program to the next, which makes
+/// diffs hard to compare between runs. Sorting costs an allocation and requires
+/// the keys to be `Ord`, so it's opt-in.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use visit_diff::{debug_diff, Sorted};
+///
+/// let a: HashMap<_, _> = vec![(3, "c"), (1, "a"), (2, "b")]
+///     .into_iter()
+///     .collect();
+/// let b: HashMap<_, _> = vec![(3, "c"), (1, "a"), (2, "x")]
+///     .into_iter()
+///     .collect();
+///
+/// assert_eq!(
+///     format!("{:?}", debug_diff(Sorted(&a), Sorted(&b))),
+///     r#"{1: "a", 2: DIFF { L: "b", R: "x" }, 3: "c"}"#,
+/// );
+/// ```
+///
+/// `Sorted` can also be used to hold a map or set in a type that derives
+/// `Diff`.
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct Sorted<T>(pub T);
+
+impl<T> std::ops::Deref for Sorted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for Sorted<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+fn sorted_entries<K: Ord, V, S>(
+    map: &std::collections::HashMap<K, V, S>,
+) -> Vec<(&K, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by_key(|&(k, _)| k);
+    entries
+}
+
+fn sorted_elements<K: Ord, S>(
+    set: &std::collections::HashSet<K, S>,
+) -> Vec<&K> {
+    let mut elements: Vec<_> = set.iter().collect();
+    elements.sort_unstable();
+    elements
+}
+
+macro_rules! sorted_impl {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a, K, V, S> Debug for Sorted<$ty>
+            where
+                K: Ord + Debug,
+                V: Debug,
+            {
+                fn fmt(
+                    &self,
+                    f: &mut std::fmt::Formatter,
+                ) -> std::fmt::Result {
+                    f.debug_map().entries(sorted_entries(&self.0)).finish()
+                }
+            }
+
+            impl<'a, K, V, S> Diff for Sorted<$ty>
+            where
+                K: Ord + Debug,
+                V: Diff,
+            {
+                fn diff<D>(
+                    a: &Self,
+                    b: &Self,
+                    out: D,
+                ) -> Result<D::Ok, D::Err>
+                where
+                    D: Differ,
+                {
+                    let (a, b) = (sorted_entries(&a.0), sorted_entries(&b.0));
+                    diff_sorted_map(a, b, out)
+                }
+            }
+        )*
+    };
+    (set $($ty:ty),*) => {
+        $(
+            impl<'a, K, S> Debug for Sorted<$ty>
+            where
+                K: Ord + Debug,
+            {
+                fn fmt(
+                    &self,
+                    f: &mut std::fmt::Formatter,
+                ) -> std::fmt::Result {
+                    f.debug_set().entries(sorted_elements(&self.0)).finish()
+                }
+            }
+
+            impl<'a, K, S> Diff for Sorted<$ty>
+            where
+                K: Ord + Diff,
+            {
+                fn diff<D>(
+                    a: &Self,
+                    b: &Self,
+                    out: D,
+                ) -> Result<D::Ok, D::Err>
+                where
+                    D: Differ,
+                {
+                    let (a, b) = (sorted_elements(&a.0), sorted_elements(&b.0));
+                    diff_sorted_set(a, b, out)
+                }
+            }
+        )*
+    };
+}
+
+sorted_impl!(
+    std::collections::HashMap<K, V, S>,
+    &'a std::collections::HashMap<K, V, S>
+);
+sorted_impl!(set
+    std::collections::HashSet<K, S>,
+    &'a std::collections::HashSet<K, S>
+);
@@ -0,0 +1,361 @@
+//! Report differences as an indented tree, with lines only in the left value
+//! marked `-` and lines only in the right value marked `+`, much like a
+//! unified diff.
+//!
+//! As in `debug_diff`, values that are different all the way through are
+//! shown whole, rather than broken down. Values that are the same are shown on
+//! a single line.
+
+use core::convert::Infallible;
+use core::fmt::{self, Debug, Write};
+
+use crate::{
+    Diff, Differ, MapDiffer, SeqDiffer, SetDiffer, StructDiffer, TupleDiffer,
+};
+
+use super::change::{Change, Tally};
+use super::debug::Whole;
+
+/// Renders the differences between `a` and `b` as an indented tree.
+///
+/// Every line starts with a mark: `-` for lines only in `a`, `+` for lines
+/// only in `b`, and a space for lines in both.
+///
+/// ```
+/// use visit_diff::{Diff, tree_diff};
+///
+/// #[derive(Diff, Debug)]
+/// struct ExampleStruct {
+///     name: &'static str,
+///     age: usize,
+/// }
+///
+/// let left = ExampleStruct { name: "Bob", age: 4 };
+/// let right = ExampleStruct { name: "Bob", age: 5 };
+///
+/// assert_eq!(
+///     tree_diff(&left, &right),
+///     "  ExampleStruct {\n\
+///     \x20   name: \"Bob\",\n\
+///      -   age: 4,\n\
+///      +   age: 5,\n\
+///     \x20 }\n",
+/// );
+/// ```
+pub fn tree_diff<T>(a: &T, b: &T) -> String
+where
+    T: Diff + ?Sized,
+{
+    let mut p = Printer {
+        text: String::new(),
+        depth: 0,
+    };
+    entry(&mut p, String::new(), "", a, b);
+    p.text
+}
+
+/// Accumulates lines of output.
+struct Printer {
+    text: String,
+    depth: usize,
+}
+
+impl Printer {
+    fn line(&mut self, mark: char, body: fmt::Arguments) {
+        let indent = 2 * self.depth;
+        writeln!(self.text, "{} {:indent$}{}", mark, "", body).unwrap();
+    }
+}
+
+/// Renders one value within a larger structure, starting with `label` (a
+/// field name or map key) and ending with `suffix` (a comma, if it's not the
+/// outermost value).
+///
+/// The value is broken down as it's rendered. If it turns out to be the same,
+/// or different all the way through, what was rendered is replaced by a
+/// single line, or by the whole of each side.
+fn entry<T: Diff + ?Sized>(
+    p: &mut Printer,
+    label: String,
+    suffix: &'static str,
+    a: &T,
+    b: &T,
+) -> Change {
+    let mark = p.text.len();
+    let out = Tree {
+        p,
+        label: label.clone(),
+        suffix,
+    };
+    let change = Diff::diff(a, b, out).unwrap_or_else(|e| match e {});
+    let out = Tree { p, label, suffix };
+    let result = match change {
+        Change::Partly => return change,
+        Change::Unchanged => {
+            out.p.text.truncate(mark);
+            out.same(&Whole(a), &Whole(b))
+        }
+        Change::Entirely => {
+            out.p.text.truncate(mark);
+            out.difference(&Whole(a), &Whole(b))
+        }
+    };
+    result.unwrap_or_else(|e| match e {});
+    change
+}
+
+/// A `Differ` that renders a single value.
+struct Tree<'a> {
+    p: &'a mut Printer,
+    label: String,
+    suffix: &'static str,
+}
+
+impl<'a> Tree<'a> {
+    fn open(self, opener: &str, close: &'static str) -> Block<'a> {
+        self.p.line(' ', format_args!("{}{}", self.label, opener));
+        self.p.depth += 1;
+        Block {
+            p: self.p,
+            close,
+            suffix: self.suffix,
+            tally: Tally::new(),
+        }
+    }
+}
+
+impl<'a> Differ for Tree<'a> {
+    type Ok = Change;
+    type Err = Infallible;
+
+    type StructDiffer = Block<'a>;
+    type StructVariantDiffer = Block<'a>;
+    type TupleDiffer = Block<'a>;
+    type TupleVariantDiffer = Block<'a>;
+    type SeqDiffer = Block<'a>;
+    type MapDiffer = Block<'a>;
+    type SetDiffer = Block<'a>;
+
+    fn difference(
+        self,
+        a: &dyn Debug,
+        b: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
+        let (label, suffix) = (&self.label, self.suffix);
+        self.p.line('-', format_args!("{}{:?}{}", label, a, suffix));
+        self.p.line('+', format_args!("{}{:?}{}", label, b, suffix));
+        Ok(Change::Entirely)
+    }
+
+    fn same(self, a: &dyn Debug, _: &dyn Debug) -> Result<Self::Ok, Self::Err> {
+        let (label, suffix) = (&self.label, self.suffix);
+        self.p.line(' ', format_args!("{}{:?}{}", label, a, suffix));
+        Ok(Change::Unchanged)
+    }
+
+    fn diff_newtype<T: ?Sized>(
+        self,
+        ty: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<Self::Ok, Self::Err>
+    where
+        T: Diff,
+    {
+        // A newtype differs as much as what it wraps.
+        let out = self.open(&format!("{}(", ty), ")");
+        let change = entry(out.p, String::new(), ",", a, b);
+        out.finish()?;
+        Ok(change)
+    }
+
+    fn begin_struct(self, ty: &'static str) -> Self::StructDiffer {
+        self.open(&format!("{} {{", ty), "}")
+    }
+
+    fn begin_struct_variant(
+        self,
+        _: &'static str,
+        var: &'static str,
+    ) -> Self::StructVariantDiffer {
+        self.open(&format!("{} {{", var), "}")
+    }
+
+    fn begin_tuple(self, ty: &'static str) -> Self::TupleDiffer {
+        self.open(&format!("{}(", ty), ")")
+    }
+
+    fn begin_tuple_variant(
+        self,
+        _: &'static str,
+        var: &'static str,
+    ) -> Self::TupleVariantDiffer {
+        self.open(&format!("{}(", var), ")")
+    }
+
+    fn begin_seq(self) -> Self::SeqDiffer {
+        self.open("[", "]")
+    }
+
+    fn begin_map(self) -> Self::MapDiffer {
+        self.open("{", "}")
+    }
+
+    fn begin_set(self) -> Self::SetDiffer {
+        self.open("{", "}")
+    }
+}
+
+/// Renders the contents of a struct, tuple, sequence, map or set, one per
+/// line, keeping track of how many of them differ.
+struct Block<'a> {
+    p: &'a mut Printer,
+    close: &'static str,
+    suffix: &'static str,
+    tally: Tally,
+}
+
+impl<'a> Block<'a> {
+    fn finish(self) -> Result<Change, Infallible> {
+        self.p.depth -= 1;
+        self.p
+            .line(' ', format_args!("{}{}", self.close, self.suffix));
+        Ok(self.tally.change())
+    }
+}
+
+impl<'a> StructDiffer for Block<'a> {
+    type Ok = Change;
+    type Err = Infallible;
+
+    fn diff_field<T: ?Sized>(&mut self, name: &'static str, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let change = entry(self.p, format!("{}: ", name), ",", a, b);
+        self.tally.part(change)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> TupleDiffer for Block<'a> {
+    type Ok = Change;
+    type Err = Infallible;
+
+    fn diff_field<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let change = entry(self.p, String::new(), ",", a, b);
+        self.tally.part(change)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> SeqDiffer for Block<'a> {
+    type Ok = Change;
+    type Err = Infallible;
+
+    fn diff_element<T: ?Sized>(&mut self, a: &T, b: &T)
+    where
+        T: Diff,
+    {
+        let change = entry(self.p, String::new(), ",", a, b);
+        self.tally.part(change)
+    }
+
+    fn left_excess<T: ?Sized>(&mut self, a: &T)
+    where
+        T: Diff,
+    {
+        self.p.line('-', format_args!("{:?},", Whole(a)));
+        self.tally.one_sided(true)
+    }
+
+    fn right_excess<T: ?Sized>(&mut self, b: &T)
+    where
+        T: Diff,
+    {
+        self.p.line('+', format_args!("{:?},", Whole(b)));
+        self.tally.one_sided(true)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> MapDiffer for Block<'a> {
+    type Ok = Change;
+    type Err = Infallible;
+
+    fn diff_entry<K, V>(&mut self, key: &K, a: &V, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        let change = entry(self.p, format!("{:?}: ", key), ",", a, b);
+        self.tally.part(change)
+    }
+
+    fn only_in_left<K, V>(&mut self, key: &K, a: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.p.line('-', format_args!("{:?}: {:?},", key, Whole(a)));
+        self.tally.one_sided(false)
+    }
+
+    fn only_in_right<K, V>(&mut self, key: &K, b: &V)
+    where
+        K: ?Sized + Debug,
+        V: ?Sized + Diff,
+    {
+        self.p.line('+', format_args!("{:?}: {:?},", key, Whole(b)));
+        self.tally.one_sided(false)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
+
+impl<'a> SetDiffer for Block<'a> {
+    type Ok = Change;
+    type Err = Infallible;
+
+    fn diff_equal<V>(&mut self, a: &V, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        let change = entry(self.p, String::new(), ",", a, b);
+        self.tally.part(change)
+    }
+
+    fn only_in_left<V>(&mut self, a: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.p.line('-', format_args!("{:?},", Whole(a)));
+        self.tally.one_sided(false)
+    }
+
+    fn only_in_right<V>(&mut self, b: &V)
+    where
+        V: ?Sized + Diff,
+    {
+        self.p.line('+', format_args!("{:?},", Whole(b)));
+        self.tally.one_sided(false)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Err> {
+        self.finish()
+    }
+}
@@ -17,11 +17,15 @@ impl Differ for () {
     type MapDiffer = ();
     type SetDiffer = ();
 
-    fn difference(self, _: &Debug, _: &Debug) -> Result<Self::Ok, Self::Err> {
+    fn difference(
+        self,
+        _: &dyn Debug,
+        _: &dyn Debug,
+    ) -> Result<Self::Ok, Self::Err> {
         Ok(())
     }
 
-    fn same(self, _: &Debug, _: &Debug) -> Result<Self::Ok, Self::Err> {
+    fn same(self, _: &dyn Debug, _: &dyn Debug) -> Result<Self::Ok, Self::Err> {
         Ok(())
     }
 
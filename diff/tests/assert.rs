@@ -0,0 +1,164 @@
+//! The `assert_*_diff!` macros.
+
+use std::panic::{catch_unwind, UnwindSafe};
+use visit_diff::{
+    assert_eq_diff, assert_ne_diff, debug_assert_eq_diff, debug_assert_ne_diff,
+    limit::Limits, Diff, DiffOptions,
+};
+
+/// Deliberately doesn't implement `PartialEq`.
+#[derive(Clone, Debug, Diff)]
+struct Job {
+    id: u32,
+    name: &'static str,
+    steps: Vec<f64>,
+}
+
+fn job() -> Job {
+    Job {
+        id: 1,
+        name: "build",
+        steps: vec![1.0, 2.0],
+    }
+}
+
+fn panic_message<F>(f: F) -> String
+where
+    F: FnOnce() + UnwindSafe,
+{
+    let payload = catch_unwind(f).unwrap_err();
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => (*payload.downcast::<&str>().unwrap()).to_string(),
+    }
+}
+
+#[test]
+fn eq_without_partial_eq() {
+    assert_eq_diff!(job(), job());
+    assert_eq_diff!(job(), job(),);
+    assert_eq_diff!(job(), job(), "jobs {} and {}", 1, 1);
+
+    let message = panic_message(|| {
+        let mut b = job();
+        b.name = "test";
+        assert_eq_diff!(job(), b);
+    });
+    assert_eq!(
+        message,
+        "assertion failed: `(left == right)`\ndifference:\nJob {\n    \
+         id: 1,\n    name: DIFF {\n        L: \"build\",\n        \
+         R: \"test\",\n    },\n    steps: [\n        1.0,\n        \
+         2.0,\n    ],\n}",
+    );
+
+    let message = panic_message(|| assert_eq_diff!(1, 2, "for {}", "one"));
+    assert_eq!(
+        message,
+        "assertion failed: `(left == right)`\nfor one\ndifference:\n\
+         DIFF {\n    L: 1,\n    R: 2,\n}",
+    );
+}
+
+#[test]
+fn ne() {
+    let mut b = job();
+    b.id = 2;
+    assert_ne_diff!(job(), b);
+    assert_ne_diff!(job(), b, "ids should differ");
+
+    let message = panic_message(|| assert_ne_diff!(vec![1], vec![1]));
+    assert_eq!(
+        message,
+        "assertion failed: `(left != right)`\nvalue:\n[\n    1,\n]",
+    );
+}
+
+#[test]
+fn ignore() {
+    let mut b = job();
+    b.id = 2;
+    b.steps[1] = 3.0;
+    assert_eq_diff!(job(), b, ignore = ["id", "steps[1]"]);
+    assert_eq_diff!(job(), b, ignore = ["id", "steps"], "ignoring {}", "id");
+    assert_ne_diff!(job(), b, ignore = ["id"]);
+
+    let message = panic_message(move || {
+        assert_eq_diff!(job(), b, ignore = ["id"], "with {:?}", "steps");
+    });
+    assert!(
+        message.starts_with(
+            "assertion failed: `(left == right)`\nwith \"steps\"\n\
+             difference:\nJob {\n    name: \"build\",\n"
+        ),
+        "{}",
+        message
+    );
+    assert!(message.contains("R: 3.0,"), "{}", message);
+
+    let message = panic_message(|| {
+        let mut b = job();
+        b.id = 2;
+        assert_ne_diff!(job(), b, ignore = ["id"]);
+    });
+    assert!(
+        message.starts_with(
+            "assertion failed: `(left != right)`\nvalue:\nJob {\n    name: \"build\",\n"
+        ),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn options() {
+    let options = DiffOptions {
+        float_tolerance: 0.5,
+        ..DiffOptions::default()
+    };
+    let mut b = job();
+    b.steps[0] = 1.25;
+    assert_ne_diff!(job(), b);
+    assert_eq_diff!(job(), b, options = options);
+    assert_eq_diff!(job(), b, options = &options, "within {}", 0.5);
+    assert_ne_diff!(1.0, 2.0, options = options);
+
+    let message = panic_message(|| {
+        assert_ne_diff!(1.0, 1.25, options = options, "too close");
+    });
+    assert_eq!(
+        message,
+        "assertion failed: `(left != right)`\ntoo close\nvalue:\n1.0"
+    );
+}
+
+#[test]
+fn limits_only_shorten_the_message() {
+    let options = DiffOptions {
+        limits: Limits {
+            max_elements: 3,
+            ..Limits::default()
+        },
+        ..DiffOptions::default()
+    };
+    let (a, b) = (vec![1, 2, 3, 4, 5], vec![1, 2, 3, 4, 6]);
+    assert_ne_diff!(a, b, options = options);
+
+    let message = panic_message(|| assert_eq_diff!(a, b, options = options));
+    assert_eq!(
+        message,
+        "assertion failed: `(left == right)`\ndifference:\n[\n    1,\n    \
+         2,\n    3,\n    DIFF {\n        L: … 1 more element,\n        \
+         R: … 1 more element,\n    },\n    … 1 more element,\n]",
+    );
+}
+
+#[test]
+fn debug_variants() {
+    debug_assert_eq_diff!(job(), job());
+    debug_assert_eq_diff!(job(), job(), ignore = ["id"]);
+    debug_assert_ne_diff!(1, 2, "differ");
+
+    let result = catch_unwind(|| debug_assert_eq_diff!(1, 2));
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+}
@@ -0,0 +1,173 @@
+//! Combining differs.
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use visit_diff::combinators::{MapErr, MapOk, Tee};
+use visit_diff::constant::Const;
+use visit_diff::record::{record_diff, Value, ValueRecorder};
+use visit_diff::{all_different_detector, difference_detector, Diff, Differ};
+use void::{ResultVoidExt, Void};
+
+#[derive(Debug, Diff)]
+struct Pair {
+    name: &'static str,
+    list: Vec<u32>,
+}
+
+const A: Pair = Pair {
+    name: "a",
+    list: Vec::new(),
+};
+
+fn b() -> Pair {
+    Pair {
+        name: "b",
+        list: vec![1],
+    }
+}
+
+#[test]
+fn tee() {
+    let b = b();
+    let out = Tee(ValueRecorder, Tee(ValueRecorder, Const(3)));
+    let (x, (y, n)) = Diff::diff(&A, &b, out).void_unwrap();
+    assert_eq!(x, record_diff(&A, &b));
+    assert_eq!(x, y);
+    assert_eq!(n, 3);
+}
+
+/// Counts how many times it's diffed.
+#[derive(Debug)]
+struct Counted<'a>(u32, &'a Cell<u32>);
+
+impl<'a> Diff for Counted<'a> {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        a.1.set(a.1.get() + 1);
+        Diff::diff(&a.0, &b.0, out)
+    }
+}
+
+#[test]
+fn tee_lockstep() {
+    let count = Cell::new(0);
+    let a = vec![Counted(1, &count), Counted(2, &count)];
+    let b = vec![Counted(1, &count), Counted(3, &count)];
+    let out = Tee(ValueRecorder, Tee(ValueRecorder, ValueRecorder));
+    let (x, (y, z)) = Diff::diff(&a, &b, out).void_unwrap();
+    assert_eq!(count.get(), 2);
+    assert_eq!(x, record_diff(&a, &b));
+    assert_eq!((&x, &x), (&y, &z));
+
+    // A differ that doesn't look inside leaves the other to do it alone.
+    count.set(0);
+    let (n, y) = Diff::diff(&a, &b, Tee(Const(1), ValueRecorder)).void_unwrap();
+    assert_eq!((n, count.get()), (1, 2));
+    assert_eq!(x, y);
+}
+
+#[test]
+fn tee_detectors() {
+    let b = b();
+    let out = Tee(
+        difference_detector(),
+        Tee(all_different_detector(), ValueRecorder),
+    );
+    let (any, (all, value)) = Diff::diff(&A, &b, out).void_unwrap();
+    assert!(any && all);
+    assert_eq!(value, record_diff(&A, &b));
+}
+
+#[test]
+fn map_ok() {
+    let b = b();
+    let out = MapOk(ValueRecorder, |v| matches!(v, Value::Struct(_)));
+    assert!(Diff::diff(&A, &b, out).void_unwrap());
+    let out = MapOk(ValueRecorder, |v| matches!(v, Value::Struct(_)));
+    assert!(!Diff::diff(&1, &2, out).void_unwrap());
+}
+
+fn absurd(v: Void) -> &'static str {
+    match v {}
+}
+
+/// A differ that fails on any difference between leaves, and otherwise
+/// doesn't look inside compound values.
+struct Strict;
+
+type Inside = MapErr<Const<()>, fn(Void) -> &'static str>;
+
+impl Differ for Strict {
+    type Ok = ();
+    type Err = &'static str;
+
+    type StructDiffer = Inside;
+    type StructVariantDiffer = Inside;
+    type TupleDiffer = Inside;
+    type TupleVariantDiffer = Inside;
+    type SeqDiffer = Inside;
+    type MapDiffer = Inside;
+    type SetDiffer = Inside;
+
+    fn difference(self, _: &dyn Debug, _: &dyn Debug) -> Result<(), Self::Err> {
+        Err("different")
+    }
+
+    fn same(self, _: &dyn Debug, _: &dyn Debug) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    fn diff_newtype<T>(
+        self,
+        _: &'static str,
+        a: &T,
+        b: &T,
+    ) -> Result<(), Self::Err>
+    where
+        T: ?Sized + Diff,
+    {
+        Diff::diff(a, b, self)
+    }
+
+    fn begin_struct(self, _: &'static str) -> Inside {
+        MapErr(Const(()), absurd)
+    }
+
+    fn begin_struct_variant(self, _: &'static str, _: &'static str) -> Inside {
+        MapErr(Const(()), absurd)
+    }
+
+    fn begin_tuple(self, _: &'static str) -> Inside {
+        MapErr(Const(()), absurd)
+    }
+
+    fn begin_tuple_variant(self, _: &'static str, _: &'static str) -> Inside {
+        MapErr(Const(()), absurd)
+    }
+
+    fn begin_seq(self) -> Inside {
+        MapErr(Const(()), absurd)
+    }
+
+    fn begin_map(self) -> Inside {
+        MapErr(Const(()), absurd)
+    }
+
+    fn begin_set(self) -> Inside {
+        MapErr(Const(()), absurd)
+    }
+}
+
+#[test]
+fn map_err() {
+    let out = || Tee(MapErr(ValueRecorder, absurd as fn(_) -> _), Strict);
+    assert_eq!(Diff::diff(&1, &2, out()).unwrap_err(), "different");
+    let (value, ()) = Diff::diff(&1, &1, out()).unwrap();
+    assert_eq!(value, Value::Same("1".into(), "1".into()));
+    assert!(Diff::diff(&A, &b(), out()).is_ok());
+
+    let out = MapErr(Strict, |e: &str| e.len());
+    assert_eq!(Diff::diff(&1, &2, out), Err(9));
+}
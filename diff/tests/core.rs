@@ -9,7 +9,27 @@ debug_equivalence! {
     str => "hello, world";
     tuple => (true, 42u32, ());
     array => [0u32, 1, 2, 3];
+    big_array => [7u8; 64];
+    tuple_12 => (1u8, 2u16, 3u32, 4u64, 5i8, 6i16, 7i32, 8i64, 9usize, 10isize,
+                 true, 'c');
     slice => &[0u32, 1, 2, 3] as &[u32];
     cell => core::cell::Cell::new(42u32);
     ref_cell => core::cell::RefCell::new(42u32);
+    once_cell => core::cell::OnceCell::from(42u32);
+    once_cell_uninit => core::cell::OnceCell::<u32>::new();
+    atomic_u32 => core::sync::atomic::AtomicU32::new(42);
+    atomic_bool => core::sync::atomic::AtomicBool::new(true);
+    range_inclusive => 1u32..=5;
+    bound => core::ops::Bound::Included(3u32);
+    bound_unbounded => core::ops::Bound::<u32>::Unbounded;
+    non_zero => core::num::NonZeroU32::new(5).unwrap();
+    reverse => core::cmp::Reverse(5u32);
+    pin => core::pin::Pin::new(&5u32);
+    poll => core::task::Poll::Ready(5u32);
+    poll_pending => core::task::Poll::<u32>::Pending;
+    control_flow => core::ops::ControlFlow::<u32, bool>::Break(5);
+    saturating => core::num::Saturating(5u32);
+    layout => core::alloc::Layout::new::<u32>();
+    type_id => core::any::TypeId::of::<u32>();
+    parse_int_error => "x".parse::<u8>().unwrap_err();
 }
@@ -0,0 +1,225 @@
+//! Impls for other crates' types. These only run with the corresponding
+//! features enabled, e.g. `cargo test --all-features`.
+
+#[allow(unused_macros)]
+#[macro_use]
+mod common;
+
+#[cfg(feature = "indexmap")]
+mod indexmap {
+    debug_equivalence! {
+        index_map => {
+            let mut map = indexmap::IndexMap::new();
+            map.insert(1234u32, "there");
+            map.insert(12, "hello");
+            map
+        };
+        index_set => {
+            let mut set = indexmap::IndexSet::new();
+            set.insert(1234u32);
+            set.insert(12);
+            set
+        };
+    }
+
+    #[test]
+    fn index_map_order() {
+        use visit_diff::record::*;
+        let a: indexmap::IndexMap<u32, u32> =
+            vec![(3, 0), (1, 0), (2, 0)].into_iter().collect();
+        let b: indexmap::IndexMap<u32, u32> =
+            vec![(4, 0), (2, 0), (1, 1)].into_iter().collect();
+        let same = |v: &str| Value::Same(v.into(), v.into());
+        assert_eq!(
+            record_diff(&a, &b),
+            Value::Map(vec![
+                ("3".into(), Element::LeftOnly("0".into())),
+                (
+                    "1".into(),
+                    Element::Both(Value::Difference("0".into(), "1".into()))
+                ),
+                ("2".into(), Element::Both(same("0"))),
+                ("4".into(), Element::RightOnly("0".into())),
+            ])
+        );
+    }
+
+    #[test]
+    fn index_reordered() {
+        use visit_diff::{any_difference, debug_diff, Ordered};
+        let a: indexmap::IndexMap<u32, u32> =
+            vec![(1, 1), (2, 2)].into_iter().collect();
+        let b: indexmap::IndexMap<u32, u32> =
+            vec![(2, 2), (1, 1)].into_iter().collect();
+        assert_eq!(a, b);
+        assert!(!any_difference(&a, &b));
+        assert_eq!(format!("{:?}", debug_diff(&a, &b)), "{1: 1, 2: 2}");
+        assert!(any_difference(&Ordered(&a), &Ordered(&b)));
+
+        let a: indexmap::IndexSet<u32> = vec![1, 2, 3].into_iter().collect();
+        let b: indexmap::IndexSet<u32> = vec![1, 3, 2].into_iter().collect();
+        assert!(!any_difference(&a, &b));
+        assert_eq!(
+            format!("{:?}", debug_diff(Ordered(&a), Ordered(&b))),
+            "[1, DIFF { L: 2, R: 3 }, DIFF { L: 3, R: 2 }]",
+        );
+        let c: indexmap::IndexSet<u32> = vec![1, 2].into_iter().collect();
+        assert_eq!(
+            format!("{:?}", debug_diff(Ordered(&a), Ordered(&c))),
+            "[1, 2, DIFF { L: 3, R: (missing) }]",
+        );
+    }
+}
+
+#[cfg(feature = "smallvec")]
+mod smallvec {
+    debug_equivalence! {
+        small_vec => smallvec::SmallVec::<[u32; 2]>::from_slice(&[1, 2, 3]);
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+mod arrayvec {
+    debug_equivalence! {
+        array_vec => arrayvec::ArrayVec::from([1u32, 2, 3]);
+        array_string => arrayvec::ArrayString::<8>::from("hello").unwrap();
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod bytes {
+    debug_equivalence! {
+        bytes => bytes::Bytes::from_static(b"hello");
+    }
+}
+
+#[cfg(feature = "uuid")]
+mod uuid {
+    debug_equivalence! {
+        uuid => uuid::Uuid::nil();
+    }
+}
+
+#[cfg(feature = "url")]
+mod url {
+    debug_equivalence! {
+        url => url::Url::parse("https://example.com/a/b").unwrap();
+    }
+}
+
+#[cfg(feature = "ordered-float")]
+mod ordered_float {
+    debug_equivalence! {
+        ordered_float => ordered_float::OrderedFloat(1.5f64);
+        not_nan => ordered_float::NotNan::new(1.5f64).unwrap();
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono {
+    debug_equivalence! {
+        naive_date => chrono::NaiveDate::from_ymd_opt(2019, 3, 1).unwrap();
+        date_time => chrono::DateTime::from_timestamp(0, 0).unwrap();
+    }
+}
+
+#[cfg(feature = "time")]
+mod time {
+    debug_equivalence! {
+        date => time::Date::MIN;
+        offset_date_time => time::OffsetDateTime::UNIX_EPOCH;
+    }
+}
+
+#[cfg(feature = "serde_json")]
+mod serde_json {
+    use serde_json::json;
+    use visit_diff::debug_diff;
+
+    #[test]
+    fn json_object() {
+        let a = json!({"a": 1, "b": [true, null], "c": "x"});
+        let b = json!({"a": 1, "b": [false, null], "d": 2.5});
+        assert_eq!(
+            format!("{:?}", debug_diff(&a, &b)),
+            "{\"a\": 1, \"b\": [DIFF { L: true, R: false }, null], \
+             \"c\": DIFF { L: \"x\", R: (missing) }, \
+             \"d\": DIFF { L: (missing), R: 2.5 }}",
+        );
+    }
+
+    #[test]
+    fn json_kind_change() {
+        let a = json!({"a": [1], "b": 0});
+        let b = json!({"a": {"x": 1}, "b": 0});
+        assert_eq!(
+            format!("{:?}", debug_diff(&a, &b)),
+            "{\"a\": DIFF { L: [1], R: {\"x\": 1} }, \"b\": 0}",
+        );
+    }
+
+    #[test]
+    fn json_same() {
+        let a = json!({"a": [1, 2], "b": null});
+        assert!(!visit_diff::any_difference(&a, &a.clone()));
+    }
+
+    #[test]
+    fn json_float_tolerance() {
+        use visit_diff::{any_difference_with, DiffOptions};
+        let a = json!({"x": 0.1, "n": 1});
+        let b = json!({"x": 0.1000001, "n": 1});
+        let options = DiffOptions {
+            float_tolerance: 1e-3,
+            ..DiffOptions::default()
+        };
+        assert!(visit_diff::any_difference(&a, &b));
+        assert!(!any_difference_with(&a, &b, &options));
+    }
+}
+
+#[cfg(feature = "toml")]
+mod toml {
+    use visit_diff::debug_diff;
+
+    #[test]
+    fn toml_table() {
+        let a: toml::Table = "x = 1\ny = [\"a\", \"b\"]".parse().unwrap();
+        let b: toml::Table = "x = 1\ny = [\"a\", \"c\"]".parse().unwrap();
+        assert_eq!(
+            format!("{:?}", debug_diff(&a, &b)),
+            "{\"x\": 1, \"y\": [\"a\", DIFF { L: \"b\", R: \"c\" }]}",
+        );
+    }
+
+    #[test]
+    fn toml_float_tolerance() {
+        use visit_diff::{any_difference_with, DiffOptions};
+        let a: toml::Table = "x = 0.1".parse().unwrap();
+        let b: toml::Table = "x = 0.1000001".parse().unwrap();
+        let options = DiffOptions {
+            float_tolerance: 1e-3,
+            ..DiffOptions::default()
+        };
+        assert!(visit_diff::any_difference(&a, &b));
+        assert!(!any_difference_with(&a, &b, &options));
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+mod serde_yaml {
+    use visit_diff::debug_diff;
+
+    #[test]
+    fn yaml_mapping() {
+        let a: serde_yaml::Value =
+            serde_yaml::from_str("1: one\nlist: [1, 2]\nz: ~").unwrap();
+        let b: serde_yaml::Value =
+            serde_yaml::from_str("1: uno\nlist: [1, 2, 3]\nz: ~").unwrap();
+        assert_eq!(
+            format!("{:?}", debug_diff(&a, &b)),
+            "{1: DIFF { L: \"one\", R: \"uno\" }, \
+             \"list\": [1, 2, DIFF { L: (missing), R: 3 }], \"z\": null}",
+        );
+    }
+}
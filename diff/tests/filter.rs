@@ -0,0 +1,242 @@
+//! Ignoring and focusing on parts of values by path.
+
+use std::collections::{BTreeMap, BTreeSet};
+use visit_diff::filter::{self, Filter, Filtered};
+use visit_diff::record::{record_diff, Value};
+use visit_diff::{any_difference, diff_stats, Diff};
+
+#[derive(Clone, Debug, Diff)]
+struct Account {
+    name: String,
+    updated_at: u64,
+    sessions: Vec<Session>,
+    roles: BTreeMap<String, u32>,
+}
+
+#[derive(Clone, Debug, Diff)]
+struct Session {
+    id: u32,
+    token: String,
+}
+
+fn account() -> Account {
+    Account {
+        name: "ann".into(),
+        updated_at: 1,
+        sessions: vec![
+            Session {
+                id: 1,
+                token: "abc".into(),
+            },
+            Session {
+                id: 2,
+                token: "def".into(),
+            },
+        ],
+        roles: vec![("admin".into(), 1), ("user".into(), 2)]
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// The same account, touched again and given new session tokens.
+fn refreshed() -> Account {
+    let mut b = account();
+    b.updated_at = 2;
+    for s in &mut b.sessions {
+        s.token.push('!');
+    }
+    b
+}
+
+fn filtered<T: Diff>(a: &T, b: &T, filter: &Filter) -> String {
+    format!("{:?}", filter::debug_diff(a, b, filter))
+}
+
+#[test]
+fn ignore() {
+    let (a, b) = (account(), refreshed());
+    assert!(any_difference(&a, &b));
+
+    let filter = Filter::new()
+        .ignore("updated_at")
+        .ignore("sessions[*].token");
+    assert!(!any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+    assert_eq!(
+        filtered(&a.sessions, &b.sessions, &Filter::new().ignore("[1].token")),
+        "[Session { id: 1, token: DIFF { L: \"abc\", R: \"abc!\" } }, \
+         Session { id: 2 }]",
+    );
+}
+
+#[test]
+fn wildcards() {
+    let (a, b) = (account(), refreshed());
+    let (list_a, list_b) = (vec![a.clone()], vec![b.clone()]);
+
+    // `*` is one level, `**` any number.
+    let filter = Filter::new().ignore("*.updated_at").ignore("**.token");
+    assert!(!any_difference(
+        &filter.wrap(&list_a),
+        &filter.wrap(&list_b)
+    ));
+    let filter = Filter::new().ignore("updated_at").ignore("*.token");
+    assert!(any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+
+    // Globs within names.
+    let filter = Filter::new().ignore("*_at").ignore("sess*[*].tok*");
+    assert!(!any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+}
+
+#[test]
+fn focus() {
+    let (a, mut b) = (account(), refreshed());
+    b.name = "bob".into();
+    let filter = Filter::new().focus("name").focus("sessions[0].id");
+    assert_eq!(
+        filtered(&a, &b, &filter),
+        "Account { name: DIFF { L: \"ann\", R: \"bob\" }, \
+         sessions: [Session { id: 1 }, _] }",
+    );
+
+    // Ignoring still applies within a focused part.
+    let filter = Filter::new().focus("sessions").ignore("**.token");
+    assert!(!any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+}
+
+#[test]
+fn keys() {
+    let (a, mut b) = (account(), account());
+    b.roles.insert("admin".into(), 9);
+    b.roles.insert("guest".into(), 3);
+    assert_eq!(
+        filtered(&a.roles, &b.roles, &Filter::new().ignore("[admin]")),
+        "{\"admin\": _, \"guest\": DIFF { L: (missing), R: 3 }, \"user\": 2}",
+    );
+    let filter = Filter::new().ignore("[\"admin\"]").ignore("[g*]");
+    assert!(!any_difference(
+        &filter.wrap(&a.roles),
+        &filter.wrap(&b.roles)
+    ));
+
+    let a: BTreeSet<u32> = vec![1, 2].into_iter().collect();
+    let b: BTreeSet<u32> = vec![1, 3].into_iter().collect();
+    let filter = Filter::new().ignore("[2]").ignore("[3]");
+    assert!(!any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+}
+
+#[test]
+fn skipped_fields() {
+    let (a, b) = (account(), refreshed());
+    let filter = Filter::new().ignore("updated_at");
+    let stats = diff_stats(&filter.wrap(&a), &filter.wrap(&b));
+    assert_eq!(stats.fields_skipped, 1);
+}
+
+#[test]
+fn recorded() {
+    let (a, b) = (account(), refreshed());
+    let filter = Filter::new().ignore("sessions");
+    match record_diff(&filter.wrap(&a), &filter.wrap(&b)) {
+        Value::Struct(s) => {
+            assert_eq!(s.fields.len(), 4);
+            assert_eq!(s.fields[2], ("sessions", None));
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+/// `Filtered` wraps differs other than the ones built in.
+#[test]
+fn any_differ() {
+    let (a, b) = (account(), refreshed());
+    let filter = Filter::new().ignore("updated_at");
+    let out = Filtered::new(visit_diff::constant::Const(7), &filter);
+    assert!(matches!(Diff::diff(&a, &b, out), Ok(7)));
+}
+
+#[test]
+#[should_panic(expected = "malformed path pattern")]
+fn malformed() {
+    Filter::new().ignore("sessions[0");
+}
+
+#[test]
+fn unordered() {
+    let (a, mut b) = (account(), refreshed());
+    b.sessions.reverse();
+    let filter = Filter::new().ignore("**.token").ignore("updated_at");
+    assert!(any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+    let filter = filter.unordered("sessions");
+    assert!(!any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+
+    // Elements without a match are paired up in order.
+    let (a, b) = (vec![vec![1, 2, 3, 4], vec![]], vec![vec![3, 5, 1], vec![]]);
+    assert_eq!(
+        filtered(&a, &b, &Filter::new().unordered("[0]")),
+        "[[1, DIFF { L: 2, R: 5 }, 3, DIFF { L: 4, R: (missing) }], []]",
+    );
+}
+
+#[test]
+fn escaped_keys() {
+    let a: BTreeMap<&str, u32> =
+        vec![("a\"b", 1), ("c\nd", 1)].into_iter().collect();
+    let b: BTreeMap<&str, u32> =
+        vec![("a\"b", 2), ("c\nd", 2)].into_iter().collect();
+    let filter = Filter::new().ignore("[a\"b]").ignore("[c\nd]");
+    assert!(!any_difference(&filter.wrap(&a), &filter.wrap(&b)));
+}
+
+#[test]
+fn check_pattern() {
+    assert_eq!(filter::check_pattern("sessions[*].token"), Ok(()));
+    assert_eq!(
+        filter::check_pattern("sessions[0"),
+        Err("unclosed `[`".to_string()),
+    );
+}
+
+#[test]
+fn changed_paths() {
+    let (a, mut b) = (account(), refreshed());
+    b.roles.insert("guest".into(), 3);
+    b.sessions.pop();
+    let paths = filter::changed_paths(&a, &b);
+    assert_eq!(
+        paths,
+        [
+            "updated_at",
+            "sessions[0].token",
+            "sessions[1]",
+            "roles[guest]"
+        ]
+    );
+
+    // Focusing on the changed paths keeps every difference.
+    let focus = paths.iter().fold(Filter::new(), |f, p| f.focus(p));
+    assert_eq!(
+        diff_stats(&focus.wrap(&a), &focus.wrap(&b)).leaves_different,
+        diff_stats(&a, &b).leaves_different,
+    );
+
+    // Keys that can't be written bare are quoted, and still match.
+    let keys = ["a]b", "a*", "a\"]b", "ab"];
+    let a: BTreeMap<&str, u32> = keys.iter().map(|&k| (k, 1)).collect();
+    let mut b = a.clone();
+    for k in &keys[..3] {
+        b.insert(k, 2);
+    }
+    let paths = filter::changed_paths(&a, &b);
+    assert_eq!(paths, ["[\"a\\\"]b\"]", "[\"a*\"]", "[\"a]b\"]"]);
+    let focus = paths.iter().fold(Filter::new(), |f, p| f.focus(p));
+    assert_eq!(
+        filtered(&a, &b, &focus),
+        "{\"a\\\"]b\": DIFF { L: 1, R: 2 }, \"a*\": DIFF { L: 1, R: 2 }, \
+         \"a]b\": DIFF { L: 1, R: 2 }, \"ab\": _}",
+    );
+
+    let a = account();
+    assert!(filter::changed_paths(&a, &a).is_empty());
+    assert_eq!(filter::changed_paths(&1, &2), [""]);
+}
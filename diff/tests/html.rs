@@ -0,0 +1,75 @@
+//! HTML reports.
+
+#![cfg(feature = "std")]
+
+use std::collections::BTreeMap;
+use visit_diff::{html_diff, Diff};
+
+#[derive(Debug, Diff)]
+struct Config {
+    name: &'static str,
+    limits: Limits,
+    tags: Vec<&'static str>,
+    users: BTreeMap<&'static str, u32>,
+}
+
+#[derive(Debug, Diff)]
+struct Limits {
+    lo: i32,
+    hi: i32,
+}
+
+fn config() -> Config {
+    Config {
+        name: "<main>",
+        limits: Limits { lo: 0, hi: 10 },
+        tags: vec!["a", "b"],
+        users: vec![("ann", 1), ("bob", 2)].into_iter().collect(),
+    }
+}
+
+#[test]
+fn document() {
+    let html = html_diff(&config(), &config());
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.trim_end().ends_with("</html>"));
+    assert!(html.contains("<style>"));
+    // Nothing changed, so nothing is expanded or highlighted.
+    assert!(!html.contains(" open>"));
+    assert!(!html.contains("<del>"));
+    assert!(!html.contains("<ins>"));
+}
+
+#[test]
+fn changes() {
+    let mut b = config();
+    b.limits.hi = 20;
+    b.tags.push("c");
+    b.users.remove("ann");
+
+    let html = html_diff(&config(), &b);
+    assert!(html.contains(
+        "<li id=\"diff.limits.hi\"><a class=\"anchor\" \
+         href=\"#diff.limits.hi\">hi:</a> <del>10</del> <ins>20</ins></li>"
+    ));
+    assert!(html.contains(
+        "<li id=\"diff.tags_5b2_5d\"><a class=\"anchor\" \
+         href=\"#diff.tags_5b2_5d\">2:</a> <ins>&quot;c&quot;</ins></li>"
+    ));
+    assert!(html.contains("&quot;ann&quot;:</a> <del>1</del>"));
+    // Changed subtrees are expanded; unchanged ones aren't.
+    assert!(html.contains("<details open><summary data-close=\"}\">"));
+    assert!(html.contains(
+        "<li id=\"diff.name\"><a class=\"anchor\" href=\"#diff.name\">name:\
+         </a> <span class=\"same\">&quot;&lt;main&gt;&quot;</span></li>"
+    ));
+}
+
+#[test]
+fn all_different_values_are_shown_whole() {
+    let a = Limits { lo: 0, hi: 1 };
+    let b = Limits { lo: 2, hi: 3 };
+    let html = html_diff(&a, &b);
+    assert!(html.contains("<del>Limits {\n    lo: 0,"));
+    assert!(!html.contains("<details"));
+}
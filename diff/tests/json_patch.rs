@@ -0,0 +1,256 @@
+//! JSON Patch and Merge Patch output. These only run with the `json_patch`
+//! feature enabled.
+
+#![cfg(feature = "json_patch")]
+
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use visit_diff::json_patch::{debug_to_json, json_patch, merge_patch};
+use visit_diff::Diff;
+
+#[derive(Clone, Debug, Diff)]
+struct Config {
+    name: String,
+    mode: Mode,
+    limit: Option<u32>,
+    pair: (u8, char),
+    users: BTreeMap<String, User>,
+}
+
+#[derive(Clone, Debug, Diff)]
+struct User {
+    id: u64,
+    roles: BTreeSet<&'static str>,
+}
+
+#[derive(Clone, Debug, Diff)]
+enum Mode {
+    Off,
+    Fixed(u32),
+    Range { lo: i32, hi: i32 },
+}
+
+fn config() -> Config {
+    let mut users = BTreeMap::new();
+    users.insert(
+        "ann".to_string(),
+        User {
+            id: 1,
+            roles: vec!["admin"].into_iter().collect(),
+        },
+    );
+    Config {
+        name: "x\"y".to_string(),
+        mode: Mode::Range { lo: -1, hi: 1 },
+        limit: Some(3),
+        pair: (1, 'c'),
+        users,
+    }
+}
+
+#[test]
+fn debug_values() {
+    assert_eq!(
+        debug_to_json(&config()),
+        json!({
+            "name": "x\"y",
+            "mode": {"lo": -1, "hi": 1},
+            "limit": 3,
+            "pair": [1, "c"],
+            "users": {"ann": {"id": 1, "roles": ["admin"]}},
+        }),
+    );
+    assert_eq!(debug_to_json(&Mode::Off), json!("Off"));
+    assert_eq!(debug_to_json(&Mode::Fixed(2)), json!(2));
+    assert_eq!(debug_to_json(&()), json!(null));
+    assert_eq!(debug_to_json(&None::<u8>), json!(null));
+    assert_eq!(debug_to_json(&1.5f32), json!(1.5));
+    assert_eq!(debug_to_json(&f64::NAN), json!("NaN"));
+    assert_eq!(debug_to_json(&u128::MAX), json!(u128::MAX.to_string()));
+    assert_eq!(debug_to_json(&"\u{1}\n"), json!("\u{1}\n"));
+    let mut map = BTreeMap::new();
+    map.insert(3, false);
+    assert_eq!(debug_to_json(&map), json!({"3": false}));
+    // Not derived Debug output, so it's kept as it is.
+    assert_eq!(debug_to_json(&format_args!("a b")), json!("a b"),);
+}
+
+#[test]
+fn unreadable_debug_values() {
+    // Escapes too big for a `u32`, and nesting too deep to follow, are kept
+    // as they're written rather than read.
+    let escape = format_args!("\"\\u{{fffffffff}}\"");
+    assert_eq!(debug_to_json(&escape), json!(escape.to_string()));
+    let deep = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+    assert_eq!(debug_to_json(&format_args!("{}", deep)), json!(deep));
+    let mut nested = json!([]);
+    for _ in 0..100 {
+        nested = json!([nested]);
+    }
+    assert_eq!(debug_to_json(&format_args!("{}", nested)), nested);
+}
+
+#[test]
+fn no_changes() {
+    assert_eq!(json_patch(&config(), &config()), json!([]));
+    assert_eq!(merge_patch(&config(), &config()), json!({}));
+}
+
+#[test]
+fn nested_changes() {
+    let a = config();
+    let mut b = config();
+    b.mode = Mode::Range { lo: -1, hi: 2 };
+    b.limit = None;
+    b.pair.1 = 'd';
+    b.users.get_mut("ann").unwrap().roles.insert("ops");
+    b.users.insert(
+        "bob".to_string(),
+        User {
+            id: 2,
+            roles: BTreeSet::new(),
+        },
+    );
+    assert_eq!(
+        json_patch(&a, &b),
+        json!([
+            {"op": "replace", "path": "/mode/hi", "value": 2},
+            {"op": "replace", "path": "/limit", "value": null},
+            {"op": "replace", "path": "/pair/1", "value": "d"},
+            {
+                "op": "replace",
+                "path": "/users/ann/roles",
+                "value": ["admin", "ops"],
+            },
+            {
+                "op": "add",
+                "path": "/users/bob",
+                // Empty sets can't be told apart from empty maps.
+                "value": {"id": 2, "roles": {}},
+            },
+        ]),
+    );
+    assert_eq!(
+        merge_patch(&a, &b),
+        json!({
+            "mode": {"hi": 2},
+            "limit": null,
+            "pair": [1, "d"],
+            "users": {
+                "ann": {"roles": ["admin", "ops"]},
+                "bob": {"id": 2, "roles": {}},
+            },
+        }),
+    );
+}
+
+#[test]
+fn newtypes_are_transparent() {
+    assert_eq!(
+        json_patch(&Some(Mode::Fixed(1)), &Some(Mode::Fixed(2))),
+        json!([{"op": "replace", "path": "", "value": 2}]),
+    );
+}
+
+#[test]
+fn variant_change() {
+    let a = config();
+    let mut b = config();
+    b.mode = Mode::Off;
+    assert_eq!(
+        json_patch(&a, &b),
+        json!([{"op": "replace", "path": "/mode", "value": "Off"}]),
+    );
+    // Merge patches have to remove the old members of an object explicitly.
+    let mut c = config();
+    c.mode = Mode::Range { lo: 5, hi: 1 };
+    assert_eq!(
+        merge_patch(&Mode::Range { lo: 1, hi: 1 }, &Mode::Fixed(1)),
+        json!(1),
+    );
+    assert_eq!(merge_patch(&b, &c), json!({"mode": {"lo": 5, "hi": 1}}));
+}
+
+#[test]
+fn sequences() {
+    assert_eq!(
+        json_patch(&vec![1, 2, 3, 4], &vec![1, 5]),
+        json!([
+            {"op": "replace", "path": "/1", "value": 5},
+            {"op": "remove", "path": "/3"},
+            {"op": "remove", "path": "/2"},
+        ]),
+    );
+    assert_eq!(
+        json_patch(&vec![1], &vec![1, 2]),
+        json!([{"op": "add", "path": "/1", "value": 2}]),
+    );
+    assert_eq!(merge_patch(&vec![1], &vec![1, 2]), json!([1, 2]));
+}
+
+#[test]
+fn null_values() {
+    let a = json!({"a": [1]});
+    let b = json!({"a": [1, null], "b": null});
+    assert_eq!(
+        json_patch(&a, &b),
+        json!([
+            {"op": "add", "path": "/a/1", "value": null},
+            {"op": "add", "path": "/b", "value": null},
+        ]),
+    );
+    // A member that's added as `null` can't be told apart from one that's
+    // removed.
+    assert_eq!(merge_patch(&a, &b), json!({"a": [1, null], "b": null}));
+}
+
+#[test]
+fn moves_and_escaping() {
+    let a: BTreeMap<&str, u32> =
+        vec![("a/b", 1), ("c~", 2), ("d", 3)].into_iter().collect();
+    let b: BTreeMap<&str, u32> =
+        vec![("c~", 2), ("d", 4), ("e", 1)].into_iter().collect();
+    assert_eq!(
+        json_patch(&a, &b),
+        json!([
+            {"op": "move", "from": "/a~1b", "path": "/e"},
+            {"op": "replace", "path": "/d", "value": 4},
+        ]),
+    );
+    assert_eq!(merge_patch(&a, &b), json!({"a/b": null, "d": 4, "e": 1}));
+}
+
+#[test]
+fn redacted_fields() {
+    #[derive(Clone, Debug, Diff)]
+    struct Login {
+        user: &'static str,
+        #[diff(redact)]
+        password: &'static str,
+    }
+    let login = Login {
+        user: "ann",
+        password: "hunter2",
+    };
+    let mut users = BTreeMap::new();
+    users.insert("ann", login.clone());
+    let patches = [
+        json_patch(&vec![], &vec![login.clone()]),
+        json_patch(&vec![login.clone()], &vec![]),
+        json_patch(&BTreeMap::new(), &users),
+        merge_patch(&BTreeMap::new(), &users),
+        json_patch(&BTreeSet::new(), &vec!["ann"].into_iter().collect()),
+        json_patch(&(1, login.clone()), &(2, login)),
+    ];
+    for patch in &patches {
+        assert!(!patch.to_string().contains("hunter2"), "{}", patch);
+    }
+    assert_eq!(
+        patches[0],
+        json!([{
+            "op": "add",
+            "path": "/0",
+            "value": {"user": "ann", "password": "<redacted>"},
+        }]),
+    );
+}
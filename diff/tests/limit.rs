@@ -0,0 +1,248 @@
+//! Limiting how much of a diff is reported.
+
+use std::collections::BTreeMap;
+use visit_diff::limit::{self, Limited, Limits};
+use visit_diff::record::{record_diff, Value};
+use visit_diff::{all_different, any_difference, debug_diff, Diff};
+
+#[derive(Debug, Diff)]
+struct Outer {
+    name: String,
+    inner: Inner,
+    list: Vec<u32>,
+    map: BTreeMap<u32, u32>,
+}
+
+#[derive(Debug, Diff)]
+struct Inner {
+    a: u32,
+    b: (u32, u32),
+}
+
+fn outer(n: u32) -> Outer {
+    Outer {
+        name: "x".repeat(n as usize),
+        inner: Inner { a: n, b: (n, n) },
+        list: (0..n).collect(),
+        map: (0..n).map(|i| (i, i)).collect(),
+    }
+}
+
+fn limited<T: Diff>(a: &T, b: &T, limits: Limits) -> String {
+    format!("{:?}", limit::debug_diff(a, b, limits))
+}
+
+#[test]
+fn unlimited_is_unchanged() {
+    let (a, b) = (outer(3), outer(5));
+    assert_eq!(
+        limited(&a, &b, Limits::default()),
+        format!("{:?}", debug_diff(&a, &b)),
+    );
+}
+
+#[test]
+fn max_depth() {
+    let limits = Limits {
+        max_depth: 1,
+        ..Limits::default()
+    };
+    let (a, mut b) = (outer(1), outer(1));
+    b.inner.a = 2;
+    assert_eq!(
+        limited(&a, &b, limits),
+        "Outer { name: \"x\", inner: DIFF { L: Inner { … }, R: Inner { … } }, \
+         list: […], map: {…} }",
+    );
+
+    let limits = Limits {
+        max_depth: 0,
+        ..Limits::default()
+    };
+    assert_eq!(limited(&a, &a, limits), "Outer { … }");
+}
+
+#[test]
+fn max_elements() {
+    let limits = Limits {
+        max_elements: 2,
+        ..Limits::default()
+    };
+    let (a, b) = (outer(1), outer(5));
+    assert_eq!(
+        limited(&a.list, &b.list, limits),
+        "[0, DIFF { L: (missing), R: 1 }, \
+         DIFF { L: … 3 more elements, R: … 3 more elements }]",
+    );
+    assert_eq!(
+        limited(&a.map, &b.map, limits),
+        "{0: 0, 1: DIFF { L: (missing), R: 1 }, \
+         …: DIFF { L: 3 more entries, R: 3 more entries }}",
+    );
+    // Values shown whole are limited too.
+    assert_eq!(
+        limited(&Some(&a.list), &None, limits),
+        "DIFF { L: Some([0]), R: None }",
+    );
+    assert_eq!(
+        limited(&None, &Some(&b.list), limits),
+        "DIFF { L: None, R: Some([0, 1, … 3 more elements]) }",
+    );
+}
+
+#[test]
+fn max_differences() {
+    let limits = Limits {
+        max_differences: 2,
+        ..Limits::default()
+    };
+    let a = vec![(0, 0), (1, 1), (2, 2), (3, 3)];
+    let b = vec![(0, 0), (1, 9), (9, 2), (9, 9)];
+    assert_eq!(
+        limited(&a, &b, limits),
+        "[(0, 0), (1, DIFF { L: 1, R: 9 }), (DIFF { L: 2, R: 9 }, 2), \
+         DIFF { L: … 1 more element, R: … 1 more element }]",
+    );
+
+    // The budget is shared out depth-first.
+    let a = vec![(5, 5, 5), (0, 0, 0), (1, 1, 1)];
+    let b = vec![(5, 5, 5), (0, 9, 9), (1, 9, 9)];
+    let limits = Limits {
+        max_differences: 3,
+        ..limits
+    };
+    assert_eq!(
+        limited(&a, &b, limits),
+        "[(5, 5, 5), (0, DIFF { L: 0, R: 9 }, DIFF { L: 0, R: 9 }), \
+         (1, DIFF { L: 1, R: 9 }, DIFF { L: … 1 more field, R: … 1 more field })]",
+    );
+
+    let (a, mut b) = (outer(1), outer(1));
+    b.name = "y".into();
+    b.inner.a = 9;
+    let limits = Limits {
+        max_differences: 1,
+        ..limits
+    };
+    assert_eq!(
+        limited(&a, &b, limits),
+        "Outer { name: DIFF { L: \"x\", R: \"y\" }, \
+         …: DIFF { L: 1 more field, R: 1 more field }, …: 2 more fields }",
+    );
+}
+
+/// Leaving parts out doesn't change whether the values differ.
+#[test]
+fn left_out_parts_count() {
+    for limits in &[
+        Limits {
+            max_elements: 3,
+            ..Limits::default()
+        },
+        Limits {
+            max_differences: 0,
+            ..Limits::default()
+        },
+    ] {
+        let wrap = |v| limits.wrap(v);
+        let (a, b) = (vec![1, 2, 3, 4, 5], vec![1, 2, 3, 4, 6]);
+        assert!(any_difference(&wrap(&a), &wrap(&b)));
+        assert!(!all_different(&wrap(&a), &wrap(&b)));
+        assert!(!any_difference(&wrap(&a), &wrap(&a)));
+        let (a, b) = (vec![1, 2, 3, 4], vec![5, 6, 7, 8]);
+        assert!(all_different(&wrap(&a), &wrap(&b)));
+        let (a, b) = (vec![1, 2, 3, 4, 5], vec![5, 6, 7, 4, 6]);
+        assert!(!all_different(&wrap(&a), &wrap(&b)));
+    }
+
+    // Too deep to take apart, but still compared.
+    let limits = Limits {
+        max_depth: 0,
+        ..Limits::default()
+    };
+    let (a, b) = (vec![vec![1]], vec![vec![2]]);
+    assert!(any_difference(&limits.wrap(&a), &limits.wrap(&b)));
+    assert!(!any_difference(&limits.wrap(&a), &limits.wrap(&a)));
+}
+
+#[test]
+fn max_text() {
+    let limits = Limits {
+        max_text: 4,
+        ..Limits::default()
+    };
+    let (a, b) = (outer(2), outer(10));
+    assert_eq!(
+        limited(&a.name, &b.name, limits),
+        "DIFF { L: \"xx\", R: \"xxx… }",
+    );
+}
+
+/// `Limited` wraps differs other than the ones built in.
+#[test]
+fn any_differ() {
+    let limits = Limits {
+        max_elements: 1,
+        ..Limits::default()
+    };
+    let (a, b) = (vec![1, 2, 3], vec![1, 5, 6]);
+    let out = Limited::new(visit_diff::constant::Const(7), limits);
+    assert!(matches!(Diff::diff(&a, &b, out), Ok(7)));
+}
+
+#[test]
+fn thousands() {
+    let limits = Limits {
+        max_elements: 0,
+        ..Limits::default()
+    };
+    let a: Vec<u32> = (0..1_234_567).collect();
+    assert_eq!(limited(&a, &a, limits), "[… 1,234,567 more elements]");
+}
+
+/// Limits of 0 leave only a summary.
+#[test]
+fn zero_limits() {
+    let limits = Limits {
+        max_differences: 0,
+        ..Limits::default()
+    };
+    assert_eq!(limited(&(1, 2), &(3, 4), limits), "DIFF { L: …, R: … }");
+    assert_eq!(limited(&1, &2, limits), "DIFF { L: …, R: … }");
+    assert_eq!(limited(&(1, 2), &(1, 2), limits), "(1, 2)");
+    assert_eq!(
+        limited(&(1, 2), &(1, 3), limits),
+        "(1, DIFF { L: … 1 more field, R: … 1 more field })",
+    );
+    let (a, b) = (outer(1), outer(2));
+    assert!(any_difference(&limits.wrap(&a), &limits.wrap(&b)));
+    assert!(!any_difference(&limits.wrap(&a), &limits.wrap(&a)));
+
+    let limits = Limits {
+        max_elements: 0,
+        ..Limits::default()
+    };
+    let (a, b) = (outer(1), outer(2));
+    assert_eq!(limited(&a.map, &a.map, limits), "{…: 1 more entry}");
+    assert_eq!(
+        limited(&a.map, &b.map, limits),
+        "{…: DIFF { L: 1 more entry, R: 1 more entry }, …: 1 more entry}",
+    );
+    assert_eq!(
+        limited(&a.list, &b.list, limits),
+        "[DIFF { L: … 1 more element, R: … 1 more element }, … 1 more element]",
+    );
+}
+
+#[test]
+fn recorded() {
+    let limits = Limits {
+        max_elements: 1,
+        ..Limits::default()
+    };
+    let (a, b) = (vec![1, 2, 3], vec![1, 5, 6]);
+    match record_diff(&limits.wrap(&a), &limits.wrap(&b)) {
+        Value::Sequence(elements) => assert_eq!(elements.len(), 2),
+        other => panic!("{:?}", other),
+    }
+}
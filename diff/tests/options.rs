@@ -0,0 +1,165 @@
+//! Diffing with `DiffOptions`.
+
+use visit_diff::filter::Filter;
+use visit_diff::limit::Limits;
+use visit_diff::record::{record_diff, record_diff_with, Value};
+use visit_diff::{
+    all_different_with, any_difference, any_difference_with, debug_diff,
+    debug_diff_with, Diff, DiffOptions, Differ,
+};
+
+#[derive(Clone, Debug, Diff)]
+struct Sample {
+    label: String,
+    taken_at: u64,
+    readings: Vec<f64>,
+    probe: Probe,
+}
+
+/// Compares only the significant digits of its `scale`, if the options are
+/// tolerant of floating point error.
+#[derive(Clone, Debug)]
+struct Probe {
+    scale: f32,
+}
+
+impl Diff for Probe {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        if out.options().float_tolerance > 0.0 {
+            Diff::diff(&(a.scale as u32), &(b.scale as u32), out)
+        } else {
+            Diff::diff(&a.scale, &b.scale, out)
+        }
+    }
+}
+
+fn sample() -> Sample {
+    Sample {
+        label: "a".into(),
+        taken_at: 1,
+        readings: vec![0.1, 0.2, 0.3],
+        probe: Probe { scale: 2.0 },
+    }
+}
+
+#[test]
+fn default_is_unchanged() {
+    let (a, mut b) = (sample(), sample());
+    b.readings[1] = 0.25;
+    let options = DiffOptions::default();
+    assert_eq!(
+        format!("{:?}", debug_diff_with(&a, &b, &options)),
+        format!("{:?}", debug_diff(&a, &b)),
+    );
+    assert_eq!(record_diff_with(&a, &b, &options), record_diff(&a, &b));
+}
+
+#[test]
+fn float_tolerance() {
+    let (a, mut b) = (sample(), sample());
+    b.readings[0] += 1e-12;
+    assert!(any_difference(&a, &b));
+
+    let options = DiffOptions {
+        float_tolerance: 1e-9,
+        ..DiffOptions::default()
+    };
+    assert!(!any_difference_with(&a, &b, &options));
+    b.readings[2] = 0.4;
+    assert_eq!(
+        format!("{:?}", debug_diff_with(&a.readings, &b.readings, &options)),
+        "[0.1, 0.2, DIFF { L: 0.3, R: 0.4 }]",
+    );
+    assert!(any_difference_with(&0.0f32, &f32::NAN, &options));
+    assert!(!any_difference_with(
+        &f64::INFINITY,
+        &f64::INFINITY,
+        &options
+    ));
+}
+
+/// Hand-written impls nested anywhere see the options.
+#[test]
+fn context() {
+    let (a, mut b) = (sample(), sample());
+    b.probe.scale = 2.5;
+    let nested = |options: &DiffOptions| {
+        any_difference_with(&vec![a.clone()], &vec![b.clone()], options)
+    };
+    assert!(nested(&DiffOptions::default()));
+    assert!(!nested(&DiffOptions {
+        float_tolerance: 0.001,
+        ..DiffOptions::default()
+    }));
+}
+
+#[test]
+fn filter_and_limits() {
+    let (a, mut b) = (sample(), sample());
+    b.label = "b".into();
+    b.taken_at = 2;
+    b.readings.push(0.4);
+    let options = DiffOptions {
+        filter: Filter::new().ignore("taken_at"),
+        limits: Limits {
+            max_elements: 1,
+            ..Limits::default()
+        },
+        ..DiffOptions::default()
+    };
+    assert_eq!(
+        format!("{:?}", debug_diff_with(&a, &b, &options)),
+        "Sample { label: DIFF { L: \"a\", R: \"b\" }, \
+         readings: [0.1, DIFF { L: … 1 more element, R: … 1 more element }, \
+         … 2 more elements], probe: 2.0 }",
+    );
+    assert!(!all_different_with(&a, &b, &options));
+    match record_diff_with(&a, &b, &options) {
+        Value::Struct(s) => assert_eq!(s.fields[1], ("taken_at", None)),
+        other => panic!("{:?}", other),
+    }
+}
+
+/// Limits only cut the report short; what counts as a difference is decided
+/// by the other options, everywhere in the values.
+#[test]
+fn limits_dont_decide() {
+    let options = DiffOptions {
+        float_tolerance: 0.5,
+        limits: Limits {
+            max_depth: 1,
+            ..Limits::default()
+        },
+        ..DiffOptions::default()
+    };
+    assert!(!any_difference_with(&1.0, &1.1, &options));
+    let (a, b) = (vec![vec![1.0]], vec![vec![1.1]]);
+    assert!(!any_difference_with(&a, &b, &options));
+    assert_eq!(format!("{:?}", debug_diff_with(&a, &b, &options)), "[[…]]");
+    let options = DiffOptions {
+        float_tolerance: 0.0,
+        filter: Filter::new().ignore("[0][0]"),
+        ..options
+    };
+    assert_eq!(format!("{:?}", debug_diff_with(&a, &b, &options)), "[[…]]");
+
+    let options = DiffOptions {
+        limits: Limits {
+            max_elements: 3,
+            max_differences: 0,
+            ..Limits::default()
+        },
+        ..DiffOptions::default()
+    };
+    let (a, b) = (vec![1, 2, 3, 4, 5], vec![1, 2, 3, 4, 6]);
+    assert!(any_difference_with(&a, &b, &options));
+    assert!(!all_different_with(&a, &b, &options));
+    assert!(all_different_with(
+        &vec![1, 2, 3, 4],
+        &vec![5, 6, 7, 8],
+        &options
+    ));
+}
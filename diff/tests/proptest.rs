@@ -0,0 +1,127 @@
+//! Property test helpers. These only run with the `proptest` feature enabled.
+
+#![cfg(feature = "proptest")]
+
+use proptest::prelude::*;
+use proptest::test_runner::{TestCaseError, TestError};
+use std::collections::BTreeMap;
+use visit_diff::proptest::{assert_diff_laws, check_diff_laws, failing_paths};
+use visit_diff::{prop_assert_eq_diff, Diff, Differ};
+
+#[derive(Clone, Debug, Diff, PartialEq)]
+struct Inventory {
+    owner: String,
+    items: Vec<u8>,
+    counts: BTreeMap<u8, u32>,
+    kind: Kind,
+}
+
+#[derive(Clone, Debug, Diff, PartialEq)]
+enum Kind {
+    Empty,
+    Boxed(u8, bool),
+}
+
+fn inventory() -> impl Strategy<Value = Inventory> {
+    let kind = prop_oneof![
+        Just(Kind::Empty),
+        (any::<u8>(), any::<bool>()).prop_map(|(n, b)| Kind::Boxed(n, b)),
+    ];
+    (
+        "[a-c]{0,2}",
+        prop::collection::vec(0..4u8, 0..4),
+        prop::collection::btree_map(0..4u8, 0..3u32, 0..3),
+        kind,
+    )
+        .prop_map(|(owner, items, counts, kind)| Inventory {
+            owner,
+            items,
+            counts,
+            kind,
+        })
+}
+
+/// A name whose `PartialEq` ignores case, but whose `Diff` doesn't.
+#[derive(Clone, Debug)]
+struct Name(String);
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_lowercase() == other.0.to_lowercase()
+    }
+}
+
+impl Diff for Name {
+    fn diff<D>(a: &Self, b: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        Diff::diff(&a.0, &b.0, out)
+    }
+}
+
+#[derive(Clone, Debug, Diff, PartialEq)]
+struct Tagged {
+    id: u8,
+    name: Name,
+}
+
+proptest! {
+    #[test]
+    fn clones_are_equal(a in inventory()) {
+        prop_assert_eq_diff!(a.clone(), a);
+    }
+}
+
+#[test]
+fn assertion_message() {
+    let check = |a: &Inventory, b: &Inventory| -> Result<(), TestCaseError> {
+        prop_assert_eq_diff!(a, b, "for owner {}", a.owner);
+        Ok(())
+    };
+    let a = Inventory {
+        owner: "ann".into(),
+        items: vec![1, 2],
+        counts: BTreeMap::new(),
+        kind: Kind::Empty,
+    };
+    let mut b = a.clone();
+    b.items[1] = 3;
+    b.kind = Kind::Boxed(1, true);
+    assert!(check(&a, &a).is_ok());
+
+    let message = check(&a, &b).unwrap_err().to_string();
+    assert!(
+        message.contains(
+            "assertion failed: `(left == right)`\nfor owner ann\n\
+             changed: items[1], kind\ndifference:\nInventory {"
+        ),
+        "{}",
+        message
+    );
+    assert!(message.contains("R: 3,"), "{}", message);
+}
+
+#[test]
+fn laws_hold() {
+    assert!(check_diff_laws(inventory()).is_ok());
+    assert_diff_laws::<(u8, Option<bool>, Vec<i16>)>();
+    assert_diff_laws::<BTreeMap<u8, String>>();
+}
+
+#[test]
+fn laws_broken() {
+    let tagged = (0..3u8, "[aA]").prop_map(|(id, name)| Tagged {
+        id,
+        name: Name(name),
+    });
+    let error = check_diff_laws(tagged).unwrap_err();
+    match &error {
+        TestError::Fail(why, (a, b)) => {
+            assert!(why.message().contains("values are equal"), "{}", why);
+            assert_eq!(a, b);
+        }
+        TestError::Abort(why) => panic!("aborted: {}", why),
+    }
+    assert_eq!(failing_paths(&error).unwrap(), ["name"]);
+}
@@ -0,0 +1,145 @@
+//! The serde bridge. These only run with the `serde` feature enabled.
+
+#![cfg(feature = "serde")]
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use visit_diff::record::{record_diff, Value};
+use visit_diff::serde::SerdeDiff;
+use visit_diff::{debug_diff, Diff};
+
+#[derive(Clone, Debug, Diff, Serialize)]
+struct Config {
+    name: String,
+    retries: Option<u32>,
+    mode: Mode,
+    limits: (u8, u8),
+    tags: Vec<&'static str>,
+}
+
+#[derive(Clone, Debug, Diff, Serialize)]
+enum Mode {
+    Off,
+    Fixed(u32),
+    Range { lo: u32, hi: u32 },
+}
+
+fn config() -> Config {
+    Config {
+        name: "x".to_string(),
+        retries: Some(3),
+        mode: Mode::Range { lo: 1, hi: 2 },
+        limits: (1, 2),
+        tags: vec!["a", "b"],
+    }
+}
+
+#[test]
+fn same_debug_as_derived() {
+    let a = config();
+    assert_eq!(
+        format!("{:?}", debug_diff(&SerdeDiff(&a), &SerdeDiff(&a))),
+        format!("{:?}", debug_diff(&a, &a)),
+    );
+}
+
+#[test]
+fn same_record_as_derived() {
+    let a = config();
+    let mut b = config();
+    b.retries = None;
+    b.mode = Mode::Range { lo: 1, hi: 5 };
+    b.limits.0 = 0;
+    b.tags.push("c");
+    assert_eq!(
+        record_diff(&SerdeDiff(&a), &SerdeDiff(&b)),
+        record_diff(&a, &b),
+    );
+
+    b.mode = Mode::Fixed(1);
+    assert_eq!(
+        record_diff(&SerdeDiff(&a), &SerdeDiff(&b)),
+        record_diff(&a, &b),
+    );
+
+    b.mode = Mode::Off;
+    assert_eq!(
+        record_diff(&SerdeDiff(&a), &SerdeDiff(&b)),
+        record_diff(&a, &b),
+    );
+}
+
+#[test]
+fn maps() {
+    let a: BTreeMap<&str, u32> =
+        vec![("a", 1), ("b", 2), ("d", 0)].into_iter().collect();
+    let b: BTreeMap<&str, u32> =
+        vec![("b", 3), ("c", 4), ("d", 0)].into_iter().collect();
+    // Entries are reported in left-hand order, then right-only entries.
+    assert_eq!(
+        format!("{:?}", debug_diff(&SerdeDiff(&a), &SerdeDiff(&b))),
+        "{\"a\": DIFF { L: 1, R: (missing) }, \
+         \"b\": DIFF { L: 2, R: 3 }, \"d\": 0, \
+         \"c\": DIFF { L: (missing), R: 4 }}",
+    );
+}
+
+#[test]
+fn skipped_fields() {
+    #[derive(Debug, Serialize)]
+    struct Sparse {
+        a: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        b: Option<u32>,
+    }
+
+    let a = SerdeDiff(Sparse { a: 1, b: None });
+    let b = SerdeDiff(Sparse { a: 1, b: Some(2) });
+    assert!(visit_diff::all_different(&a, &b));
+    assert!(!visit_diff::any_difference(&a, &a));
+}
+
+#[test]
+fn newtypes() {
+    #[derive(Debug, Serialize)]
+    struct Meters(u32);
+
+    assert_eq!(
+        record_diff(&SerdeDiff(Meters(1)), &SerdeDiff(Meters(2))),
+        Value::Newtype(
+            "Meters",
+            Box::new(Value::Difference("1".into(), "2".into())),
+        ),
+    );
+}
+
+#[test]
+fn float_tolerance() {
+    use visit_diff::{any_difference_with, DiffOptions};
+
+    #[derive(Debug, Diff, Serialize)]
+    struct Reading {
+        value: f64,
+        scale: f32,
+    }
+
+    let a = Reading {
+        value: 0.1,
+        scale: 1.0,
+    };
+    let b = Reading {
+        value: 0.1000001,
+        scale: 1.0001,
+    };
+    let options = DiffOptions {
+        float_tolerance: 1e-3,
+        ..DiffOptions::default()
+    };
+    assert!(!any_difference_with(&a, &b, &options));
+    assert!(!any_difference_with(
+        &SerdeDiff(&a),
+        &SerdeDiff(&b),
+        &options
+    ));
+    assert!(visit_diff::any_difference(&SerdeDiff(&a), &SerdeDiff(&b)));
+}
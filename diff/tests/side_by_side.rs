@@ -0,0 +1,124 @@
+//! Side-by-side text output.
+
+#![cfg(feature = "std")]
+
+use std::collections::BTreeMap;
+use visit_diff::{side_by_side_diff, Diff};
+
+#[derive(Debug, Diff)]
+struct Config {
+    name: &'static str,
+    tags: Vec<&'static str>,
+    users: BTreeMap<&'static str, (u32, bool)>,
+    nested: Option<Limits>,
+}
+
+#[derive(Debug, Diff)]
+struct Limits {
+    lo: i32,
+    hi: i32,
+}
+
+#[test]
+fn nested() {
+    let a = Config {
+        name: "a very long name that will not fit",
+        tags: vec!["w", "x", "y", "z"],
+        users: vec![("ann", (1, true)), ("bob", (2, false))]
+            .into_iter()
+            .collect(),
+        nested: Some(Limits { lo: 0, hi: 1 }),
+    };
+    let b = Config {
+        name: "short",
+        tags: vec!["x", "q", "z", "v"],
+        users: vec![("ann", (1, false)), ("cat", (3, true))]
+            .into_iter()
+            .collect(),
+        nested: None,
+    };
+    assert_eq!(
+        side_by_side_diff(&a, &b, 60),
+        "\
+Config {                       Config {
+    name: \"a very long name… |     name: \"short\",
+    tags: [                        tags: [
+        \"w\",                 <
+        \"x\",                           \"x\",
+        \"y\",                 |         \"q\",
+        \"z\",                           \"z\",
+                             >         \"v\",
+    ],                             ],
+    users: {                       users: {
+        \"ann\": (                       \"ann\": (
+            1,                             1,
+            true,            |             false,
+        ),                             ),
+        \"bob\": (             <
+            2,               <
+            false,           <
+        ),                   <
+                             >         \"cat\": (
+                             >             3,
+                             >             true,
+                             >         ),
+    },                             },
+    nested: Some(            |     nested: None,
+        Limits {             <
+            lo: 0,           <
+            hi: 1,           <
+        },                   <
+    ),                       <
+}                              }
+"
+    );
+}
+
+#[test]
+fn same() {
+    let a = Limits { lo: 0, hi: 1 };
+    assert_eq!(
+        side_by_side_diff(&a, &a, 23),
+        "\
+Limits {     Limits {
+    lo: 0,       lo: 0,
+    hi: 1,       hi: 1,
+}            }
+"
+    );
+}
+
+#[test]
+fn narrow() {
+    assert_eq!(side_by_side_diff(&12345, &12346, 9), "12… | 12…\n");
+    assert_eq!(side_by_side_diff(&"ab", &"ab", 0), "…   …\n");
+}
+
+#[test]
+fn mostly_same() {
+    let a = vec![Limits { lo: 1, hi: 2 }, Limits { lo: 3, hi: 4 }];
+    let b = vec![
+        Limits { lo: 7, hi: 8 },
+        Limits { lo: 1, hi: 2 },
+        Limits { lo: 3, hi: 5 },
+    ];
+    assert_eq!(
+        side_by_side_diff(&a, &b, 40),
+        "\
+[                    [
+                   >     Limits {
+                   >         lo: 7,
+                   >         hi: 8,
+                   >     },
+    Limits {             Limits {
+        lo: 1,               lo: 1,
+        hi: 2,               hi: 2,
+    },                   },
+    Limits {             Limits {
+        lo: 3,               lo: 3,
+        hi: 4,     |         hi: 5,
+    },                   },
+]                    ]
+"
+    );
+}
@@ -0,0 +1,105 @@
+//! Similarity scores.
+
+use std::collections::BTreeMap;
+use visit_diff::{edit_similarity, similarity, Diff};
+
+#[derive(Debug, Diff)]
+struct Record {
+    name: &'static str,
+    tags: Vec<&'static str>,
+    attrs: BTreeMap<&'static str, (u32, u32)>,
+}
+
+fn record() -> Record {
+    Record {
+        name: "widget",
+        tags: vec!["a", "b", "c"],
+        attrs: vec![("x", (1, 2)), ("y", (3, 4))].into_iter().collect(),
+    }
+}
+
+#[test]
+fn identical() {
+    assert_eq!(similarity(&record(), &record()), 1.0);
+    assert_eq!(edit_similarity(&record(), &record()), 1.0);
+    assert_eq!(similarity(&(), &()), 1.0);
+    assert_eq!(similarity(&Vec::<u8>::new(), &vec![]), 1.0);
+}
+
+#[test]
+fn weighted_by_leaves() {
+    let mut b = record();
+    b.name = "gadget";
+    // 1 name + 3 tags + 4 attribute numbers.
+    assert_eq!(similarity(&record(), &b), 7.0 / 8.0);
+
+    // A missing entry counts as many leaves as it contains.
+    b.attrs.remove("y");
+    assert_eq!(similarity(&record(), &b), 5.0 / 8.0);
+
+    assert_eq!(similarity(&1, &2), 0.0);
+    assert_eq!(similarity(&Some(1), &None), 0.0);
+    assert_eq!(similarity(&vec![1], &vec![2, 3]), 0.0);
+}
+
+#[test]
+fn edit_distance() {
+    // Three edits, out of the nine characters of `"sitting"` with its quotes.
+    let score = edit_similarity(&"kitten", &"sitting");
+    assert!((score - 2.0 / 3.0).abs() < 1e-9, "{}", score);
+    assert!(
+        edit_similarity(&"kitten", &"sitting")
+            > edit_similarity(&"kitten", &"puppy")
+    );
+    assert_eq!(edit_similarity(&1000, &1001), 0.75);
+}
+
+#[test]
+fn sequence_alignment() {
+    let a = vec![1, 2, 3, 4];
+    let b = vec![0, 1, 2, 3, 4];
+    // In order, only the first four elements pair up, and all differ.
+    assert_eq!(similarity(&a, &b), 0.0);
+    // Aligned, the inserted 0 is the only difference.
+    assert_eq!(edit_similarity(&a, &b), 4.0 / 5.0);
+
+    // Near misses still pair up with each other.
+    let a = vec!["ann", "bob"];
+    let b = vec!["zed", "ann", "bobby"];
+    assert_eq!(similarity(&a, &b), 0.0);
+    // ann and ann, and bob and bobby (5/7 alike), out of three elements.
+    let score = edit_similarity(&a, &b);
+    assert!((score - (1.0 + 5.0 / 7.0) / 3.0).abs() < 1e-9, "{}", score);
+}
+
+/// The intended use: picking the closest candidate.
+#[test]
+fn closest_match() {
+    let actual = Record {
+        name: "widgets",
+        tags: vec!["b", "c"],
+        ..record()
+    };
+    let candidates = [
+        Record {
+            name: "sprocket",
+            ..record()
+        },
+        record(),
+        Record {
+            attrs: BTreeMap::new(),
+            ..record()
+        },
+    ];
+    let best = candidates
+        .iter()
+        .enumerate()
+        .max_by(|(_, x), (_, y)| {
+            edit_similarity(&actual, *x)
+                .partial_cmp(&edit_similarity(&actual, *y))
+                .unwrap()
+        })
+        .unwrap()
+        .0;
+    assert_eq!(best, 1);
+}
@@ -0,0 +1,155 @@
+//! Snapshot testing.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::{env, fs};
+use visit_diff::assert_snapshot_diff;
+use visit_diff::snapshot::{assert_snapshot, Snapshot, UPDATE_VAR};
+use visit_diff::{Diff, Redacted};
+
+#[derive(Clone, Debug, Diff)]
+struct Config {
+    name: &'static str,
+    ports: Vec<u16>,
+    limits: BTreeMap<&'static str, u32>,
+    tags: BTreeSet<&'static str>,
+    owner: Option<(u32, &'static str)>,
+    token: Redacted<&'static str>,
+    mode: Mode,
+}
+
+#[derive(Clone, Debug, Diff)]
+enum Mode {
+    Fast,
+    Careful { retries: u8 },
+}
+
+fn config() -> Config {
+    Config {
+        name: "web",
+        ports: vec![80, 443],
+        limits: vec![("cpu", 2), ("memory", 512)].into_iter().collect(),
+        tags: vec!["a", "b"].into_iter().collect(),
+        owner: Some((7, "ann")),
+        token: Redacted("hunter2"),
+        mode: Mode::Careful { retries: 3 },
+    }
+}
+
+/// A directory of its own for each test, so they can run at once.
+fn scratch(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!(
+        "visit_diff-snapshot-{}-{}",
+        std::process::id(),
+        test
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn stored_snapshot() {
+    assert_snapshot_diff!(config(), "config");
+}
+
+#[test]
+fn rendering() {
+    let rendered = Snapshot::of(&config()).render();
+    assert!(!rendered.contains("hunter2"), "{}", rendered);
+    assert!(rendered.starts_with("Config {\n    name: \"web\",\n"));
+
+    // What's rendered can be read back in.
+    let empty = (Vec::<u8>::new(), BTreeSet::<u8>::new(), Mode::Fast, ());
+    for snapshot in &[Snapshot::of(&config()), Snapshot::of(&empty)] {
+        let read = Snapshot::parse(&snapshot.render());
+        assert!(!visit_diff::any_difference(&read, snapshot), "{:#?}", read);
+    }
+}
+
+/// Everything that depends on `UPDATE_VAR` is in this one test, so that
+/// setting it doesn't affect others running at the same time.
+#[test]
+fn mismatch_and_update() {
+    let dir = scratch("mismatch");
+    let check = |value: &Config| {
+        catch_unwind(AssertUnwindSafe(|| {
+            assert_snapshot(value, &dir, "config")
+        }))
+    };
+
+    // A missing snapshot fails the check, and is only stored on request.
+    let message = *check(&config()).unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("hasn't been stored"), "{}", message);
+    let path = dir.join("config.snap");
+    assert!(!path.exists());
+    env::set_var(UPDATE_VAR, "1");
+    let stored = check(&config());
+    env::remove_var(UPDATE_VAR);
+    assert!(stored.is_ok());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        Snapshot::of(&config()).render()
+    );
+    assert!(check(&config()).is_ok());
+
+    let mut changed = config();
+    changed.ports.push(8080);
+    changed.limits.insert("cpu", 4);
+    let message = *check(&changed).unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains(UPDATE_VAR), "{}", message);
+    assert!(
+        message.contains(
+            "DIFF {\n            L: (missing),\n            R: 8080,"
+        ),
+        "{}",
+        message
+    );
+    assert!(
+        message
+            .contains("\"cpu\": DIFF {\n            L: 2,\n            R: 4,"),
+        "{}",
+        message
+    );
+    // A failed check leaves the snapshot alone.
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        Snapshot::of(&config()).render()
+    );
+
+    env::set_var(UPDATE_VAR, "1");
+    let updated = check(&changed);
+    env::remove_var(UPDATE_VAR);
+    assert!(updated.is_ok());
+    assert!(check(&changed).is_ok());
+    assert!(check(&config()).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unordered_entries() {
+    let dir = scratch("unordered");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("map.snap"), "{\n    2: \"b\",\n    1: \"a\",\n}\n")
+        .unwrap();
+    fs::write(dir.join("set.snap"), "{\n    2,\n    1,\n}\n").unwrap();
+
+    let map: HashMap<u32, &str> =
+        vec![(1, "a"), (2, "b")].into_iter().collect();
+    assert_snapshot(&map, &dir, "map");
+    let set: HashSet<u32> = vec![1, 2].into_iter().collect();
+    assert_snapshot(&set, &dir, "set");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn added_field() {
+    let old = Snapshot::parse("Point {\n    x: 1,\n}\n");
+    let new = Snapshot::parse("Point {\n    x: 1,\n    y: 2,\n}\n");
+    assert_eq!(
+        format!("{:?}", visit_diff::debug_diff(&old, &new)),
+        "{x: 1, y: DIFF { L: (missing), R: 2 }}",
+    );
+}
@@ -0,0 +1,119 @@
+//! Counting differences.
+
+use std::collections::{BTreeMap, BTreeSet};
+use visit_diff::{diff_stats, Diff, DiffStats, Differ, StructDiffer};
+
+#[derive(Debug, Diff)]
+struct State {
+    tick: u64,
+    cache: Cache,
+    players: Vec<Player>,
+    scores: BTreeMap<&'static str, u32>,
+    flags: BTreeSet<&'static str>,
+}
+
+/// Something that isn't worth comparing.
+#[derive(Debug)]
+struct Cache;
+
+impl Diff for Cache {
+    fn diff<D>(_: &Self, _: &Self, out: D) -> Result<D::Ok, D::Err>
+    where
+        D: Differ,
+    {
+        let mut s = out.begin_struct("Cache");
+        s.skip_field::<u32>("hits");
+        s.end()
+    }
+}
+
+#[derive(Debug, Diff)]
+struct Player {
+    name: &'static str,
+    pos: (i32, i32),
+}
+
+fn state() -> State {
+    State {
+        tick: 1,
+        cache: Cache,
+        players: vec![
+            Player {
+                name: "ann",
+                pos: (0, 0),
+            },
+            Player {
+                name: "bob",
+                pos: (1, 1),
+            },
+        ],
+        scores: vec![("ann", 1), ("bob", 2)].into_iter().collect(),
+        flags: vec!["x"].into_iter().collect(),
+    }
+}
+
+#[test]
+fn no_changes() {
+    let stats = diff_stats(&state(), &state());
+    assert!(stats.is_empty());
+    assert_eq!(stats.leaves_compared, 10);
+    assert_eq!(stats.fields_skipped, 1);
+    assert_eq!(stats.max_depth, 4);
+    assert_eq!(stats.to_string(), "no changes");
+}
+
+#[test]
+fn changes() {
+    let mut b = state();
+    b.tick = 2;
+    b.players[1].pos.0 = 2;
+    b.players.push(Player {
+        name: "cat",
+        pos: (3, 3),
+    });
+    b.scores.remove("ann");
+    b.scores.insert("bob", 3);
+    b.scores.insert("cat", 0);
+    b.flags.insert("y");
+
+    let stats = diff_stats(&state(), &b);
+    assert_eq!(
+        stats,
+        DiffStats {
+            leaves_compared: 9,
+            leaves_different: 3,
+            // tick, players, scores, flags, players[1].pos and
+            // players[1].pos.0.
+            fields_changed: 6,
+            fields_skipped: 1,
+            // players[1].
+            elements_changed: 1,
+            elements_added: 2,
+            elements_removed: 0,
+            keys_added: 1,
+            keys_removed: 1,
+            keys_changed: 1,
+            max_depth: 4,
+        }
+    );
+    assert!(!stats.is_empty());
+    assert_eq!(
+        stats.to_string(),
+        "6 fields changed, 1 element changed, 2 elements added, 1 key added, \
+         1 key removed, 1 key changed"
+    );
+}
+
+#[test]
+fn changed_and_removed_elements() {
+    let stats = diff_stats(&vec![1, 2, 3], &vec![1, 5]);
+    assert_eq!((stats.elements_changed, stats.elements_removed), (1, 1));
+    assert_eq!(stats.to_string(), "1 element changed, 1 element removed");
+}
+
+#[test]
+fn top_level() {
+    assert_eq!(diff_stats(&1, &2).to_string(), "1 value changed");
+    assert_eq!(diff_stats(&1, &2).max_depth, 0);
+    assert_eq!(diff_stats(&Some(1), &None).to_string(), "1 value changed");
+}
@@ -27,4 +27,157 @@ debug_equivalence! {
         map.insert(1234, "there");
         map
     };
+    mutex => std::sync::Mutex::new(42u32);
+    rw_lock => std::sync::RwLock::new(42u32);
+    once_lock => std::sync::OnceLock::from(42u32);
+    weak => std::rc::Rc::downgrade(&std::rc::Rc::new(42u32));
+    path => std::path::Path::new("/a/b/c");
+    path_buf => std::path::PathBuf::from("/a/b/c");
+    os_string => std::ffi::OsString::from("hello");
+    c_string => std::ffi::CString::new("hello").unwrap();
+    system_time => std::time::UNIX_EPOCH;
+    boxed_str => Box::<str>::from("hello");
+    boxed_slice => vec![1u32, 2, 3].into_boxed_slice();
+    rc_str => std::rc::Rc::<str>::from("hello");
+    arc_slice => std::sync::Arc::<[u32]>::from(vec![1, 2, 3]);
+    cow_str => std::borrow::Cow::<str>::Borrowed("hello");
+    sorted_hashset => {
+        let mut set = std::collections::HashSet::new();
+        set.insert(12u32);
+        set.insert(1234);
+        visit_diff::Sorted(set)
+    };
+    sorted_hashmap => {
+        let mut map = std::collections::HashMap::new();
+        map.insert(12u32, "hello");
+        map.insert(1234, "there");
+        visit_diff::Sorted(map)
+    };
+    custom_hasher => {
+        let mut map = std::collections::HashMap::with_hasher(
+            std::hash::BuildHasherDefault::<
+                std::collections::hash_map::DefaultHasher,
+            >::default(),
+        );
+        map.insert(12u32, "hello");
+        map.insert(1234, "there");
+        map
+    };
+}
+
+#[test]
+fn sorted_hashmap_order() {
+    use visit_diff::record::*;
+    use visit_diff::Sorted;
+    let a: std::collections::HashMap<u32, u32> =
+        (0..100).map(|i| (i, i)).collect();
+    let mut b = a.clone();
+    b.remove(&50);
+    b.insert(200, 0);
+
+    let keys: Vec<String> = match record_diff(&Sorted(&a), &Sorted(&b)) {
+        Value::Map(entries) => entries.into_iter().map(|(k, _)| k).collect(),
+        other => panic!("expected a map, got {:?}", other),
+    };
+    let expected: Vec<String> =
+        (0..100).chain(Some(200)).map(|i| i.to_string()).collect();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn path_components() {
+    use visit_diff::debug_diff;
+    let a = std::path::Path::new("/a/b/c");
+    let b = std::path::Path::new("/a/x/c");
+    assert_eq!(
+        format!("{:?}", debug_diff(a, b)),
+        r#"["/", "a", DIFF { L: "b", R: "x" }, "c"]"#,
+    );
+}
+
+#[test]
+fn nan_shown_whole() {
+    use visit_diff::debug_diff;
+    // NaN differs from itself, but one-sided values show it only once.
+    assert_eq!(
+        format!("{:?}", debug_diff(&Some(vec![f64::NAN]), &None)),
+        "DIFF { L: Some([NaN]), R: None }",
+    );
+    assert_eq!(
+        format!("{:?}", debug_diff(&vec![1.0, f64::NAN], &vec![1.0])),
+        "[1.0, DIFF { L: NaN, R: (missing) }]",
+    );
+}
+
+#[test]
+fn mutex_distinct() {
+    use visit_diff::debug_diff;
+    let (a, b) = (std::sync::Mutex::new(42u32), std::sync::Mutex::new(42u32));
+    assert_eq!(format!("{:?}", a), format!("{:?}", debug_diff(&a, &b)));
+}
+
+#[test]
+fn mutex_itself() {
+    use visit_diff::{any_difference, debug_diff};
+    let m = std::sync::Mutex::new(42u32);
+    assert_eq!(
+        format!("{:?}", debug_diff(&m, &m)),
+        "Mutex { data: 42, poisoned: false, .. }",
+    );
+    // Held elsewhere, it's still the same as itself.
+    let _guard = m.lock().unwrap();
+    assert!(!any_difference(&m, &m));
+    let l = std::sync::RwLock::new(1u8);
+    let _write = l.write().unwrap();
+    assert!(!any_difference(&l, &l));
+}
+
+#[test]
+fn mutex_contended() {
+    use visit_diff::record::*;
+    let (a, b) = (std::sync::Mutex::new(42u32), std::sync::Mutex::new(42u32));
+    let _guard = b.lock().unwrap();
+    assert_eq!(
+        record_diff(&a, &b),
+        Value::Struct(Struct {
+            name: "Mutex",
+            fields: vec![
+                (
+                    "data",
+                    Some(Value::Difference("42".into(), "<locked>".into()))
+                ),
+                (
+                    "poisoned",
+                    Some(Value::Same("false".into(), "false".into()))
+                ),
+            ],
+        })
+    );
+}
+
+#[test]
+fn mutex_poisoned() {
+    use visit_diff::record::*;
+    let a = std::sync::Arc::new(std::sync::Mutex::new(42u32));
+    let b = std::sync::Mutex::new(42u32);
+    let a2 = a.clone();
+    std::thread::spawn(move || {
+        let _guard = a2.lock().unwrap();
+        panic!("poisoning the mutex");
+    })
+    .join()
+    .unwrap_err();
+    assert_eq!(
+        record_diff(&*a, &b),
+        Value::Struct(Struct {
+            name: "Mutex",
+            fields: vec![
+                ("data", Some(Value::Same("42".into(), "42".into()))),
+                (
+                    "poisoned",
+                    Some(Value::Difference("true".into(), "false".into(),))
+                ),
+            ],
+        })
+    );
 }
@@ -0,0 +1,36 @@
+#![cfg(feature = "std")]
+
+use std::collections::BTreeMap;
+use visit_diff::tree_diff;
+
+#[test]
+fn nested() {
+    let a: BTreeMap<&str, Vec<u32>> =
+        vec![("a", vec![1, 2]), ("b", vec![3, 4]), ("c", vec![])]
+            .into_iter()
+            .collect();
+    let b: BTreeMap<&str, Vec<u32>> =
+        vec![("a", vec![1, 2]), ("b", vec![3, 5, 6]), ("d", vec![])]
+            .into_iter()
+            .collect();
+    assert_eq!(
+        tree_diff(&a, &b),
+        "  {
+    \"a\": [1, 2],
+    \"b\": [
+      3,
+-     4,
++     5,
++     6,
+    ],
+-   \"c\": [],
++   \"d\": [],
+  }
+"
+    );
+}
+
+#[test]
+fn same() {
+    assert_eq!(tree_diff(&[1, 2], &[1, 2]), "  [1, 2]\n");
+}
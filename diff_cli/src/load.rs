@@ -0,0 +1,114 @@
+//! Loading documents in each supported format into a `serde_json::Value`.
+
+use std::io::Read;
+use std::path::Path;
+
+use serde_json::Value;
+
+/// A supported document format.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+    Ron,
+}
+
+impl Format {
+    /// Looks up a format by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "ron" => Some(Format::Ron),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format of a file from its extension.
+    fn from_path(path: &str) -> Option<Self> {
+        Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| Format::from_name(&ext.to_ascii_lowercase()))
+    }
+}
+
+/// Reads and parses the document at `path`, which is stdin if it's `-`.
+///
+/// If `format` is `None`, it's guessed from the file extension.
+pub fn load(path: &str, format: Option<Format>) -> Result<Value, String> {
+    let format =
+        format.or_else(|| Format::from_path(path)).ok_or_else(|| {
+            format!("can't tell the format of {}; use --format", path)
+        })?;
+
+    let text = if path == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        std::fs::read_to_string(path)
+    }
+    .map_err(|e| format!("{}: {}", path, e))?;
+
+    parse(&text, format).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Parses `text` as a document in `format`.
+pub fn parse(text: &str, format: Format) -> Result<Value, String> {
+    match format {
+        Format::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+        Format::Toml => text
+            .parse::<toml::Table>()
+            .map(|t| from_toml(toml::Value::Table(t)))
+            .map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+        Format::Ron => ron::from_str::<ron::Value>(text)
+            .map(from_ron)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Converts a TOML value by hand, since going through serde would turn
+/// datetimes into single-entry tables.
+fn from_toml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(x) => Value::String(x),
+        toml::Value::Integer(x) => x.into(),
+        toml::Value::Float(x) => x.into(),
+        toml::Value::Boolean(x) => x.into(),
+        toml::Value::Datetime(x) => Value::String(x.to_string()),
+        toml::Value::Array(xs) => xs.into_iter().map(from_toml).collect(),
+        toml::Value::Table(t) => Value::Object(
+            t.into_iter().map(|(k, v)| (k, from_toml(v))).collect(),
+        ),
+    }
+}
+
+/// Converts a RON value by hand, since `serde_json` can't deserialize RON's
+/// anonymous structs directly. Map keys that aren't strings are written in
+/// RON syntax.
+fn from_ron(value: ron::Value) -> Value {
+    match value {
+        ron::Value::Bool(x) => x.into(),
+        ron::Value::Char(x) => Value::String(x.to_string()),
+        ron::Value::Number(ron::Number::Integer(x)) => x.into(),
+        ron::Value::Number(ron::Number::Float(x)) => x.get().into(),
+        ron::Value::String(x) => Value::String(x),
+        ron::Value::Option(None) | ron::Value::Unit => Value::Null,
+        ron::Value::Option(Some(x)) => from_ron(*x),
+        ron::Value::Seq(xs) => xs.into_iter().map(from_ron).collect(),
+        ron::Value::Map(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let k = match k {
+                        ron::Value::String(k) => k,
+                        k => ron::to_string(&k).unwrap_or_default(),
+                    };
+                    (k, from_ron(v))
+                })
+                .collect(),
+        ),
+    }
+}
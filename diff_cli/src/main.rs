@@ -0,0 +1,240 @@
+//! `visit-diff`: structural diffs of JSON, TOML, YAML and RON documents.
+//!
+//! Both documents are loaded into a `serde_json::Value`, whatever their
+//! format, and compared using `visit_diff`. Like `diff(1)`, the exit status is
+//! 0 if the documents are the same, 1 if they differ, and 2 if something went
+//! wrong.
+
+// The Differ traits split `?Sized` from the trait bounds on their generic
+// methods, and impls of them follow suit.
+#![allow(clippy::multiple_bound_locations)]
+
+mod load;
+mod pointer;
+
+use std::io::IsTerminal;
+
+use load::Format;
+use visit_diff::filter::{self, Filter};
+use visit_diff::json_patch;
+
+const USAGE: &str = "\
+usage: visit-diff [OPTIONS] <LEFT> <RIGHT>
+
+Compares two documents structurally. Either file may be `-` for stdin.
+
+options:
+  -f, --format <FORMAT>   json, toml, yaml or ron; by default, this is
+                          guessed from each file's extension
+  -o, --output <OUTPUT>   tree (the default), paths (a JSON Pointer for
+                          each change, marked ~, - or +), patch (a JSON
+                          Patch) or merge-patch (a JSON Merge Patch)
+      --ignore <PATH>     leaves out parts of both documents; may be repeated
+      --unordered <PATH>  compares arrays without regard to order; may be
+                          repeated
+      --color <WHEN>      auto (the default), always or never
+  -q, --quiet             prints nothing; only sets the exit status
+  -h, --help              prints this message
+
+PATHs pick out parts of documents, like `[users][0][name]`: each `[key]` is
+a key of an object or an index of an array, and keys can be quoted, as in
+`[\"a key\"]`. In an unquoted key, `*` matches any run of characters, and a
+`**` step, as in `**[id]`, matches any number of keys. PATHs can also be
+JSON Pointers, like `/users/0/name`, in which `*` matches any run of
+characters in a key and a `**` segment matches any number of keys.
+
+exit status: 0 if the documents are the same, 1 if they differ, 2 on error.
+";
+
+/// What to print.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Output {
+    /// The documents, with changed parts marked.
+    Tree,
+    /// The JSON Pointer of each change, one per line, marked `~` if it's in
+    /// both documents, `-` if it's only in the left one, and `+` if it's only
+    /// in the right one.
+    Paths,
+    /// A JSON Patch that turns the left document into the right one.
+    Patch,
+    /// A JSON Merge Patch that turns the left document into the right one.
+    MergePatch,
+}
+
+/// Command line options.
+#[derive(Debug)]
+struct Options {
+    format: Option<Format>,
+    output: Output,
+    filter: Filter,
+    color: Option<bool>,
+    quiet: bool,
+    left: String,
+    right: String,
+}
+
+impl Options {
+    /// Parses command line arguments, or returns `None` if the user asked for
+    /// help.
+    fn parse<I>(args: I) -> Result<Option<Self>, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut args = args.into_iter();
+        let mut format = None;
+        let mut output = Output::Tree;
+        let mut filter = Filter::new();
+        let mut color = None;
+        let mut quiet = false;
+        let mut files = vec![];
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next().ok_or_else(|| format!("{} needs a value", name))
+            };
+            let pattern = |path: String| {
+                let pattern = if path.starts_with('/') {
+                    pointer::to_pattern(&path)
+                } else {
+                    Ok(path.clone())
+                };
+                pattern
+                    .and_then(|p| filter::check_pattern(&p).map(|()| p))
+                    .map_err(|why| {
+                        format!("malformed path {:?}: {}", path, why)
+                    })
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-f" | "--format" => {
+                    let v = value(&arg)?;
+                    format = Some(
+                        Format::from_name(&v)
+                            .ok_or_else(|| format!("unknown format: {}", v))?,
+                    );
+                }
+                "-o" | "--output" => {
+                    output = match value(&arg)?.as_str() {
+                        "tree" => Output::Tree,
+                        "paths" => Output::Paths,
+                        "patch" => Output::Patch,
+                        "merge-patch" => Output::MergePatch,
+                        v => return Err(format!("unknown output: {}", v)),
+                    };
+                }
+                "--ignore" => filter = filter.ignore(&pattern(value(&arg)?)?),
+                "--unordered" => {
+                    filter = filter.unordered(&pattern(value(&arg)?)?)
+                }
+                "--color" => {
+                    color = match value(&arg)?.as_str() {
+                        "auto" => None,
+                        "always" => Some(true),
+                        "never" => Some(false),
+                        v => return Err(format!("unknown color mode: {}", v)),
+                    };
+                }
+                "-q" | "--quiet" => quiet = true,
+                "-" => files.push(arg),
+                _ if arg.starts_with('-') => {
+                    return Err(format!("unknown option: {}", arg))
+                }
+                _ => files.push(arg),
+            }
+        }
+
+        if files.len() != 2 {
+            return Err("expected two files to compare".to_string());
+        }
+        let right = files.pop().unwrap();
+        let left = files.pop().unwrap();
+
+        Ok(Some(Options {
+            format,
+            output,
+            filter,
+            color,
+            quiet,
+            left,
+            right,
+        }))
+    }
+}
+
+/// Compares the documents named in `opts`, returning `true` if they differ.
+fn run(opts: &Options) -> Result<bool, String> {
+    let left = load::load(&opts.left, opts.format)?;
+    let right = load::load(&opts.right, opts.format)?;
+    let (a, b) = (opts.filter.wrap(&left), opts.filter.wrap(&right));
+
+    let differ = visit_diff::any_difference(&a, &b);
+    if opts.quiet {
+        return Ok(differ);
+    }
+
+    match opts.output {
+        Output::Tree => {
+            if differ {
+                let color = opts.color.unwrap_or_else(|| {
+                    std::env::var_os("NO_COLOR").is_none()
+                        && std::io::stdout().is_terminal()
+                });
+                print_tree(&visit_diff::tree_diff(&a, &b), color);
+            }
+        }
+        Output::Paths => {
+            for path in filter::changed_paths(&a, &b) {
+                let path = pointer::from_path(&path);
+                let mark = match (left.pointer(&path), right.pointer(&path)) {
+                    (Some(_), None) => '-',
+                    (None, Some(_)) => '+',
+                    _ => '~',
+                };
+                println!("{} {}", mark, path);
+            }
+        }
+        Output::Patch | Output::MergePatch => {
+            let patch = if opts.output == Output::Patch {
+                json_patch::json_patch(&a, &b)
+            } else {
+                json_patch::merge_patch(&a, &b)
+            };
+            println!("{}", serde_json::to_string_pretty(&patch).unwrap());
+        }
+    }
+
+    Ok(differ)
+}
+
+/// Prints the output of `tree_diff`, coloring lines only in the left document
+/// red and lines only in the right document green if `color` is set.
+fn print_tree(tree: &str, color: bool) {
+    for line in tree.lines() {
+        match (color, line.chars().next()) {
+            (true, Some('-')) => println!("\x1b[31m{}\x1b[0m", line),
+            (true, Some('+')) => println!("\x1b[32m{}\x1b[0m", line),
+            _ => println!("{}", line),
+        }
+    }
+}
+
+fn main() {
+    let status = match Options::parse(std::env::args().skip(1)) {
+        Ok(None) => {
+            print!("{}", USAGE);
+            0
+        }
+        Ok(Some(opts)) => match run(&opts) {
+            Ok(differ) => differ as i32,
+            Err(e) => {
+                eprintln!("visit-diff: {}", e);
+                2
+            }
+        },
+        Err(e) => {
+            eprintln!("visit-diff: {}\n\n{}", e, USAGE);
+            2
+        }
+    };
+    std::process::exit(status);
+}
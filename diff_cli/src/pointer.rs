@@ -0,0 +1,72 @@
+//! JSON Pointers, like `/users/0/name`, which the command line accepts in
+//! place of `visit_diff::filter` paths.
+
+/// Rewrites a JSON Pointer, in which `*` matches any run of characters in a
+/// key or index and a `**` segment matches any number of them, as a filter
+/// pattern.
+pub fn to_pattern(pointer: &str) -> Result<String, String> {
+    let mut pattern = String::new();
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        if segment == "**" {
+            if !pattern.is_empty() {
+                pattern.push('.');
+            }
+            pattern.push_str("**");
+        } else if segment.contains('*') {
+            // Only unquoted keys are globs.
+            if segment.contains(']') || segment.starts_with('"') {
+                return Err(format!(
+                    "a key with `*` can't also contain `]` or start with \
+                     `\"`: {}",
+                    segment
+                ));
+            }
+            pattern.push('[');
+            pattern.push_str(&segment);
+            pattern.push(']');
+        } else {
+            pattern.push_str("[\"");
+            for c in segment.chars() {
+                if c == '\\' || c == '"' {
+                    pattern.push('\\');
+                }
+                pattern.push(c);
+            }
+            pattern.push_str("\"]");
+        }
+    }
+    Ok(pattern)
+}
+
+/// Rewrites a path listed by `visit_diff::filter::changed_paths` as a JSON
+/// Pointer. Paths within documents are made up only of keys and indices, like
+/// `[users][0]` or `["a key"]`.
+pub fn from_path(path: &str) -> String {
+    let mut pointer = String::new();
+    let mut rest = path;
+    while let Some(inner) = rest.strip_prefix('[') {
+        let mut key = String::new();
+        if let Some(quoted) = inner.strip_prefix('"') {
+            let mut chars = quoted.char_indices();
+            rest = "";
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => key.extend(chars.next().map(|(_, c)| c)),
+                    '"' if quoted[i + 1..].starts_with(']') => {
+                        rest = &quoted[i + 2..];
+                        break;
+                    }
+                    c => key.push(c),
+                }
+            }
+        } else {
+            let end = inner.find(']').unwrap_or(inner.len());
+            key.push_str(&inner[..end]);
+            rest = inner.get(end + 1..).unwrap_or("");
+        }
+        pointer.push('/');
+        pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+    }
+    pointer
+}
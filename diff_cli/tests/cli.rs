@@ -0,0 +1,144 @@
+//! Runs the `visit-diff` binary against documents written to a scratch
+//! directory.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Writes `files` into a fresh directory named after `test`, and returns its
+/// path.
+fn scratch(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "visit-diff-cli-{}-{}",
+        std::process::id(),
+        test
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, text) in files {
+        std::fs::write(dir.join(name), text).unwrap();
+    }
+    dir
+}
+
+/// Runs the binary in `dir`, returning its exit status and stdout.
+fn run(dir: &PathBuf, args: &[&str]) -> (i32, String) {
+    let out = Command::new(env!("CARGO_BIN_EXE_visit-diff"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        out.status.code().unwrap(),
+        String::from_utf8(out.stdout).unwrap(),
+    )
+}
+
+const LEFT: &str = r#"{"name": "x", "port": 80, "tags": ["a", "b"]}"#;
+const RIGHT: &str = "name = 'x'\nport = 8080\ntags = ['b', 'a']\n";
+
+#[test]
+fn same_documents() {
+    let dir = scratch("same", &[("a.json", LEFT), ("b.yaml", LEFT)]);
+    assert_eq!(run(&dir, &["a.json", "b.yaml"]), (0, String::new()));
+}
+
+#[test]
+fn tree() {
+    let dir = scratch("tree", &[("a.json", LEFT), ("b.toml", RIGHT)]);
+    assert_eq!(
+        run(&dir, &["--color", "never", "a.json", "b.toml"]),
+        (
+            1,
+            "  {\n\
+             \x20   \"name\": \"x\",\n\
+             -   \"port\": 80,\n\
+             +   \"port\": 8080,\n\
+             -   \"tags\": [\"a\", \"b\"],\n\
+             +   \"tags\": [\"b\", \"a\"],\n\
+             \x20 }\n"
+                .to_string()
+        ),
+    );
+}
+
+#[test]
+fn paths_and_unordered() {
+    let dir = scratch("paths", &[("a.json", LEFT), ("b.toml", RIGHT)]);
+    assert_eq!(
+        run(
+            &dir,
+            &["-o", "paths", "--unordered", "[tags]", "a.json", "b.toml"]
+        ),
+        (1, "~ /port\n".to_string()),
+    );
+
+    let a = r#"{"a/b": [1, 2], "c": {"d]": 0}}"#;
+    let b = r#"{"a/b": [1], "c": {"e": 0}, "f": null}"#;
+    let dir = scratch("paths-kinds", &[("a.json", a), ("b.json", b)]);
+    assert_eq!(
+        run(&dir, &["-o", "paths", "a.json", "b.json"]),
+        (1, "- /a~1b/1\n- /c/d]\n+ /c/e\n+ /f\n".to_string()),
+    );
+}
+
+#[test]
+fn patch_and_ignore() {
+    let a = r#"{"items": [{"id": 1, "at": 5}, {"id": 2}, {"id": 3}], "x": 0}"#;
+    let b = r#"{"items": [{"id": 1, "at": 6}], "y": 0}"#;
+    let dir = scratch("patch", &[("a.json", a), ("b.json", b)]);
+    let (status, out) = run(
+        &dir,
+        &[
+            "-o",
+            "patch",
+            "--ignore",
+            "[items][*][at]",
+            "a.json",
+            "b.json",
+        ],
+    );
+    assert_eq!(status, 1);
+    let patch: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        patch,
+        serde_json::json!([
+            {"op": "remove", "path": "/items/2"},
+            {"op": "remove", "path": "/items/1"},
+            {"op": "move", "from": "/x", "path": "/y"},
+        ]),
+    );
+
+    // Paths can also be JSON Pointers.
+    let args = ["-o", "patch", "--ignore", "/items/*/at", "a.json", "b.json"];
+    assert_eq!(run(&dir, &args), (status, out));
+}
+
+#[test]
+fn merge_patch() {
+    let dir = scratch("merge", &[("a.json", LEFT), ("b.toml", RIGHT)]);
+    let (status, out) = run(&dir, &["-o", "merge-patch", "a.json", "b.toml"]);
+    assert_eq!(status, 1);
+    let patch: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(patch, serde_json::json!({"port": 8080, "tags": ["b", "a"]}));
+}
+
+#[test]
+fn ron_and_quiet() {
+    let dir = scratch(
+        "ron",
+        &[("a.ron", "(name: \"x\", port: 80)"), ("b.json", LEFT)],
+    );
+    assert_eq!(run(&dir, &["-q", "a.ron", "b.json"]), (1, String::new()));
+}
+
+#[test]
+fn errors() {
+    let dir = scratch("errors", &[("a.json", LEFT), ("b.txt", LEFT)]);
+    assert_eq!(run(&dir, &["a.json", "missing.json"]).0, 2);
+    assert_eq!(run(&dir, &["a.json", "b.txt"]).0, 2);
+    assert_eq!(run(&dir, &["-f", "json", "a.json", "b.txt"]).0, 0);
+    assert_eq!(run(&dir, &["a.json"]).0, 2);
+    let bad = ["-f", "json", "--ignore", "[a", "a.json", "b.txt"];
+    assert_eq!(run(&dir, &bad).0, 2);
+    let bad = ["-f", "json", "--ignore", "/a*]", "a.json", "b.txt"];
+    assert_eq!(run(&dir, &bad).0, 2);
+}
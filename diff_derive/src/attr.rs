@@ -0,0 +1,400 @@
+//! Parsing of `#[diff(...)]` attributes.
+
+use syn::spanned::Spanned;
+
+/// How a field's values are hidden from the `Differ`, if at all.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Redact {
+    /// `#[diff(redact)]`: shown as `<redacted>`.
+    Plain,
+    /// `#[diff(redact = "hash")]`: shown as `<redacted>` and a short hash.
+    Hash,
+}
+
+/// A naming convention given by `#[diff(rename_all = "...")]`, spelled as in
+/// serde.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RenameRule {
+    /// `lowercase`
+    Lower,
+    /// `UPPERCASE`
+    Upper,
+    /// `PascalCase`
+    Pascal,
+    /// `camelCase`
+    Camel,
+    /// `snake_case`
+    Snake,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+    /// `kebab-case`
+    Kebab,
+    /// `SCREAMING-KEBAB-CASE`
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(lit: &syn::LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            other => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!("unknown `rename_all` convention `{}`", other),
+                ))
+            }
+        })
+    }
+
+    /// Renames a variant, which is assumed to be written in `PascalCase`.
+    pub fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            RenameRule::Pascal => variant.to_owned(),
+            RenameRule::Lower => variant.to_ascii_lowercase(),
+            RenameRule::Upper => variant.to_ascii_uppercase(),
+            RenameRule::Camel => {
+                let mut chars = variant.chars();
+                match chars.next() {
+                    Some(first) => {
+                        first.to_ascii_lowercase().to_string() + chars.as_str()
+                    }
+                    None => String::new(),
+                }
+            }
+            RenameRule::Snake
+            | RenameRule::ScreamingSnake
+            | RenameRule::Kebab
+            | RenameRule::ScreamingKebab => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                self.respell_snake(snake)
+            }
+        }
+    }
+
+    /// Renames a field, which is assumed to be written in `snake_case`.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_owned(),
+            RenameRule::Upper => field.to_ascii_uppercase(),
+            RenameRule::Pascal | RenameRule::Camel => {
+                let mut pascal = String::new();
+                let mut capitalize = self == RenameRule::Pascal;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            RenameRule::ScreamingSnake
+            | RenameRule::Kebab
+            | RenameRule::ScreamingKebab => {
+                self.respell_snake(field.to_owned())
+            }
+        }
+    }
+
+    /// Converts `snake`, a `snake_case` name, for the conventions that only
+    /// differ from it in case and separator.
+    fn respell_snake(self, snake: String) -> String {
+        match self {
+            RenameRule::ScreamingSnake => snake.to_ascii_uppercase(),
+            RenameRule::Kebab => snake.replace('_', "-"),
+            RenameRule::ScreamingKebab => {
+                snake.replace('_', "-").to_ascii_uppercase()
+            }
+            _ => snake,
+        }
+    }
+}
+
+/// Options given by attributes on a struct or enum.
+#[derive(Clone, Default)]
+pub struct ContainerAttrs {
+    pub name: Option<String>,
+    pub rename_all: Option<RenameRule>,
+    /// Diff the sole field in place of the struct.
+    pub transparent: bool,
+    /// The type this is a mirror of, if it's not the type to implement `Diff`
+    /// for.
+    pub remote: Option<syn::Path>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut parsed = ContainerAttrs::default();
+        for meta in diff_metas(attrs)? {
+            if meta.name() == "name" {
+                set_once(&mut parsed.name, str_value(&meta)?.value(), &meta)?;
+            } else if meta.name() == "rename_all" {
+                let rule = RenameRule::parse(&str_value(&meta)?)?;
+                set_once(&mut parsed.rename_all, rule, &meta)?;
+            } else if meta.name() == "remote" {
+                let path = str_value(&meta)?.parse()?;
+                set_once(&mut parsed.remote, path, &meta)?;
+            } else if meta.name() == "transparent" {
+                if parsed.transparent {
+                    return Err(syn::Error::new(
+                        meta.span(),
+                        "duplicate `transparent` option",
+                    ));
+                }
+                word(&meta)?;
+                parsed.transparent = true;
+            } else {
+                return Err(unknown(&meta));
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// The type name to give the `Differ`. Mirrors of remote types go by the
+    /// name of the remote type, which is what its `Debug` impl would show.
+    pub fn type_name(&self, ty: &syn::Ident) -> String {
+        let remote =
+            self.remote.as_ref().and_then(|p| p.segments.iter().last());
+        match (&self.name, remote) {
+            (Some(name), _) => name.clone(),
+            (None, Some(segment)) => unraw(&segment.ident),
+            (None, None) => unraw(ty),
+        }
+    }
+}
+
+/// Options given by attributes on an enum variant.
+#[derive(Clone, Debug, Default)]
+pub struct VariantAttrs {
+    pub rename: Option<String>,
+    /// Applies to the variant's fields.
+    pub rename_all: Option<RenameRule>,
+}
+
+impl VariantAttrs {
+    pub fn parse(variant: &syn::Variant) -> syn::Result<Self> {
+        let mut attrs = VariantAttrs::default();
+        for meta in diff_metas(&variant.attrs)? {
+            if meta.name() == "rename" {
+                set_once(&mut attrs.rename, str_value(&meta)?.value(), &meta)?;
+            } else if meta.name() == "rename_all" {
+                let rule = RenameRule::parse(&str_value(&meta)?)?;
+                set_once(&mut attrs.rename_all, rule, &meta)?;
+            } else {
+                return Err(unknown(&meta));
+            }
+        }
+        Ok(attrs)
+    }
+
+    /// The variant name to give the `Differ`, given the enum's `rename_all`
+    /// convention.
+    pub fn name(&self, v: &syn::Ident, rule: Option<RenameRule>) -> String {
+        match (&self.rename, rule) {
+            (Some(name), _) => name.clone(),
+            (None, Some(rule)) => rule.apply_to_variant(&unraw(v)),
+            (None, None) => unraw(v),
+        }
+    }
+}
+
+/// Options given by attributes on a field.
+#[derive(Clone, Default)]
+pub struct FieldAttrs {
+    pub redact: Option<Redact>,
+    pub rename: Option<String>,
+    /// A function to read the field with, for mirrors of remote types.
+    pub getter: Option<syn::Path>,
+    /// A `DiffRemote` mirror to diff the field through.
+    pub with: Option<syn::Path>,
+}
+
+impl FieldAttrs {
+    pub fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut attrs = FieldAttrs::default();
+        for meta in diff_metas(&field.attrs)? {
+            if meta.name() == "rename" {
+                if field.ident.is_none() {
+                    return Err(syn::Error::new(
+                        meta.span(),
+                        "`rename` only applies to named fields",
+                    ));
+                }
+                set_once(&mut attrs.rename, str_value(&meta)?.value(), &meta)?;
+            } else if meta.name() == "getter" {
+                let path = str_value(&meta)?.parse()?;
+                set_once(&mut attrs.getter, path, &meta)?;
+            } else if meta.name() == "with" {
+                let path = str_value(&meta)?.parse()?;
+                set_once(&mut attrs.with, path, &meta)?;
+            } else if meta.name() == "redact" {
+                let redact = match &meta {
+                    syn::Meta::Word(_) => Redact::Plain,
+                    syn::Meta::NameValue(syn::MetaNameValue {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    }) if s.value() == "hash" => Redact::Hash,
+                    _ => {
+                        return Err(syn::Error::new(
+                            meta.span(),
+                            "expected `redact` or `redact = \"hash\"`",
+                        ))
+                    }
+                };
+                set_once(&mut attrs.redact, redact, &meta)?;
+            } else {
+                return Err(unknown(&meta));
+            }
+        }
+        Ok(attrs)
+    }
+
+    /// The name of a named field to give the `Differ`, given the
+    /// `rename_all` convention of its struct or variant.
+    pub fn name(&self, f: &syn::Ident, rule: Option<RenameRule>) -> String {
+        match (&self.rename, rule) {
+            (Some(name), _) => name.clone(),
+            (None, Some(rule)) => rule.apply_to_field(&unraw(f)),
+            (None, None) => unraw(f),
+        }
+    }
+
+    /// Generates an expression giving a reference to the field `member` of the
+    /// struct that `value` refers to, using the getter if there is one.
+    pub fn access(
+        &self,
+        value: &str,
+        member: &syn::Member,
+    ) -> proc_macro2::TokenStream {
+        let value = syn::Ident::new(value, proc_macro2::Span::call_site());
+        match &self.getter {
+            Some(getter) => quote::quote! { &#getter(#value) },
+            None => quote::quote! { &#value.#member },
+        }
+    }
+
+    /// Wraps `value`, an expression giving a reference to the field's value,
+    /// as the attributes require before it's passed to the `Differ`.
+    pub fn wrap(
+        &self,
+        value: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let value = match &self.with {
+            None => value,
+            Some(mirror) => quote::quote! {
+                &::visit_diff::Remote::<#mirror, _>::new(#value)
+            },
+        };
+        match self.redact {
+            None => value,
+            Some(Redact::Plain) => quote::quote! {
+                &::visit_diff::Redacted(#value)
+            },
+            Some(Redact::Hash) => quote::quote! {
+                &::visit_diff::RedactedHash(#value)
+            },
+        }
+    }
+}
+
+/// Collects the items inside all `#[diff(...)]` attributes in `attrs`.
+fn diff_metas(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::Meta>> {
+    let mut metas = vec![];
+    for attr in attrs.iter().filter(|a| a.path.is_ident("diff")) {
+        match attr.parse_meta()? {
+            syn::Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        syn::NestedMeta::Meta(meta) => metas.push(meta),
+                        syn::NestedMeta::Literal(lit) => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                "expected an option name",
+                            ))
+                        }
+                    }
+                }
+            }
+            other => {
+                return Err(syn::Error::new(
+                    other.span(),
+                    "expected `#[diff(...)]`",
+                ))
+            }
+        }
+    }
+    Ok(metas)
+}
+
+/// Sets an option, complaining if it's been given already.
+fn set_once<T>(
+    slot: &mut Option<T>,
+    value: T,
+    meta: &syn::Meta,
+) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new(
+            meta.span(),
+            format!("duplicate `{}` option", meta.name()),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Gets the string in an option like `name = "..."`.
+fn str_value(meta: &syn::Meta) -> syn::Result<syn::LitStr> {
+    match meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(s),
+            ..
+        }) => Ok(s.clone()),
+        _ => Err(syn::Error::new(
+            meta.span(),
+            format!("expected `{} = \"...\"`", meta.name()),
+        )),
+    }
+}
+
+/// Checks that an option is given without a value, like `transparent`.
+fn word(meta: &syn::Meta) -> syn::Result<()> {
+    match meta {
+        syn::Meta::Word(_) => Ok(()),
+        _ => Err(syn::Error::new(
+            meta.span(),
+            format!("expected `{}` without a value", meta.name()),
+        )),
+    }
+}
+
+/// Spells an identifier as written, but without any `r#` prefix.
+pub fn unraw(ident: &syn::Ident) -> String {
+    let name = ident.to_string();
+    match name.strip_prefix("r#") {
+        Some(name) => name.to_owned(),
+        None => name,
+    }
+}
+
+fn unknown(meta: &syn::Meta) -> syn::Error {
+    syn::Error::new(
+        meta.span(),
+        format!("unknown `diff` option `{}`", meta.name()),
+    )
+}
@@ -3,33 +3,66 @@
 
 extern crate proc_macro;
 
+mod attr;
+
 use proc_macro::TokenStream;
-use quote::{quote, quote_spanned};
+use quote::quote_spanned;
 use std::iter::FromIterator;
-use syn;
 use syn::spanned::Spanned;
 
-#[proc_macro_derive(Diff)]
+use crate::attr::{
+    unraw, ContainerAttrs, FieldAttrs, RenameRule, VariantAttrs,
+};
+
+#[proc_macro_derive(Diff, attributes(diff))]
 pub fn diff_derive(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
 
     let name = input.ident;
+    let data = &input.data;
 
     let generics = add_trait_bounds(input.generics);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let dispatch = gen_dispatch(&name, &input.data);
+    let attrs = match ContainerAttrs::parse(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(e) => return TokenStream::from(e.to_compile_error()),
+    };
+    let dispatch = match gen_dispatch(&name, &attrs, data) {
+        Ok(dispatch) => dispatch,
+        Err(e) => return TokenStream::from(e.to_compile_error()),
+    };
 
-    let expanded = quote_spanned! {name.span()=>
-        impl #impl_generics ::visit_diff::Diff for #name #ty_generics
-        #where_clause {
-            fn diff<D>(a: &Self, b: &Self, out: D)
-                -> ::std::result::Result<D::Ok, D::Err>
-            where D: ::visit_diff::Differ
-            {
-                #dispatch
+    let expanded = match &attrs.remote {
+        None => quote_spanned! {name.span()=>
+            impl #impl_generics ::visit_diff::Diff for #name #ty_generics
+            #where_clause {
+                fn diff<D>(a: &Self, b: &Self, out: D)
+                    -> ::std::result::Result<D::Ok, D::Err>
+                where D: ::visit_diff::Differ
+                {
+                    #dispatch
+                }
             }
-        }
+        },
+        // A mirror of a type from elsewhere: diff the other type, reading it
+        // as though it were this one.
+        Some(remote) => quote_spanned! {name.span()=>
+            impl #impl_generics
+                ::visit_diff::DiffRemote<#remote #ty_generics>
+                for #name #ty_generics
+            #where_clause {
+                fn diff<D>(
+                    a: &#remote #ty_generics,
+                    b: &#remote #ty_generics,
+                    out: D,
+                ) -> ::std::result::Result<D::Ok, D::Err>
+                where D: ::visit_diff::Differ
+                {
+                    #dispatch
+                }
+            }
+        },
     };
 
     TokenStream::from(expanded)
@@ -51,12 +84,59 @@ fn add_trait_bounds(mut generics: syn::Generics) -> syn::Generics {
 
 /// Generates the "dispatcher" body of `diff`, which turns around and calls
 /// methods on the `Differ` depending on type.
-fn gen_dispatch(ty: &syn::Ident, data: &syn::Data) -> proc_macro2::TokenStream {
-    match data {
+fn gen_dispatch(
+    ty: &syn::Ident,
+    attrs: &ContainerAttrs,
+    data: &syn::Data,
+) -> syn::Result<proc_macro2::TokenStream> {
+    // Getters can only read fields of structs, and only stand in for fields
+    // of remote types that aren't public.
+    let fields: Vec<&syn::Field> = match data {
+        syn::Data::Struct(data) => data.fields.iter().collect(),
+        syn::Data::Enum(data) => {
+            data.variants.iter().flat_map(|v| v.fields.iter()).collect()
+        }
+        syn::Data::Union(_) => vec![],
+    };
+    for f in fields {
+        if FieldAttrs::parse(f)?.getter.is_none() {
+            continue;
+        }
+        let why = match data {
+            syn::Data::Struct(_) if attrs.remote.is_some() => continue,
+            syn::Data::Struct(_) => "`getter` only applies to `remote` mirrors",
+            _ => "`getter` only applies to fields of structs",
+        };
+        return Err(syn::Error::new(f.span(), why));
+    }
+
+    // Enum variants are matched by their paths in the type being diffed.
+    let path: syn::Path = match &attrs.remote {
+        Some(remote) => remote.clone(),
+        None => ty.clone().into(),
+    };
+
+    if attrs.transparent {
+        return match data {
+            syn::Data::Struct(data) => gen_transparent(ty, attrs, &data.fields),
+            _ => Err(syn::Error::new(
+                ty.span(),
+                "`transparent` only applies to structs",
+            )),
+        };
+    }
+    Ok(match data {
         syn::Data::Struct(data) => {
             match &data.fields {
-                syn::Fields::Named(fields) => gen_named_struct(ty, fields),
-                syn::Fields::Unnamed(fields) => gen_unnamed_struct(ty, fields),
+                syn::Fields::Named(fields) => {
+                    gen_named_struct(ty, attrs, fields)?
+                }
+                syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    gen_newtype(ty, attrs, &fields.unnamed[0])?
+                }
+                syn::Fields::Unnamed(fields) => {
+                    gen_unnamed_struct(ty, attrs, fields)?
+                }
                 syn::Fields::Unit => {
                     // A unit struct without fields. There is only one instance
                     // of such a type, and so we know statically that our
@@ -73,36 +153,138 @@ fn gen_dispatch(ty: &syn::Ident, data: &syn::Data) -> proc_macro2::TokenStream {
             // the corresponding match arms.
             let variants = data.variants.iter().map(|v| {
                 let name = &v.ident;
-                match &v.fields {
-                    syn::Fields::Named(fields) => {
-                        gen_named_variant(ty, name, fields)
-                    }
+                let v_attrs = VariantAttrs::parse(v)?;
+                let names = Names {
+                    ty: attrs.type_name(ty),
+                    variant: v_attrs.name(name, attrs.rename_all),
+                };
+                Ok(match &v.fields {
+                    syn::Fields::Named(fields) => gen_named_variant(
+                        &path,
+                        name,
+                        &names,
+                        v_attrs.rename_all,
+                        fields,
+                    )?,
                     syn::Fields::Unnamed(fields) => {
-                        gen_unnamed_variant(ty, name, fields)
+                        gen_unnamed_variant(&path, name, &names, fields)?
                     }
                     syn::Fields::Unit => {
                         // For a unit variant, we only need to check that both
                         // sides use the same variant.
                         quote_spanned! {v.span()=>
-                            (#ty::#name, #ty::#name) => out.same(a, b),
+                            (#path::#name, #path::#name) => out.same(a, b),
                         }
                     }
-                }
+                })
             });
+            let variants = variants.collect::<syn::Result<Vec<_>>>()?;
             let variants = proc_macro2::TokenStream::from_iter(variants);
 
+            // Values of different variants are passed to the Differ whole.
+            // They're shown through their Diff impls rather than their own
+            // Debug impls, so that redacted fields stay redacted, including
+            // those of other types nested in the variants.
+            let mismatch = if attrs.remote.is_some() {
+                quote_spanned! {ty.span()=>
+                    {
+                        let a = ::visit_diff::Remote::<Self, _>::new(a);
+                        let b = ::visit_diff::Remote::<Self, _>::new(b);
+                        out.difference(
+                            &::visit_diff::__Whole(&a),
+                            &::visit_diff::__Whole(&b),
+                        )
+                    }
+                }
+            } else {
+                quote_spanned! {ty.span()=>
+                    out.difference(
+                        &::visit_diff::__Whole(a),
+                        &::visit_diff::__Whole(b),
+                    )
+                }
+            };
+
+            // With only one variant, both sides always match it, and a
+            // catch-all arm would be unreachable.
+            let mismatch = if data.variants.len() == 1 {
+                quote_spanned! {ty.span()=> }
+            } else {
+                quote_spanned! {ty.span()=> _ => #mismatch, }
+            };
+
             // Now combine the match arms into a valid match expression.
             quote_spanned! {ty.span()=>
                 match (a, b) {
                     #variants
-                    _ => out.difference(a, b),
+                    #mismatch
                 }
             }
         }
         syn::Data::Union(_) => {
             unimplemented!("A `union` type cannot be meaningfully diffed")
         }
+    })
+}
+
+/// The names an enum variant is given to the `Differ` under.
+struct Names {
+    ty: String,
+    variant: String,
+}
+
+/// Generates dispatcher for a `#[diff(transparent)]` struct, which must have
+/// exactly one field, and is diffed as though it were that field.
+fn gen_transparent(
+    ty: &syn::Ident,
+    attrs: &ContainerAttrs,
+    fields: &syn::Fields,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if attrs.name.is_some() {
+        return Err(syn::Error::new(
+            ty.span(),
+            "`transparent` structs don't show their own name",
+        ));
     }
+    let field = match fields.iter().collect::<Vec<_>>().as_slice() {
+        [field] => *field,
+        _ => {
+            return Err(syn::Error::new(
+                ty.span(),
+                "`transparent` structs must have exactly one field",
+            ))
+        }
+    };
+    let member = match &field.ident {
+        Some(name) => syn::Member::Named(name.clone()),
+        None => syn::Member::Unnamed(syn::Index::from(0)),
+    };
+    let f_attrs = FieldAttrs::parse(field)?;
+    let left = f_attrs.wrap(f_attrs.access("a", &member));
+    let right = f_attrs.wrap(f_attrs.access("b", &member));
+    Ok(quote_spanned! {ty.span()=>
+        ::visit_diff::Diff::diff(#left, #right, out)
+    })
+}
+
+/// Generates dispatcher for a tuple struct with one field, which is presented
+/// to the `Differ` as a newtype.
+fn gen_newtype(
+    ty: &syn::Ident,
+    attrs: &ContainerAttrs,
+    field: &syn::Field,
+) -> syn::Result<proc_macro2::TokenStream> {
+    // Generated code will resemble:
+    //
+    //   out.diff_newtype("TypeName", &a.0, &b.0)
+    let member = syn::Member::Unnamed(syn::Index::from(0));
+    let f_attrs = FieldAttrs::parse(field)?;
+    let left = f_attrs.wrap(f_attrs.access("a", &member));
+    let right = f_attrs.wrap(f_attrs.access("b", &member));
+    let ty_name = attrs.type_name(ty);
+    Ok(quote_spanned! {ty.span()=>
+        out.diff_newtype(#ty_name, #left, #right)
+    })
 }
 
 /// Generates dispatcher for a named struct.
@@ -111,8 +293,9 @@ fn gen_dispatch(ty: &syn::Ident, data: &syn::Data) -> proc_macro2::TokenStream {
 /// the different ways we access their fields.
 fn gen_named_struct(
     ty: &syn::Ident,
+    attrs: &ContainerAttrs,
     fields: &syn::FieldsNamed,
-) -> proc_macro2::TokenStream {
+) -> syn::Result<proc_macro2::TokenStream> {
     // A traditional struct: named fields, curly braces, etc.
     // Generated code will resemble:
     //
@@ -123,19 +306,26 @@ fn gen_named_struct(
 
     // First, generate the `diff_field` statements.
     let stmts = fields.named.iter().map(|f| {
-        let name = &f.ident;
-        quote_spanned! {f.span()=>
-            s.diff_field(stringify!(#name), &a.#name, &b.#name);
-        }
+        let name = f.ident.as_ref().unwrap();
+        let f_attrs = FieldAttrs::parse(f)?;
+        let shown = f_attrs.name(name, attrs.rename_all);
+        let member = syn::Member::Named(name.clone());
+        let left = f_attrs.wrap(f_attrs.access("a", &member));
+        let right = f_attrs.wrap(f_attrs.access("b", &member));
+        Ok(quote_spanned! {f.span()=>
+            s.diff_field(#shown, #left, #right);
+        })
     });
+    let stmts = stmts.collect::<syn::Result<Vec<_>>>()?;
     let stmts = proc_macro2::TokenStream::from_iter(stmts);
 
-    quote_spanned! {ty.span()=>
+    let ty_name = attrs.type_name(ty);
+    Ok(quote_spanned! {ty.span()=>
         use ::visit_diff::StructDiffer;
-        let mut s = out.begin_struct(stringify!(#ty));
+        let mut s = out.begin_struct(#ty_name);
         #stmts
         s.end()
-    }
+    })
 }
 
 /// Generates dispatcher for a named enum variant.
@@ -143,10 +333,12 @@ fn gen_named_struct(
 /// Named structs are different from enum variants with named fields, because of
 /// the different ways we access their fields.
 fn gen_named_variant(
-    ty: &syn::Ident,
+    ty: &syn::Path,
     name: &syn::Ident,
+    names: &Names,
+    rename_all: Option<RenameRule>,
     fields: &syn::FieldsNamed,
-) -> proc_macro2::TokenStream {
+) -> syn::Result<proc_macro2::TokenStream> {
     // A variant with named fields is very much like a
     // struct, except that we have to access the fields
     // using pattern matching instead of dotted names.
@@ -163,26 +355,28 @@ fn gen_named_variant(
     //   },
     let a_pat = named_fields_pattern(fields.named.iter(), "_a");
     let b_pat = named_fields_pattern(fields.named.iter(), "_b");
-    let stmts = diff_named_fields(fields.named.iter(), "_a", "_b");
-    quote_spanned! {name.span()=>
+    let stmts = diff_named_fields(fields.named.iter(), rename_all, "_a", "_b")?;
+    let Names {
+        ty: ty_name,
+        variant,
+    } = names;
+    Ok(quote_spanned! {name.span()=>
         ( #ty::#name { #a_pat },
           #ty::#name { #b_pat }) => {
             use ::visit_diff::StructDiffer;
-            let mut s = out.begin_struct_variant(
-                stringify!(#ty),
-                stringify!(#name),
-            );
+            let mut s = out.begin_struct_variant(#ty_name, #variant);
             #stmts
             s.end()
         },
-    }
+    })
 }
 
 /// Generates dispatcher for a struct with unnamed fields (i.e. a tuple struct).
 fn gen_unnamed_struct(
     ty: &syn::Ident,
+    attrs: &ContainerAttrs,
     fields: &syn::FieldsUnnamed,
-) -> proc_macro2::TokenStream {
+) -> syn::Result<proc_macro2::TokenStream> {
     // A tuple struct: unnamed fields, parens. Generated code
     // will resemble:
     //
@@ -193,27 +387,33 @@ fn gen_unnamed_struct(
 
     // First, generate the `diff_field` statements.
     let stmts = fields.unnamed.iter().enumerate().map(|(i, f)| {
-        let index = syn::Index::from(i);
-        quote_spanned! {f.span()=>
-            s.diff_field(&a.#index, &b.#index);
-        }
+        let member = syn::Member::Unnamed(syn::Index::from(i));
+        let f_attrs = FieldAttrs::parse(f)?;
+        let left = f_attrs.wrap(f_attrs.access("a", &member));
+        let right = f_attrs.wrap(f_attrs.access("b", &member));
+        Ok(quote_spanned! {f.span()=>
+            s.diff_field(#left, #right);
+        })
     });
+    let stmts = stmts.collect::<syn::Result<Vec<_>>>()?;
     let stmts = proc_macro2::TokenStream::from_iter(stmts);
-    quote_spanned! {ty.span()=>
+    let ty_name = attrs.type_name(ty);
+    Ok(quote_spanned! {ty.span()=>
         use ::visit_diff::TupleDiffer;
-        let mut s = out.begin_tuple(stringify!(#ty));
+        let mut s = out.begin_tuple(#ty_name);
         #stmts
         s.end()
-    }
+    })
 }
 
 /// Generates dispatcher for an enum variant with unnamed fields (i.e. a tuple
 /// variant).
 fn gen_unnamed_variant(
-    ty: &syn::Ident,
+    ty: &syn::Path,
     name: &syn::Ident,
+    names: &Names,
     fields: &syn::FieldsUnnamed,
-) -> proc_macro2::TokenStream {
+) -> syn::Result<proc_macro2::TokenStream> {
     // A variant with unnamed fields is very much like a tuple struct, except
     // that we have to access the fields by pattern matching instead of using
     // dotted numbers.
@@ -229,18 +429,19 @@ fn gen_unnamed_variant(
     //   },
     let a_pat = unnamed_fields_pattern(fields.unnamed.iter(), "a");
     let b_pat = unnamed_fields_pattern(fields.unnamed.iter(), "b");
-    let stmts = diff_unnamed_fields(fields.unnamed.iter(), "a", "b");
-    quote_spanned! {name.span()=>
+    let stmts = diff_unnamed_fields(fields.unnamed.iter(), "a", "b")?;
+    let Names {
+        ty: ty_name,
+        variant,
+    } = names;
+    Ok(quote_spanned! {name.span()=>
         (#ty::#name(#a_pat), #ty::#name(#b_pat)) => {
             use ::visit_diff::TupleDiffer;
-            let mut s = out.begin_tuple_variant(
-                stringify!(#ty),
-                stringify!(#name),
-            );
+            let mut s = out.begin_tuple_variant(#ty_name, #variant);
             #stmts
             s.end()
         },
-    }
+    })
 }
 
 /// Generates a pattern match that captures named fields under new names. This
@@ -274,8 +475,8 @@ where
 {
     let pat = fields.into_iter().map(|f| {
         let name = f.ident.as_ref().unwrap();
-        let suffixed =
-            syn::Ident::new(&format!("{}{}", name, suffix), name.span());
+        let suffixed = format!("{}{}", unraw(name), suffix);
+        let suffixed = syn::Ident::new(&suffixed, name.span());
         quote_spanned! {f.span()=> #name: #suffixed, }
     });
     proc_macro2::TokenStream::from_iter(pat)
@@ -312,23 +513,33 @@ where
 /// the `StructDiffer` to each pair.
 fn diff_named_fields<'a, I>(
     fields: I,
+    rename_all: Option<RenameRule>,
     left_suffix: &str,
     right_suffix: &str,
-) -> proc_macro2::TokenStream
+) -> syn::Result<proc_macro2::TokenStream>
 where
     I: IntoIterator<Item = &'a syn::Field>,
 {
     let stmts = fields.into_iter().map(|f| {
         let name = f.ident.as_ref().unwrap();
-        let left =
-            syn::Ident::new(&format!("{}{}", name, left_suffix), name.span());
-        let right =
-            syn::Ident::new(&format!("{}{}", name, right_suffix), name.span());
-        quote_spanned! {f.span()=>
-            s.diff_field(stringify!(#name), #left, #right);
-        }
+        let attrs = FieldAttrs::parse(f)?;
+        let shown = attrs.name(name, rename_all);
+        let left = syn::Ident::new(
+            &format!("{}{}", unraw(name), left_suffix),
+            name.span(),
+        );
+        let right = syn::Ident::new(
+            &format!("{}{}", unraw(name), right_suffix),
+            name.span(),
+        );
+        let left = attrs.wrap(quote_spanned! {f.span()=> #left });
+        let right = attrs.wrap(quote_spanned! {f.span()=> #right });
+        Ok(quote_spanned! {f.span()=>
+            s.diff_field(#shown, #left, #right);
+        })
     });
-    proc_macro2::TokenStream::from_iter(stmts)
+    let stmts = stmts.collect::<syn::Result<Vec<_>>>()?;
+    Ok(proc_macro2::TokenStream::from_iter(stmts))
 }
 
 /// Given unnamed fields bound by `unnamed_fields_pattern`, generates code to
@@ -337,17 +548,21 @@ fn diff_unnamed_fields<'a, I>(
     fields: I,
     left_prefix: &str,
     right_prefix: &str,
-) -> proc_macro2::TokenStream
+) -> syn::Result<proc_macro2::TokenStream>
 where
     I: IntoIterator<Item = &'a syn::Field>,
 {
     let stmts = fields.into_iter().enumerate().map(|(i, f)| {
+        let attrs = FieldAttrs::parse(f)?;
         let left = syn::Ident::new(&format!("{}{}", left_prefix, i), f.span());
         let right =
             syn::Ident::new(&format!("{}{}", right_prefix, i), f.span());
-        quote_spanned! {f.span()=>
+        let left = attrs.wrap(quote_spanned! {f.span()=> #left });
+        let right = attrs.wrap(quote_spanned! {f.span()=> #right });
+        Ok(quote_spanned! {f.span()=>
             s.diff_field(#left, #right);
-        }
+        })
     });
-    proc_macro2::TokenStream::from_iter(stmts)
+    let stmts = stmts.collect::<syn::Result<Vec<_>>>()?;
+    Ok(proc_macro2::TokenStream::from_iter(stmts))
 }
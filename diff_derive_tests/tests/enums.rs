@@ -79,3 +79,18 @@ fn enum_different_field_tuple() {
     }));
 }
 
+
+#[test]
+fn enum_different_shape_nan() {
+    use visit_diff::record::*;
+
+    #[derive(Diff, Debug)]
+    enum Reading {
+        Value(f64, u8),
+        Missing,
+    }
+
+    let diff = record_diff(&Reading::Value(f64::NAN, 1), &Reading::Missing);
+    assert_eq!(diff, Value::Difference("Value(NaN, 1)".into(),
+                                       "Missing".into()));
+}
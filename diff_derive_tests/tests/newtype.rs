@@ -0,0 +1,71 @@
+use visit_diff::debug_diff;
+use visit_diff::record::*;
+use visit_diff::Diff;
+
+#[macro_use]
+mod common;
+
+/// newtype struct
+#[derive(Diff, Debug)]
+struct Meters(u32);
+
+#[derive(Diff, Debug)]
+#[diff(transparent)]
+struct Id(u32);
+
+#[derive(Diff, Debug)]
+#[diff(transparent)]
+struct Wrapper {
+    inner: Vec<u32>,
+}
+
+#[derive(Diff, Debug)]
+struct Holder {
+    id: Id,
+    len: Meters,
+}
+
+debug_equivalence! {
+    newtype => Meters(3);
+}
+
+#[test]
+fn newtype_struct() {
+    assert_eq!(
+        record_diff(&Meters(1), &Meters(2)),
+        Value::Newtype(
+            "Meters",
+            Box::new(Value::Difference("1".into(), "2".into()))
+        ),
+    );
+    assert_eq!(
+        format!("{:?}", debug_diff(&Meters(1), &Meters(2))),
+        "DIFF { L: Meters(1), R: Meters(2) }",
+    );
+}
+
+#[test]
+fn transparent() {
+    assert_eq!(
+        record_diff(&Id(1), &Id(2)),
+        Value::Difference("1".into(), "2".into()),
+    );
+    assert_eq!(
+        record_diff(&Wrapper { inner: vec![1] }, &Wrapper { inner: vec![1] }),
+        record_diff(&vec![1u32], &vec![1u32]),
+    );
+
+    let a = Holder {
+        id: Id(1),
+        len: Meters(5),
+    };
+    let b = Holder {
+        id: Id(2),
+        len: Meters(5),
+    };
+    // Transparent values are shown as their fields are.
+    assert_eq!(
+        format!("{:?}", debug_diff(&a, &b)),
+        "Holder { id: DIFF { L: 1, R: 2 }, len: Meters(5) }",
+    );
+}
@@ -0,0 +1,176 @@
+use visit_diff::record::*;
+use visit_diff::{
+    any_difference, any_difference_with, debug_diff, html_diff,
+    side_by_side_diff, tree_diff, Diff, DiffOptions,
+};
+
+const SECRET: &str = "hunter2";
+const OTHER: &str = "letmein";
+
+#[derive(Clone, Diff, Debug)]
+struct Login {
+    user: &'static str,
+    #[diff(redact)]
+    password: &'static str,
+}
+
+#[derive(Clone, Diff, Debug)]
+struct Token(u32, #[diff(redact = "hash")] &'static str);
+
+#[derive(Clone, Diff, Debug)]
+enum Auth {
+    Password {
+        #[diff(redact)]
+        password: &'static str,
+    },
+    Token(#[diff(redact)] &'static str),
+    Anonymous,
+}
+
+/// Has no redacted fields of its own.
+#[derive(Clone, Diff, Debug)]
+enum Session {
+    User(Login),
+    Guest,
+}
+
+fn login(password: &'static str) -> Login {
+    Login {
+        user: "ann",
+        password,
+    }
+}
+
+/// Checks that none of the ways of showing a diff mentions a secret.
+fn assert_hidden<T: Diff>(a: &T, b: &T) {
+    let shown = [
+        format!("{:?}", debug_diff(a, b)),
+        format!("{:#?}", debug_diff(a, b)),
+        format!("{:?}", record_diff(a, b)),
+        html_diff(a, b),
+        side_by_side_diff(a, b, 80),
+        tree_diff(a, b),
+    ];
+    for text in &shown {
+        assert!(!text.contains(SECRET), "{}", text);
+        assert!(!text.contains(OTHER), "{}", text);
+    }
+}
+
+#[test]
+fn named_field() {
+    assert_eq!(
+        format!("{:?}", debug_diff(&login(SECRET), &login(SECRET))),
+        "Login { user: \"ann\", password: <redacted> }",
+    );
+    assert_eq!(
+        record_diff(&login(SECRET), &login(OTHER)),
+        Value::Struct(Struct {
+            name: "Login",
+            fields: vec![
+                (
+                    "user",
+                    Some(Value::Same("\"ann\"".into(), "\"ann\"".into()))
+                ),
+                (
+                    "password",
+                    Some(Value::Difference(
+                        "<redacted>".into(),
+                        "<redacted>".into()
+                    ))
+                ),
+            ],
+        }),
+    );
+}
+
+#[test]
+fn hashed_field() {
+    let diff = format!("{:?}", debug_diff(&Token(1, SECRET), &Token(1, OTHER)));
+    assert!(
+        diff.starts_with("Token(1, DIFF { L: <redacted "),
+        "{}",
+        diff
+    );
+    // Hashes are stable, and tell different values apart.
+    let hash = |s| format!("{:?}", visit_diff::RedactedHash(s));
+    assert_eq!(hash(SECRET), hash(SECRET));
+    assert_ne!(hash(SECRET), hash(OTHER));
+    assert_eq!(hash(SECRET).len(), "<redacted 01234567>".len());
+    assert!(diff.contains(&hash(SECRET)) && diff.contains(&hash(OTHER)));
+}
+
+#[test]
+fn whole_values() {
+    // Hoisted, because every field differs.
+    let mut b = login(OTHER);
+    b.user = "bob";
+    assert_eq!(
+        format!("{:?}", debug_diff(&login(SECRET), &b)),
+        "DIFF { L: Login { user: \"ann\", password: <redacted> }, \
+         R: Login { user: \"bob\", password: <redacted> } }",
+    );
+    assert_hidden(&login(SECRET), &b);
+
+    // Only on one side.
+    assert_hidden(&vec![login(SECRET)], &vec![]);
+    assert_hidden(&None, &Some(Token(1, OTHER)));
+
+    // Different variants.
+    let password = Auth::Password { password: SECRET };
+    assert_hidden(&password, &Auth::Token(OTHER));
+    assert_hidden(&password, &Auth::Anonymous);
+    assert_hidden(&Session::User(login(SECRET)), &Session::Guest);
+    assert_eq!(
+        record_diff(&password, &Auth::Anonymous),
+        Value::Difference(
+            "Password { password: <redacted> }".into(),
+            "Anonymous".into()
+        ),
+    );
+}
+
+#[test]
+fn tree() {
+    let a = vec![login(SECRET)];
+    let mut b = a.clone();
+    b.push(Login {
+        user: "bob",
+        password: OTHER,
+    });
+    let tree = tree_diff(&a, &b);
+    assert_eq!(
+        tree,
+        "  [\n\
+        \x20   Login { user: \"ann\", password: <redacted> },\n\
+         +   Login { user: \"bob\", password: <redacted> },\n\
+        \x20 ]\n",
+    );
+    assert_hidden(&a, &b);
+}
+
+#[test]
+fn options() {
+    #[derive(Diff, Debug)]
+    struct Reading {
+        #[diff(redact)]
+        value: f64,
+        #[diff(redact = "hash")]
+        scale: f64,
+    }
+
+    let a = Reading {
+        value: 1.0,
+        scale: 2.0,
+    };
+    let b = Reading {
+        value: 1.25,
+        scale: 2.25,
+    };
+    let options = DiffOptions {
+        float_tolerance: 0.5,
+        ..DiffOptions::default()
+    };
+    assert!(any_difference(&a, &b));
+    assert!(!any_difference_with(&a, &b, &options));
+}
@@ -0,0 +1,180 @@
+use visit_diff::record::*;
+use visit_diff::{debug_diff, Diff, DiffRemote, Remote};
+
+/// Stands in for another crate, whose types can't implement `Diff`.
+mod other {
+    #[derive(Debug)]
+    pub struct Point {
+        pub x: i32,
+        y: i32,
+    }
+
+    impl Point {
+        pub fn new(x: i32, y: i32) -> Self {
+            Point { x, y }
+        }
+
+        pub fn y(&self) -> i32 {
+            self.y
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct Celsius(pub f64);
+
+    #[derive(Debug)]
+    pub enum Shape {
+        Dot(Point),
+        Line { from: Point, to: Point },
+        Empty,
+    }
+
+    #[derive(Debug)]
+    pub struct Pair<T> {
+        pub left: T,
+        pub right: T,
+    }
+
+    #[derive(Debug)]
+    pub enum Secret {
+        Key(&'static str),
+        None,
+    }
+}
+
+#[derive(Diff)]
+#[diff(remote = "other::Point")]
+#[allow(dead_code)]
+struct PointDef {
+    x: i32,
+    #[diff(getter = "other::Point::y")]
+    y: i32,
+}
+
+#[derive(Diff)]
+#[diff(remote = "other::Celsius")]
+#[allow(dead_code)]
+struct CelsiusDef(f64);
+
+#[derive(Diff)]
+#[diff(remote = "other::Shape")]
+#[allow(dead_code)]
+enum ShapeDef {
+    Dot(#[diff(with = "PointDef")] other::Point),
+    Line {
+        #[diff(with = "PointDef")]
+        from: other::Point,
+        #[diff(with = "PointDef")]
+        to: other::Point,
+    },
+    Empty,
+}
+
+#[derive(Diff)]
+#[diff(remote = "other::Pair")]
+#[allow(dead_code)]
+struct PairDef<T: std::fmt::Debug> {
+    left: T,
+    right: T,
+}
+
+#[derive(Diff)]
+#[diff(remote = "other::Secret")]
+#[allow(dead_code)]
+enum SecretDef {
+    Key(#[diff(redact)] &'static str),
+    None,
+}
+
+#[derive(Diff, Debug)]
+struct Reading {
+    #[diff(with = "CelsiusDef")]
+    temperature: other::Celsius,
+    #[diff(with = "ShapeDef")]
+    area: other::Shape,
+}
+
+fn diff<R, T>(a: &T, b: &T) -> Value
+where
+    R: DiffRemote<T>,
+    T: std::fmt::Debug,
+{
+    record_diff(&Remote::<R, T>::new(a), &Remote::new(b))
+}
+
+#[test]
+fn remote_struct() {
+    let (a, b) = (other::Point::new(1, 2), other::Point::new(1, 3));
+    assert_eq!(
+        diff::<PointDef, _>(&a, &b),
+        Value::Struct(Struct {
+            name: "Point",
+            fields: vec![
+                ("x", Some(Value::Same("1".into(), "1".into()))),
+                ("y", Some(Value::Difference("2".into(), "3".into()))),
+            ],
+        }),
+    );
+    assert_eq!(
+        diff::<CelsiusDef, _>(&other::Celsius(1.5), &other::Celsius(2.5)),
+        Value::Newtype(
+            "Celsius",
+            Box::new(Value::Difference("1.5".into(), "2.5".into()))
+        ),
+    );
+}
+
+#[test]
+fn remote_enum() {
+    let dot = |x| other::Shape::Dot(other::Point::new(x, 0));
+    assert_eq!(
+        format!(
+            "{:?}",
+            debug_diff(
+                Remote::<ShapeDef, _>::new(&dot(1)),
+                Remote::new(&dot(2))
+            )
+        ),
+        "DIFF { L: Dot(Point { x: 1, y: 0 }), R: Dot(Point { x: 2, y: 0 }) }",
+    );
+    assert_eq!(
+        diff::<ShapeDef, _>(&dot(1), &other::Shape::Empty),
+        Value::Difference("Dot(Point { x: 1, y: 0 })".into(), "Empty".into()),
+    );
+    let line = |y| other::Shape::Line {
+        from: other::Point::new(0, 0),
+        to: other::Point::new(5, y),
+    };
+    let reading = |area| Reading {
+        temperature: other::Celsius(20.0),
+        area,
+    };
+    assert_eq!(
+        format!("{:?}", debug_diff(&reading(line(1)), &reading(line(2)))),
+        "Reading { temperature: Celsius(20.0), area: Line { \
+         from: Point { x: 0, y: 0 }, \
+         to: Point { x: 5, y: DIFF { L: 1, R: 2 } } } }",
+    );
+}
+
+#[test]
+fn remote_generic() {
+    let a = other::Pair { left: 1, right: 2 };
+    let b = other::Pair { left: 1, right: 3 };
+    assert_eq!(
+        format!(
+            "{:?}",
+            debug_diff(Remote::<PairDef<i32>, _>::new(&a), Remote::new(&b))
+        ),
+        "Pair { left: 1, right: DIFF { L: 2, R: 3 } }",
+    );
+}
+
+#[test]
+fn remote_redacted() {
+    let key = other::Secret::Key("hunter2");
+    assert_eq!(
+        diff::<SecretDef, _>(&key, &other::Secret::None),
+        Value::Difference("Key(<redacted>)".into(), "None".into()),
+    );
+}
@@ -0,0 +1,117 @@
+use visit_diff::debug_diff;
+use visit_diff::record::*;
+use visit_diff::Diff;
+
+#[derive(Diff, Debug)]
+#[diff(name = "Message", rename_all = "camelCase")]
+struct RawMessage {
+    message_id: u32,
+    r#type: &'static str,
+    #[diff(rename = "body")]
+    text: &'static str,
+}
+
+#[derive(Diff, Debug)]
+#[diff(name = "Point")]
+struct P(i32, i32);
+
+#[derive(Diff, Debug)]
+#[diff(rename_all = "snake_case")]
+enum Event {
+    KeyPress(char, bool),
+    #[diff(rename_all = "kebab-case")]
+    MouseMove {
+        delta_x: i32,
+        delta_y: i32,
+    },
+    #[diff(rename = "quit")]
+    Exit,
+}
+
+#[derive(Diff, Debug)]
+enum Raw {
+    r#Match { r#ref: u8 },
+}
+
+fn message(text: &'static str) -> RawMessage {
+    RawMessage {
+        message_id: 1,
+        r#type: "note",
+        text,
+    }
+}
+
+#[test]
+fn struct_names() {
+    assert_eq!(
+        record_diff(&message("a"), &message("b")),
+        Value::Struct(Struct {
+            name: "Message",
+            fields: vec![
+                ("messageId", Some(Value::Same("1".into(), "1".into()))),
+                (
+                    "type",
+                    Some(Value::Same("\"note\"".into(), "\"note\"".into()))
+                ),
+                (
+                    "body",
+                    Some(Value::Difference("\"a\"".into(), "\"b\"".into()))
+                ),
+            ],
+        }),
+    );
+    assert_eq!(
+        format!("{:?}", debug_diff(&P(1, 2), &P(1, 3))),
+        "Point(1, DIFF { L: 2, R: 3 })",
+    );
+}
+
+#[test]
+fn variant_names() {
+    assert_eq!(
+        format!(
+            "{:?}",
+            debug_diff(
+                &Event::KeyPress('a', true),
+                &Event::KeyPress('b', true)
+            )
+        ),
+        "key_press(DIFF { L: 'a', R: 'b' }, true)",
+    );
+    let a = Event::MouseMove {
+        delta_x: 1,
+        delta_y: 2,
+    };
+    let b = Event::MouseMove {
+        delta_x: 1,
+        delta_y: 3,
+    };
+    assert_eq!(
+        format!("{:?}", debug_diff(&a, &b)),
+        "mouse_move { delta-x: 1, delta-y: DIFF { L: 2, R: 3 } }",
+    );
+    // Unit variants are passed whole, through their Debug impls.
+    assert_eq!(
+        record_diff(&Event::Exit, &Event::Exit),
+        Value::Same("Exit".into(), "Exit".into()),
+    );
+}
+
+#[test]
+fn raw_identifiers() {
+    let diff =
+        record_diff(&Raw::r#Match { r#ref: 1 }, &Raw::r#Match { r#ref: 2 });
+    assert_eq!(
+        diff,
+        Value::Enum(Enum {
+            name: "Raw",
+            variant: Variant::Struct(Struct {
+                name: "Match",
+                fields: vec![(
+                    "ref",
+                    Some(Value::Difference("1".into(), "2".into()))
+                )],
+            }),
+        }),
+    );
+}